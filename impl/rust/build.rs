@@ -0,0 +1,77 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/**
+ * Generates `fennec.h` from the `extern "C"` functions and `#[repr(C)]` types in `src/extern_c`,
+ * and `fennec_abi.h` from the opaque-handle ABI in `src/extern_c/abi.rs`.
+ * They go in `OUT_DIR`, and `tests/headers.rs` checks that the committed copies match them.
+ */
+fn main() {
+    let dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=cbindgen-abi.toml");
+    println!("cargo:rerun-if-changed=fennec_abi.map");
+    println!("cargo:rerun-if-changed=src/extern_c");
+
+    generate(
+        &dir,
+        &out,
+        "cbindgen.toml",
+        "fennec.h",
+        cbindgen::Builder::new().with_crate(&dir),
+    );
+    generate(
+        &dir,
+        &out,
+        "cbindgen-abi.toml",
+        "fennec_abi.h",
+        cbindgen::Builder::new().with_src(dir.join("src/extern_c/abi.rs")),
+    );
+
+    // The `.symver`s in abi.rs only bind to a version that the linker has been told about
+    let linux = env::var("CARGO_CFG_TARGET_OS").unwrap() == "linux";
+    if linux && env::var_os("CARGO_FEATURE_SYMBOL_VERSIONING").is_some() {
+        println!(
+            "cargo:rustc-cdylib-link-arg=-Wl,--version-script={}",
+            dir.join("fennec_abi.map").display()
+        );
+    }
+}
+
+fn generate(dir: &Path, out: &Path, config: &str, header_name: &str, builder: cbindgen::Builder) {
+    let config = cbindgen::Config::from_file(dir.join(config)).unwrap();
+    let mut header = Vec::new();
+    builder
+        .with_config(config)
+        .generate()
+        .unwrap_or_else(|e| panic!("Couldn't generate {header_name}: {e}"))
+        .write(&mut header);
+    let header = strip_stars(&String::from_utf8(header).unwrap());
+
+    // Only touch the file when it changes, so C builds that depend on it don't rebuild every time.
+    let path = out.join(header_name);
+    if fs::read_to_string(&path).ok().as_deref() != Some(&header[..]) {
+        fs::write(path, header).unwrap();
+    }
+}
+
+/**
+ * The sources use `/** */` doc comments, which cbindgen copies over along with their leading `*`s
+ */
+fn strip_stars(header: &str) -> String {
+    header
+        .lines()
+        .map(|line| match line.trim_start().strip_prefix("// *") {
+            Some(doc) => {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                format!("{indent}//{doc}")
+            }
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
@@ -0,0 +1,516 @@
+use std::{collections::HashSet, fmt::Write, fs, io, path::Path};
+
+use fennec::{
+    parse::{FennecType, ParseError},
+    schema::{self, Schema, SchemaError, SchemaType},
+};
+
+/**
+ * Turns schemas into Rust types. Meant to be run from a build script:
+ *
+ * ```no_run
+ * // build.rs
+ * let out = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("config.rs");
+ * println!("cargo:rerun-if-changed=config.fennec-schema");
+ * fennec_codegen::generate_file(&["config.fennec-schema"], &out, &Default::default()).unwrap();
+ *
+ * // src/main.rs
+ * // include!(concat!(env!("OUT_DIR"), "/config.rs"));
+ * // let config: Config = fennec::de::from_str(&text)?;
+ * ```
+ */
+pub struct Options {
+    /**
+     * The name of the type generated for the root of the document
+     */
+    pub root_name: String,
+    /**
+     * Derive serde's `Serialize` and `Deserialize`, for use with `fennec::de`
+     */
+    pub serde: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            root_name: "Config".to_string(),
+            serde: true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse(ParseError),
+    Schema(SchemaError),
+    NoInputs,
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/**
+ * Reads a `.fennec-schema` file, or infers a schema from one or more sample documents
+ */
+pub fn load_schema(inputs: &[impl AsRef<Path>]) -> Result<Schema, Error> {
+    if let [input] = inputs {
+        if input
+            .as_ref()
+            .extension()
+            .is_some_and(|ext| ext == "fennec-schema")
+        {
+            let text = fs::read_to_string(input)?;
+            return Schema::parse(&text).map_err(Error::Schema);
+        }
+    }
+
+    if inputs.is_empty() {
+        return Err(Error::NoInputs);
+    }
+
+    let mut docs = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let text = fs::read_to_string(input)?;
+        docs.push(fennec::parse_document(&text).map_err(Error::Parse)?);
+    }
+    Ok(schema::infer_documents(&docs))
+}
+
+/**
+ * `load_schema`, then `generate`, writing the code to `output`
+ */
+pub fn generate_file(
+    inputs: &[impl AsRef<Path>],
+    output: impl AsRef<Path>,
+    options: &Options,
+) -> Result<(), Error> {
+    let schema = load_schema(inputs)?;
+    fs::write(output, generate(&schema, options))?;
+    Ok(())
+}
+
+pub fn generate(schema: &Schema, options: &Options) -> String {
+    let mut generator = Generator {
+        options,
+        items: Vec::new(),
+        names: HashSet::new(),
+    };
+
+    if let SchemaType::Object { .. } = schema.ty {
+        generator.generate_struct(schema, &options.root_name);
+    } else {
+        let name = generator.unique_name(&options.root_name, "");
+        let ty = generator.type_of(schema, &name, "");
+        let mut item = docs(&schema.description, "");
+        let _ = writeln!(item, "pub type {name} = {ty};");
+        generator.items.insert(0, item);
+    }
+
+    let mut out = "// Generated by fennec-codegen, don't edit by hand.\n".to_string();
+    for item in generator.items {
+        out.push('\n');
+        out.push_str(&item);
+    }
+    out
+}
+
+struct Generator<'a> {
+    options: &'a Options,
+    items: Vec<String>,
+    names: HashSet<String>,
+}
+
+impl Generator<'_> {
+    fn derives(&self) -> &'static str {
+        if self.options.serde {
+            "#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]\n"
+        } else {
+            "#[derive(Debug, Clone, PartialEq)]\n"
+        }
+    }
+
+    /**
+     * A type name that's not taken yet, prefixed with its parent's name if it needs to be
+     */
+    fn unique_name(&mut self, hint: &str, parent: &str) -> String {
+        let base = pascal_case(hint);
+        let mut name = base.clone();
+        if self.names.contains(&name) {
+            name = format!("{parent}{base}");
+        }
+        let mut i = 2;
+        while self.names.contains(&name) {
+            name = format!("{parent}{base}{i}");
+            i += 1;
+        }
+        self.names.insert(name.clone());
+        name
+    }
+
+    /**
+     * The Rust type for the schema, generating it first if it needs a definition.
+     * Generated types are named after `hint`, or `parent` + `hint` if that's taken.
+     */
+    fn type_of(&mut self, schema: &Schema, hint: &str, parent: &str) -> String {
+        match &schema.ty {
+            SchemaType::Any => "fennec::parse::FennecType".to_string(),
+            SchemaType::Object {
+                keys,
+                additional_keys: true,
+            } if keys.is_empty() => {
+                "std::collections::HashMap<String, fennec::parse::FennecType>".to_string()
+            }
+            SchemaType::Object { .. } => {
+                let name = self.unique_name(hint, parent);
+                self.generate_struct(schema, &name);
+                name
+            }
+            SchemaType::Array { items, .. } => match items {
+                Some(items) => format!(
+                    "Vec<{}>",
+                    self.type_of(items, &format!("{hint}Item"), parent)
+                ),
+                None => "Vec<fennec::parse::FennecType>".to_string(),
+            },
+            SchemaType::String { .. } => match string_variants(schema) {
+                Some(variants) => {
+                    let name = self.unique_name(hint, parent);
+                    self.generate_string_enum(schema, &name, &variants);
+                    name
+                }
+                None => "String".to_string(),
+            },
+            SchemaType::Int(_) => "i64".to_string(),
+            SchemaType::Float(_) | SchemaType::Number(_) => "f64".to_string(),
+            SchemaType::Bool => "bool".to_string(),
+            SchemaType::Null => "()".to_string(),
+            SchemaType::DateTime => "fennec::datetime::DateTime".to_string(),
+            SchemaType::Date => "fennec::datetime::Date".to_string(),
+            SchemaType::Time => "fennec::datetime::Time".to_string(),
+            SchemaType::Duration => "std::time::Duration".to_string(),
+            SchemaType::Quantity => "fennec::quantity::Quantity".to_string(),
+            SchemaType::Union(schemas) => {
+                let not_null: Vec<_> = schemas
+                    .iter()
+                    .filter(|s| !matches!(s.ty, SchemaType::Null))
+                    .collect();
+
+                if not_null.len() < schemas.len() {
+                    let inner = match not_null.as_slice() {
+                        [] => "()".to_string(),
+                        [schema] => self.type_of(schema, hint, parent),
+                        schemas => self.generate_union(schemas, hint, parent),
+                    };
+                    return format!("Option<{inner}>");
+                }
+
+                self.generate_union(&not_null, hint, parent)
+            }
+        }
+    }
+
+    fn generate_struct(&mut self, schema: &Schema, name: &str) {
+        let SchemaType::Object {
+            keys,
+            additional_keys,
+        } = &schema.ty
+        else {
+            return;
+        };
+
+        self.names.insert(name.to_string());
+        let slot = self.items.len();
+        self.items.push(String::new());
+
+        let mut keys: Vec<_> = keys.iter().collect();
+        keys.sort_by_key(|(key, _)| *key);
+
+        let mut fields = String::new();
+        let mut defaults = String::new();
+        let mut field_names = HashSet::new();
+
+        for (key, field) in keys {
+            let mut field_name = snake_case(key);
+            let mut i = 2;
+            while field_names.contains(&field_name) {
+                field_name = format!("{}_{i}", snake_case(key));
+                i += 1;
+            }
+            field_names.insert(field_name.clone());
+
+            let ty = self.type_of(field, key, name);
+            let default = field.default.as_ref().and_then(|d| default_expr(d, &ty));
+
+            let mut attrs = Vec::new();
+            if field_name != *key {
+                attrs.push(format!("rename = {key:?}"));
+            }
+
+            let ty = if let Some(default) = default {
+                let func = format!("default_{field_name}");
+                attrs.push(format!("default = \"{name}::{func}\""));
+                let _ = writeln!(
+                    defaults,
+                    "    fn {func}() -> {ty} {{\n        {default}\n    }}"
+                );
+                ty
+            } else if field.optional || field.default.is_some() {
+                if ty == "bool" {
+                    attrs.push("default".to_string());
+                    ty
+                } else if ty.starts_with("Option<") {
+                    ty
+                } else {
+                    format!("Option<{ty}>")
+                }
+            } else {
+                ty
+            };
+
+            fields.push_str(&docs(&field.description, "    "));
+            if self.options.serde && !attrs.is_empty() {
+                let _ = writeln!(fields, "    #[serde({})]", attrs.join(", "));
+            }
+            let _ = writeln!(fields, "    pub {}: {ty},", escape_keyword(&field_name));
+        }
+
+        if *additional_keys {
+            if self.options.serde {
+                fields.push_str("    #[serde(flatten)]\n");
+            }
+            fields.push_str(
+                "    pub additional_keys: std::collections::HashMap<String, fennec::parse::FennecType>,\n",
+            );
+        }
+
+        let mut item = docs(&schema.description, "");
+        item.push_str(self.derives());
+        if self.options.serde && !additional_keys {
+            item.push_str("#[serde(deny_unknown_fields)]\n");
+        }
+        let _ = writeln!(item, "pub struct {name} {{\n{fields}}}");
+        if !defaults.is_empty() {
+            let _ = writeln!(item, "\nimpl {name} {{\n{defaults}}}");
+        }
+
+        self.items[slot] = item;
+    }
+
+    fn generate_string_enum(&mut self, schema: &Schema, name: &str, variants: &[String]) {
+        let mut item = docs(&schema.description, "");
+        item.push_str(self.derives());
+        let _ = writeln!(item, "pub enum {name} {{");
+        for variant in variants {
+            let variant_name = pascal_case(variant);
+            if self.options.serde && variant_name != *variant {
+                let _ = writeln!(item, "    #[serde(rename = {variant:?})]");
+            }
+            let _ = writeln!(item, "    {variant_name},");
+        }
+        item.push_str("}\n");
+        self.items.push(item);
+    }
+
+    fn generate_union(&mut self, schemas: &[&Schema], hint: &str, parent: &str) -> String {
+        let name = self.unique_name(hint, parent);
+        let slot = self.items.len();
+        self.items.push(String::new());
+
+        let mut item = String::new();
+        item.push_str(self.derives());
+        if self.options.serde {
+            item.push_str("#[serde(untagged)]\n");
+        }
+        let _ = writeln!(item, "pub enum {name} {{");
+        for schema in schemas {
+            let variant = pascal_case(&schema.ty.name());
+            let ty = self.type_of(schema, &format!("{hint}{variant}"), parent);
+            let _ = writeln!(item, "    {variant}({ty}),");
+        }
+        item.push_str("}\n");
+
+        self.items[slot] = item;
+        name
+    }
+}
+
+/**
+ * The values of a string `enum`, if every one of them is a string
+ */
+fn string_variants(schema: &Schema) -> Option<Vec<String>> {
+    schema
+        .allowed
+        .as_ref()?
+        .iter()
+        .map(|val| val.as_string())
+        .collect()
+}
+
+fn default_expr(default: &FennecType, ty: &str) -> Option<String> {
+    match (default, ty) {
+        (FennecType::Int(i), "i64") => Some(i.to_string()),
+        (FennecType::Int(i), "f64") => Some(format!("{:?}", *i as f64)),
+        (FennecType::Float(f), "f64") if f.is_finite() => Some(format!("{f:?}")),
+        (FennecType::Bool(b), "bool") => Some(b.to_string()),
+        (FennecType::String(str), "String") => Some(format!("{str:?}.to_string()")),
+        (FennecType::String(str), ty) if is_ident(ty) => {
+            Some(format!("{ty}::{}", pascal_case(str)))
+        }
+        _ => None,
+    }
+}
+
+fn docs(description: &Option<String>, indent: &str) -> String {
+    let mut out = String::new();
+    if let Some(description) = description {
+        for line in description.lines() {
+            let _ = writeln!(out, "{indent}/// {}", line.trim_end());
+        }
+    }
+    out
+}
+
+fn words(str: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+
+    for char in str.chars() {
+        if !char.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                out.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if char.is_ascii_uppercase() && prev_lower {
+            out.push(std::mem::take(&mut current));
+        }
+        prev_lower = char.is_ascii_lowercase() || char.is_ascii_digit();
+        current.push(char);
+    }
+    if !current.is_empty() {
+        out.push(current);
+    }
+    out
+}
+
+fn pascal_case(str: &str) -> String {
+    let mut out: String = words(str)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            let first = chars.next().map(|c| c.to_ascii_uppercase());
+            first
+                .into_iter()
+                .chain(chars.map(|c| c.to_ascii_lowercase()))
+                .collect::<String>()
+        })
+        .collect();
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, 'V');
+    }
+    if RESERVED.contains(&out.as_str()) {
+        out.push('_');
+    }
+    out
+}
+
+fn snake_case(str: &str) -> String {
+    let mut out = words(str)
+        .iter()
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    if RESERVED.contains(&out.as_str()) {
+        out.push('_');
+    }
+    out
+}
+
+fn is_ident(str: &str) -> bool {
+    str.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv", "try",
+    "typeof", "unsized", "virtual", "yield",
+];
+
+/**
+ * Keywords that can't be raw identifiers either, so names get a `_` on the end instead
+ */
+const RESERVED: &[&str] = &["self", "Self", "super", "crate"];
+
+fn escape_keyword(str: &str) -> String {
+    if KEYWORDS.contains(&str) {
+        return format!("r#{str}");
+    }
+    str.to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use fennec::schema::Schema;
+
+    use super::{generate, pascal_case, snake_case, Options};
+
+    #[test]
+    fn names() {
+        assert_eq!(pascal_case("someNest"), "SomeNest");
+        assert_eq!(pascal_case("Hello, World!"), "HelloWorld");
+        assert_eq!(snake_case("someTypedArray"), "some_typed_array");
+        assert_eq!(snake_case("max-connections"), "max_connections");
+        assert_eq!(snake_case("2fa"), "_2fa");
+    }
+
+    #[test]
+    fn without_serde() {
+        let schema = Schema::parse("keys { type { type = \"string\" } }").unwrap();
+        let code = generate(
+            &schema,
+            &Options {
+                root_name: "Settings".to_string(),
+                serde: false,
+            },
+        );
+
+        assert!(code.contains("pub struct Settings {\n    pub r#type: String,\n}"));
+        assert!(!code.contains("serde"));
+    }
+
+    #[test]
+    fn reserved_names() {
+        let schema = Schema::parse(
+            "keys {
+                self { keys { crate { type = \"int\" } } }
+                super { type = \"string\" enum [ \"self\" \"other\" ] }
+            }",
+        )
+        .unwrap();
+        let code = generate(&schema, &Options::default());
+
+        assert!(code.contains("#[serde(rename = \"self\")]\n    pub self_: Self_,"));
+        assert!(code.contains("#[serde(rename = \"crate\")]\n    pub crate_: i64,"));
+        assert!(code.contains("#[serde(rename = \"super\")]\n    pub super_: Super,"));
+        assert!(code.contains("#[serde(rename = \"self\")]\n    Self_,"));
+    }
+
+    #[test]
+    fn root_that_isnt_an_object() {
+        let schema = Schema::parse("type = \"array\" items { type = \"int\" }").unwrap();
+        let code = generate(&schema, &Options::default());
+
+        assert!(code.contains("pub type Config = Vec<i64>;"));
+    }
+}
@@ -0,0 +1,64 @@
+use std::{env, io::Write, process::ExitCode};
+
+use fennec_codegen::{generate, load_schema, Options};
+
+const USAGE: &str = "\
+usage: fennec-codegen [--name <Type>] [--no-serde] [-o <output.rs>] <input>...
+
+Generates Rust types from a single .fennec-schema file,
+or from a schema inferred from one or more sample .fennec files.";
+
+fn main() -> ExitCode {
+    let mut options = Options::default();
+    let mut output = None;
+    let mut inputs = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--name" => match args.next() {
+                Some(name) => options.root_name = name,
+                None => return usage(),
+            },
+            "-o" | "--output" => match args.next() {
+                Some(path) => output = Some(path),
+                None => return usage(),
+            },
+            "--no-serde" => options.serde = false,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ => inputs.push(arg),
+        }
+    }
+
+    if inputs.is_empty() {
+        return usage();
+    }
+
+    let schema = match load_schema(&inputs) {
+        Ok(schema) => schema,
+        Err(e) => {
+            eprintln!("fennec-codegen: {e:?}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let code = generate(&schema, &options);
+    let written = match output {
+        Some(path) => std::fs::write(path, code),
+        None => std::io::stdout().write_all(code.as_bytes()),
+    };
+
+    if let Err(e) = written {
+        eprintln!("fennec-codegen: {e}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!("{USAGE}");
+    ExitCode::FAILURE
+}
@@ -0,0 +1,72 @@
+// Generated by fennec-codegen, don't edit by hand.
+
+/// Settings for a single server process
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Server {
+    pub fallback: Option<String>,
+    /// Hostname to bind to
+    pub host: String,
+    pub id: Id,
+    pub limits: Limits,
+    pub metadata: Option<fennec::parse::FennecType>,
+    /// Which environment we're running in
+    #[serde(default = "Server::default_mode")]
+    pub mode: Mode,
+    #[serde(default = "Server::default_port")]
+    pub port: i64,
+    pub ratio: Option<f64>,
+    /// How long to wait on an upstream before giving up
+    pub timeout: Option<std::time::Duration>,
+    /// Upstreams to forward requests to
+    pub upstreams: Vec<UpstreamsItem>,
+    #[serde(default)]
+    pub verbose: bool,
+}
+
+impl Server {
+    fn default_mode() -> Mode {
+        Mode::Development
+    }
+    fn default_port() -> i64 {
+        8080
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum Id {
+    Int(i64),
+    String(String),
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Limits {
+    #[serde(rename = "max-connections")]
+    pub max_connections: i64,
+    #[serde(flatten)]
+    pub additional_keys: std::collections::HashMap<String, fennec::parse::FennecType>,
+}
+
+/// Which environment we're running in
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Mode {
+    #[serde(rename = "development")]
+    Development,
+    #[serde(rename = "production")]
+    Production,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpstreamsItem {
+    pub url: String,
+    #[serde(default = "UpstreamsItem::default_weight")]
+    pub weight: f64,
+}
+
+impl UpstreamsItem {
+    fn default_weight() -> f64 {
+        1.0
+    }
+}
@@ -0,0 +1,65 @@
+use fennec::parse::FennecType;
+use fennec::schema::Schema;
+use fennec_codegen::{generate, Options};
+
+mod server {
+    include!("expected/server.rs");
+}
+
+const SCHEMA: &str = include_str!("server.fennec-schema");
+
+const SAMPLE: &str = r#"
+host = "example.com"
+id = "primary"
+timeout = 2s500ms
+-verbose
+upstreams [
+    { url = "http://a" weight = 2.5 }
+    { url = "http://b" }
+]
+limits {
+    max-connections = 100
+    burst = 20
+}
+"#;
+
+#[test]
+fn snapshot_is_up_to_date() {
+    let schema = Schema::parse(SCHEMA).unwrap();
+    let code = generate(
+        &schema,
+        &Options {
+            root_name: "Server".to_string(),
+            ..Options::default()
+        },
+    );
+
+    assert_eq!(code, include_str!("expected/server.rs"));
+}
+
+#[test]
+fn generated_types_load_documents() {
+    let server: server::Server = fennec::de::from_str(SAMPLE).unwrap();
+
+    assert_eq!(server.host, "example.com");
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.mode, server::Mode::Development);
+    assert!(server.verbose);
+    assert_eq!(server.ratio, None);
+    assert_eq!(server.id, server::Id::String("primary".to_string()));
+    assert_eq!(server.fallback, None);
+    assert_eq!(server.timeout, Some(std::time::Duration::from_millis(2500)));
+    assert_eq!(server.upstreams.len(), 2);
+    assert_eq!(server.upstreams[1].weight, 1.0);
+    assert_eq!(server.limits.max_connections, 100);
+    assert_eq!(server.limits.additional_keys["burst"], FennecType::Int(20));
+}
+
+#[test]
+fn generated_types_reject_unknown_keys() {
+    let err = fennec::de::from_str::<server::Server>(
+        "host = \"a\" id = 1 upstreams [ ] limits { max-connections = 1 } typo = 1",
+    );
+
+    assert!(err.is_err());
+}
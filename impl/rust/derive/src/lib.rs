@@ -0,0 +1,362 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    ext::IdentExt, parse_macro_input, parse_quote, spanned::Spanned, Attribute, Data, DeriveInput,
+    Error, ExprPath, Fields, FieldsNamed, FieldsUnnamed, Generics, Ident, LitStr, Token, Type,
+};
+
+/**
+ * Implements `FromFennec`, `TryFrom<&FennecType>`, `IntoFennec` and `From<T> for FennecType`.
+ * Structs are read from objects, tuple structs from arrays and newtypes from whatever they wrap.
+ * Unit enum variants are written as strings, other variants as an object with the variant's name as its only key.
+ *
+ * - `#[fennec(rename = "name")]` reads and writes a different key
+ * - `#[fennec(default)]` or `#[fennec(default = "path::to::fn")]` fills in a missing key
+ * - `#[fennec(flag)]` on a `bool` field reads a missing key as `false`, and only writes the key when it's `true`
+ *
+ * `Option` fields are always optional, and `None` is left out when writing.
+ */
+#[proc_macro_derive(FennecConfig, attributes(fennec))]
+pub fn derive_fennec_config(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(out) => out.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+enum DefaultValue {
+    Trait,
+    Function(ExprPath),
+}
+
+#[derive(Default)]
+struct Attrs {
+    rename: Option<String>,
+    default: Option<DefaultValue>,
+    flag: bool,
+}
+
+impl Attrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("fennec")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    out.rename = Some(name.value());
+                    return Ok(());
+                }
+                if meta.path.is_ident("default") {
+                    out.default = Some(match meta.input.peek(Token![=]) {
+                        true => {
+                            let func: LitStr = meta.value()?.parse()?;
+                            DefaultValue::Function(func.parse()?)
+                        }
+                        false => DefaultValue::Trait,
+                    });
+                    return Ok(());
+                }
+                if meta.path.is_ident("flag") {
+                    out.flag = true;
+                    return Ok(());
+                }
+                Err(meta.error("expected `rename`, `default` or `flag`"))
+            })?;
+        }
+        Ok(out)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rename.is_none() && self.default.is_none() && !self.flag
+    }
+
+    fn key(&self, ident: &Ident) -> String {
+        self.rename
+            .clone()
+            .unwrap_or_else(|| ident.unraw().to_string())
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !Attrs::parse(&input.attrs)?.is_empty() {
+        return Err(Error::new(
+            Span::call_site(),
+            "fennec attributes go on fields and variants",
+        ));
+    }
+
+    let (read, write) = match &input.data {
+        Data::Struct(data) => (
+            read_fields(&data.fields, quote!(Self))?,
+            write_struct(&data.fields)?,
+        ),
+        Data::Enum(data) => {
+            let mut units = Vec::new();
+            let mut others = Vec::new();
+            let mut writes = Vec::new();
+            for variant in &data.variants {
+                let attrs = Attrs::parse(&variant.attrs)?;
+                if attrs.default.is_some() || attrs.flag {
+                    return Err(Error::new(variant.span(), "variants can only be renamed"));
+                }
+                let key = attrs.key(&variant.ident);
+                let ident = &variant.ident;
+
+                if let Fields::Unit = variant.fields {
+                    units.push(quote!(#key => Ok(Self::#ident)));
+                    writes.push(quote! {
+                        Self::#ident => ::fennec::parse::FennecType::String(#key.to_string())
+                    });
+                    continue;
+                }
+
+                let read = read_fields(&variant.fields, quote!(Self::#ident))?;
+                others.push(quote! {
+                    #key => {
+                        let path = &path.join_key(name);
+                        #read
+                    }
+                });
+
+                let bindings = bindings(&variant.fields);
+                let pattern = pattern(&variant.fields, &bindings);
+                let write = write_fields(&variant.fields, &bindings)?;
+                writes.push(quote! {
+                    Self::#ident #pattern => ::fennec::parse::FennecType::Object(
+                        ::std::collections::HashMap::from([(#key.to_string(), #write)])
+                    )
+                });
+            }
+
+            let read = quote! {
+                match value {
+                    ::fennec::parse::FennecType::String(name) => match name.as_str() {
+                        #(#units,)*
+                        _ => Err(::fennec::convert::ConvertError::UnknownVariant(name.clone(), path.clone())),
+                    },
+                    ::fennec::parse::FennecType::Object(obj) if obj.len() == 1 => {
+                        let (name, value) = obj.iter().next().expect("We just checked the length!");
+                        match name.as_str() {
+                            #(#others,)*
+                            _ => Err(::fennec::convert::ConvertError::UnknownVariant(name.clone(), path.clone())),
+                        }
+                    }
+                    _ => Err(::fennec::convert::wrong_type("string or object with one key", value, path)),
+                }
+            };
+            let write = quote! {
+                match self {
+                    #(#writes,)*
+                }
+            };
+            (read, write)
+        }
+        Data::Union(_) => {
+            return Err(Error::new(
+                Span::call_site(),
+                "FennecConfig can't be derived for unions",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let from_generics = with_bound(&input.generics, quote!(::fennec::convert::FromFennec));
+    let into_generics = with_bound(&input.generics, quote!(::fennec::convert::IntoFennec));
+    let mut try_from_generics = from_generics.clone();
+    try_from_generics.params.insert(0, parse_quote!('__fennec));
+
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let (from_impl, _, from_where) = from_generics.split_for_impl();
+    let (into_impl, _, into_where) = into_generics.split_for_impl();
+    let (try_from_impl, _, _) = try_from_generics.split_for_impl();
+
+    Ok(quote! {
+        impl #from_impl ::fennec::convert::FromFennec for #name #ty_generics #from_where {
+            fn from_fennec(
+                value: &::fennec::parse::FennecType,
+                path: &::fennec::path::Path,
+            ) -> ::std::result::Result<Self, ::fennec::convert::ConvertError> {
+                #read
+            }
+        }
+
+        impl #try_from_impl ::std::convert::TryFrom<&'__fennec ::fennec::parse::FennecType> for #name #ty_generics #from_where {
+            type Error = ::fennec::convert::ConvertError;
+
+            fn try_from(value: &'__fennec ::fennec::parse::FennecType) -> ::std::result::Result<Self, Self::Error> {
+                <Self as ::fennec::convert::FromFennec>::from_fennec(value, &::fennec::path::Path::new())
+            }
+        }
+
+        impl #into_impl ::fennec::convert::IntoFennec for #name #ty_generics #into_where {
+            fn into_fennec(self) -> ::fennec::parse::FennecType {
+                #write
+            }
+        }
+
+        impl #into_impl ::std::convert::From<#name #ty_generics> for ::fennec::parse::FennecType #into_where {
+            fn from(value: #name #ty_generics) -> Self {
+                ::fennec::convert::IntoFennec::into_fennec(value)
+            }
+        }
+    })
+}
+
+fn with_bound(generics: &Generics, bound: TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
+
+/**
+ * Builds `ctor` out of `value`, reporting errors under `path`
+ */
+fn read_fields(fields: &Fields, ctor: TokenStream2) -> syn::Result<TokenStream2> {
+    match fields {
+        Fields::Named(fields) => read_named(fields, ctor),
+        Fields::Unnamed(fields) => read_unnamed(fields, ctor),
+        Fields::Unit => Ok(quote! {
+            match value {
+                ::fennec::parse::FennecType::Null => Ok(#ctor),
+                _ => Err(::fennec::convert::wrong_type("null", value, path)),
+            }
+        }),
+    }
+}
+
+fn read_named(fields: &FieldsNamed, ctor: TokenStream2) -> syn::Result<TokenStream2> {
+    let mut reads = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("Named fields have names!");
+        let attrs = Attrs::parse(&field.attrs)?;
+        let key = attrs.key(ident);
+
+        if attrs.flag && !is_bool(&field.ty) {
+            return Err(Error::new(field.ty.span(), "flags have to be `bool`"));
+        }
+
+        let read = match (&attrs.default, attrs.flag) {
+            (Some(_), true) => {
+                return Err(Error::new(field.span(), "flags already default to `false`"))
+            }
+            (None, true) => quote!(::fennec::convert::field_or(obj, #key, path, || false)),
+            (Some(DefaultValue::Trait), _) => quote! {
+                ::fennec::convert::field_or(obj, #key, path, ::std::default::Default::default)
+            },
+            (Some(DefaultValue::Function(func)), _) => {
+                quote!(::fennec::convert::field_or(obj, #key, path, #func))
+            }
+            (None, false) => quote!(::fennec::convert::field(obj, #key, path)),
+        };
+        reads.push(quote!(#ident: #read?));
+    }
+
+    Ok(quote! {{
+        let obj = ::fennec::convert::expect_object(value, path)?;
+        Ok(#ctor { #(#reads,)* })
+    }})
+}
+
+fn read_unnamed(fields: &FieldsUnnamed, ctor: TokenStream2) -> syn::Result<TokenStream2> {
+    no_attrs(fields)?;
+
+    if fields.unnamed.len() == 1 {
+        return Ok(quote! {
+            Ok(#ctor(::fennec::convert::FromFennec::from_fennec(value, path)?))
+        });
+    }
+
+    let len = fields.unnamed.len();
+    let reads = (0..len).map(
+        |i| quote!(::fennec::convert::FromFennec::from_fennec(&arr[#i], &path.join_index(#i))?),
+    );
+    Ok(quote! {{
+        let arr = ::fennec::convert::expect_tuple(value, #len, path)?;
+        Ok(#ctor(#(#reads,)*))
+    }})
+}
+
+fn no_attrs(fields: &FieldsUnnamed) -> syn::Result<()> {
+    for field in &fields.unnamed {
+        if !Attrs::parse(&field.attrs)?.is_empty() {
+            return Err(Error::new(
+                field.span(),
+                "only named fields take fennec attributes",
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn is_bool(ty: &Type) -> bool {
+    matches!(ty, Type::Path(ty) if ty.qself.is_none() && ty.path.is_ident("bool"))
+}
+
+fn write_struct(fields: &Fields) -> syn::Result<TokenStream2> {
+    let bindings = bindings(fields);
+    let pattern = pattern(fields, &bindings);
+    let write = write_fields(fields, &bindings)?;
+    Ok(quote! {
+        let Self #pattern = self;
+        #write
+    })
+}
+
+/**
+ * One local per field, named so they can't clash with anything the user wrote
+ */
+fn bindings(fields: &Fields) -> Vec<Ident> {
+    (0..fields.len())
+        .map(|i| format_ident!("__field{}", i))
+        .collect()
+}
+
+fn pattern(fields: &Fields, bindings: &[Ident]) -> TokenStream2 {
+    match fields {
+        Fields::Named(fields) => {
+            let idents = fields.named.iter().map(|field| &field.ident);
+            quote!({ #(#idents: #bindings),* })
+        }
+        Fields::Unnamed(_) => quote!((#(#bindings),*)),
+        Fields::Unit => quote!(),
+    }
+}
+
+fn write_fields(fields: &Fields, bindings: &[Ident]) -> syn::Result<TokenStream2> {
+    match fields {
+        Fields::Named(fields) => {
+            let mut writes = Vec::new();
+            for (field, binding) in fields.named.iter().zip(bindings) {
+                let attrs = Attrs::parse(&field.attrs)?;
+                let key = attrs.key(field.ident.as_ref().expect("Named fields have names!"));
+                writes.push(match attrs.flag {
+                    true => quote! {
+                        if #binding {
+                            obj.insert(#key.to_string(), ::fennec::parse::FennecType::Bool(true));
+                        }
+                    },
+                    false => quote!(::fennec::convert::insert(&mut obj, #key, #binding);),
+                });
+            }
+            Ok(quote! {{
+                let mut obj = ::std::collections::HashMap::new();
+                #(#writes)*
+                ::fennec::parse::FennecType::Object(obj)
+            }})
+        }
+        Fields::Unnamed(_) if bindings.len() == 1 => {
+            let binding = &bindings[0];
+            Ok(quote!(::fennec::convert::IntoFennec::into_fennec(#binding)))
+        }
+        Fields::Unnamed(_) => Ok(quote! {
+            ::fennec::parse::FennecType::Array(vec![
+                #(::fennec::convert::IntoFennec::into_fennec(#bindings),)*
+            ])
+        }),
+        Fields::Unit => Ok(quote!(::fennec::parse::FennecType::Null)),
+    }
+}
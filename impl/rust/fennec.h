@@ -1,192 +1,312 @@
 #ifndef __FENNEC_HEADER__
 #define __FENNEC_HEADER__
 
-#ifdef __cplusplus
-extern "C" {
-#endif
+/* Generated from the Rust sources by build.rs. Don't edit it by hand. */
 
 #include <stdbool.h>
 #include <stddef.h>
-#include <string.h>
+#include <stdint.h>
 
+// Which member of the value's union is set
 typedef enum FennecConfig_FennecValue_Type {
-    FennecConfig_FennecValue_Type_Object,
-    FennecConfig_FennecValue_Type_Array,
-    FennecConfig_FennecValue_Type_String,
-    FennecConfig_FennecValue_Type_Float,
-    FennecConfig_FennecValue_Type_Int,
-    FennecConfig_FennecValue_Type_Bool,
-    FennecConfig_FennecValue_Type_Null,
-    FennecConfig_FennecValue_Type_Error
+  FennecConfig_FennecValue_Type_Object,
+  FennecConfig_FennecValue_Type_Array,
+  FennecConfig_FennecValue_Type_String,
+  FennecConfig_FennecValue_Type_Float,
+  FennecConfig_FennecValue_Type_Int,
+  FennecConfig_FennecValue_Type_Bool,
+  FennecConfig_FennecValue_Type_Null,
+  // A document that didn't parse, or a string or object with a NUL in a key or value,
+  // since C would only read up to it
+  FennecConfig_FennecValue_Type_Error,
+  // The Parse* functions resolve every reference, so these won't show up there
+  FennecConfig_FennecValue_Type_Reference,
+  FennecConfig_FennecValue_Type_BigInt,
+  FennecConfig_FennecValue_Type_DateTime,
+  FennecConfig_FennecValue_Type_Date,
+  FennecConfig_FennecValue_Type_Time,
+  FennecConfig_FennecValue_Type_Duration,
+  FennecConfig_FennecValue_Type_Quantity,
 } FennecConfig_FennecValue_Type;
 
-struct FennecConfig_FennecValue;
+// Why a parse failed. Only handed to C as an opaque pointer.
+typedef struct FennecConfig_Error FennecConfig_Error;
+
+typedef struct FennecConfig_Value FennecConfig_Value;
 
-/// Ignore the capacity values, these are kept track of to properly free memory without leaking.
+// The capacities are only kept to free the arrays without leaking
 typedef struct FennecConfig_FennecValue_Object {
-    size_t len;
-    size_t keys_capacity;
-    char **keys;
-    size_t values_capacity;
-    struct FennecConfig_FennecValue *values;
+  size_t len;
+  size_t keys_capacity;
+  const char *const *keys;
+  size_t values_capacity;
+  const struct FennecConfig_FennecValue *values;
 } FennecConfig_FennecValue_Object;
 
-/// Ignore the capacity values, these are kept track of to properly free memory without leaking.
 typedef struct FennecConfig_FennecValue_Array {
-    size_t len;
-    size_t capacity;
-    struct FennecConfig_FennecValue *arr;
+  size_t len;
+  size_t capacity;
+  const struct FennecConfig_FennecValue *arr;
 } FennecConfig_FennecValue_Array;
 
-/// It isn't recommended to operate on this by iteself. Rather, you should copy the data to your own representation and then free it with FennecConfig_FennecType_Free.
+typedef struct FennecConfig_Duration {
+  uint64_t secs;
+  uint32_t nanos;
+} FennecConfig_Duration;
+
+typedef union FennecConfig_FennecValue_Value {
+  struct FennecConfig_FennecValue_Object object;
+  struct FennecConfig_FennecValue_Array array;
+  const char *string;
+  double f;
+  int64_t i;
+  bool b;
+  // The path of an unresolved reference, e.g. `server.ports[0]`
+  const char *reference;
+  // An int too big for an `int64_t`, written out in decimal
+  const char *big_int;
+  // RFC 3339 text, e.g. `1979-05-27T07:32:00Z`, or without the offset for a local date-time
+  const char *date_time;
+  // `1979-05-27`
+  const char *date;
+  // `07:32:00`
+  const char *time;
+  struct FennecConfig_Duration duration;
+  // A number with its unit, as it was written, e.g. `512MiB`
+  const char *quantity;
+} FennecConfig_FennecValue_Value;
+
+// Read it with the lookup and iteration functions rather than walking the unions by hand
 typedef struct FennecConfig_FennecValue {
-    FennecConfig_FennecValue_Type type;
-    union {
-        FennecConfig_FennecValue_Object object;
-        FennecConfig_FennecValue_Array array;
-        char *string;
-        double f;
-        long i;
-        bool b;
-    } value;
+  enum FennecConfig_FennecValue_Type type;
+  union FennecConfig_FennecValue_Value value;
 } FennecConfig_FennecValue;
 
-FennecConfig_FennecValue *FennecConfig_ParseString(char *str);
+// Walks an object or array one entry at a time. Lives on the C side's stack, so there's nothing to free.
+typedef struct FennecConfig_Iter {
+  const struct FennecConfig_FennecValue *value;
+  size_t index;
+} FennecConfig_Iter;
+
+// Mirrors `StringifyOptions`
+typedef struct FennecConfig_StringifyOptions {
+  // Spaces per level of nesting, 4 by default
+  size_t indent;
+  // Write keys in sorted order instead of the order they're stored in
+  bool sort_keys;
+  // Collapse objects that only hold one key into dotted keys like `a.b.c = 1`
+  bool dotted_keys;
+} FennecConfig_StringifyOptions;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Returns an Error value if `str` doesn't parse, see `FennecConfig_ParseStringEx` for why
+const struct FennecConfig_FennecValue *FennecConfig_ParseString(const char *str);
+
+const struct FennecConfig_FennecValue *FennecConfig_ParseFile(const char *filename);
+
+// Returns null on failure. If `error` isn't null, it's set to null on success and to a new error on failure.
+const struct FennecConfig_FennecValue *FennecConfig_ParseStringEx(const char *str,
+                                                                  const struct FennecConfig_Error **error);
+
+const struct FennecConfig_FennecValue *FennecConfig_ParseFileEx(const char *filename,
+                                                                const struct FennecConfig_Error **error);
+
+// Owned by the error, so it's freed along with it
+const char *FennecConfig_Error_Message(const struct FennecConfig_Error *error);
+
+// Lines and columns count from 1.
+// All three are 0 when the error isn't about the input's contents, e.g. when a file can't be read.
+size_t FennecConfig_Error_Line(const struct FennecConfig_Error *error);
+
+size_t FennecConfig_Error_Column(const struct FennecConfig_Error *error);
+
+// In bytes from the start of the input
+size_t FennecConfig_Error_Offset(const struct FennecConfig_Error *error);
+
+void FennecConfig_Error_Free(const struct FennecConfig_Error *error);
+
+// Only call this on the values you get from the Parse* functions, never on their children
+void FennecConfig_FennecValue_Free(const struct FennecConfig_FennecValue *fen);
+
+// Looks up a path like `server.ports[0]` or `a."quoted key"` under `fen`, null if nothing is there.
+// The result is owned by `fen`.
+const struct FennecConfig_FennecValue *FennecConfig_Get(const struct FennecConfig_FennecValue *fen,
+                                                        const char *path);
+
+// Null if `fen` isn't an object or doesn't have the key
+const struct FennecConfig_FennecValue *FennecConfig_ObjectGet(const struct FennecConfig_FennecValue *fen,
+                                                              const char *key);
+
+// Null if `fen` isn't an array or `index` is out of bounds
+const struct FennecConfig_FennecValue *FennecConfig_ArrayGet(const struct FennecConfig_FennecValue *fen,
+                                                             size_t index);
+
+// The number of entries in an object or array, 0 for anything else
+size_t FennecConfig_Len(const struct FennecConfig_FennecValue *fen);
+
+// The typed getters look up `path` like `FennecConfig_Get`, an empty path reading `fen` itself.
+// They return false without touching `out` if the value isn't there or has another type.
+bool FennecConfig_GetString(const struct FennecConfig_FennecValue *fen,
+                            const char *path,
+                            const char **out);
+
+bool FennecConfig_GetInt(const struct FennecConfig_FennecValue *fen,
+                         const char *path,
+                         int64_t *out);
+
+// Ints are read as floats too
+bool FennecConfig_GetFloat(const struct FennecConfig_FennecValue *fen,
+                           const char *path,
+                           double *out);
+
+// Reads ints that fit in a `uint64_t`, including the ones too big for `FennecConfig_GetInt`
+bool FennecConfig_GetUInt(const struct FennecConfig_FennecValue *fen,
+                          const char *path,
+                          uint64_t *out);
+
+// Only for ints too big for an `int64_t`, which are given as decimal strings owned by `fen`
+bool FennecConfig_GetBigInt(const struct FennecConfig_FennecValue *fen,
+                            const char *path,
+                            const char **out);
+
+bool FennecConfig_GetBool(const struct FennecConfig_FennecValue *fen, const char *path, bool *out);
+
+// Reads date-times, dates and times as text owned by `fen`, e.g. `1979-05-27T07:32:00Z`
+bool FennecConfig_GetDateTime(const struct FennecConfig_FennecValue *fen,
+                              const char *path,
+                              const char **out);
+
+// Seconds and nanoseconds since the Unix epoch. Only date-times with an offset are a point in time.
+bool FennecConfig_GetTimestamp(const struct FennecConfig_FennecValue *fen,
+                               const char *path,
+                               int64_t *secs,
+                               uint32_t *nanos);
+
+bool FennecConfig_GetDuration(const struct FennecConfig_FennecValue *fen,
+                              const char *path,
+                              uint64_t *secs,
+                              uint32_t *nanos);
+
+// Reads a number with a unit as it was written, e.g. `512MiB`, owned by `fen`
+bool FennecConfig_GetQuantity(const struct FennecConfig_FennecValue *fen,
+                              const char *path,
+                              const char **out);
+
+// Reads a size like `512MiB` in bytes.
+// False for other units, and for sizes that aren't a whole number of bytes.
+bool FennecConfig_GetBytes(const struct FennecConfig_FennecValue *fen,
+                           const char *path,
+                           uint64_t *out);
+
+// Walks the entries of an object or array:
+//
+// ```c
+// FennecConfig_Iter iter = FennecConfig_Iterate(fen);
+// const char *key;
+// const FennecConfig_FennecValue *value;
+// while (FennecConfig_IterNext(&iter, &key, &value)) { ... }
+// ```
+struct FennecConfig_Iter FennecConfig_Iterate(const struct FennecConfig_FennecValue *fen);
+
+// Moves to the next entry, returning false once there are none left.
+// `key` is set to null for array elements.
+bool FennecConfig_IterNext(struct FennecConfig_Iter *iter,
+                           const char **key,
+                           const struct FennecConfig_FennecValue **out);
+
+bool FennecConfig_FennecValue_IsObject(const struct FennecConfig_FennecValue *fen);
+
+const struct FennecConfig_FennecValue_Object *FennecConfig_FennecValue_GetObject(const struct FennecConfig_FennecValue *fen);
+
+bool FennecConfig_FennecValue_Object_HasKey(const struct FennecConfig_FennecValue_Object *obj,
+                                            const char *key);
+
+const struct FennecConfig_FennecValue *FennecConfig_FennecValue_Object_GetKey(const struct FennecConfig_FennecValue_Object *obj,
+                                                                              const char *key);
+
+bool FennecConfig_FennecValue_IsArray(const struct FennecConfig_FennecValue *fen);
+
+const struct FennecConfig_FennecValue_Array *FennecConfig_FennecValue_GetArray(const struct FennecConfig_FennecValue *fen);
+
+size_t FennecConfig_FennecValue_Array_Len(const struct FennecConfig_FennecValue_Array *arr);
+
+const struct FennecConfig_FennecValue *FennecConfig_FennecValue_Array_GetIdx(const struct FennecConfig_FennecValue_Array *arr,
+                                                                             size_t idx);
+
+bool FennecConfig_FennecValue_IsString(const struct FennecConfig_FennecValue *fen);
+
+const char *FennecConfig_FennecValue_GetString(const struct FennecConfig_FennecValue *fen);
+
+bool FennecConfig_FennecValue_IsFloat(const struct FennecConfig_FennecValue *fen);
+
+double FennecConfig_FennecValue_GetFloat(const struct FennecConfig_FennecValue *fen);
+
+bool FennecConfig_FennecValue_IsInt(const struct FennecConfig_FennecValue *fen);
+
+int64_t FennecConfig_FennecValue_GetInt(const struct FennecConfig_FennecValue *fen);
+
+bool FennecConfig_FennecValue_IsNumber(const struct FennecConfig_FennecValue *fen);
+
+double FennecConfig_FennecValue_GetNumber(const struct FennecConfig_FennecValue *fen);
+
+bool FennecConfig_FennecValue_IsBool(const struct FennecConfig_FennecValue *fen);
+
+bool FennecConfig_FennecValue_GetBool(const struct FennecConfig_FennecValue *fen);
+
+bool FennecConfig_FennecValue_IsNull(const struct FennecConfig_FennecValue *fen);
+
+bool FennecConfig_FennecValue_IsError(const struct FennecConfig_FennecValue *fen);
+
+struct FennecConfig_Value *FennecConfig_NewObject(void);
+
+struct FennecConfig_Value *FennecConfig_NewArray(void);
+
+// Copies `value`, null if it isn't valid UTF-8
+struct FennecConfig_Value *FennecConfig_NewString(const char *value);
+
+struct FennecConfig_Value *FennecConfig_NewFloat(double value);
+
+struct FennecConfig_Value *FennecConfig_NewInt(int64_t value);
+
+struct FennecConfig_Value *FennecConfig_NewBool(bool value);
+
+struct FennecConfig_Value *FennecConfig_NewNull(void);
+
+// Takes a date-time, date or time in its literal form, e.g. `1979-05-27T07:32:00Z`.
+// Null if it's none of them.
+struct FennecConfig_Value *FennecConfig_NewDateTime(const char *value);
+
+struct FennecConfig_Value *FennecConfig_NewDuration(uint64_t secs, uint32_t nanos);
+
+// Takes a number with a unit, e.g. `512MiB`. Null if the unit isn't a known one.
+struct FennecConfig_Value *FennecConfig_NewQuantity(const char *value);
+
+// Copies a parsed value so it can be changed and written back out, null for Error values
+struct FennecConfig_Value *FennecConfig_Edit(const struct FennecConfig_FennecValue *fen);
+
+// Replaces the key if it's already there.
+// Takes ownership of `value`, even when it fails because `obj` isn't an object.
+bool FennecConfig_ObjectSet(struct FennecConfig_Value *obj,
+                            const char *key,
+                            struct FennecConfig_Value *value);
+
+// Takes ownership of `value`, even when it fails because `arr` isn't an array
+bool FennecConfig_ArrayPush(struct FennecConfig_Value *arr, struct FennecConfig_Value *value);
+
+// `options` may be null for the defaults. Free the result with `FennecConfig_String_Free`.
+char *FennecConfig_Stringify(const struct FennecConfig_Value *value,
+                             const struct FennecConfig_StringifyOptions *options);
+
+void FennecConfig_String_Free(char *str);
 
-FennecConfig_FennecValue *FennecConfig_ParseFile(char *filename);
-
-/// Only call this on the FennecValues you get from the Parse* functions. Never call it on their children.
-void FennecConfig_FennecValue_Free(FennecConfig_FennecValue *fen);
-
-bool FennecConfig_FennecValue_IsObject(FennecConfig_FennecValue *fen) {
-    return fen->type == FennecConfig_FennecValue_Type_Object;
-}
-
-FennecConfig_FennecValue_Object *FennecConfig_FennecValue_GetObject(FennecConfig_FennecValue *fen) {
-    if (!FennecConfig_FennecValue_IsObject(fen)) {
-        return (FennecConfig_FennecValue_Object *)0;
-    }
-
-    return &fen->value.object;
-}
-
-bool FennecConfig_FennecValue_Object_HasKey(FennecConfig_FennecValue_Object *obj, char *key) {
-    for (int i = 0; i < obj->len; i++) {
-        if (strcmp((const char *)key, (const char *)obj->keys[i]) == 0) {
-            return true;
-        }
-    }
-    return false;
-}
-
-FennecConfig_FennecValue *FennecConfig_FennecValue_Object_GetKey(FennecConfig_FennecValue_Object *obj, char *key) {
-    for (int i = 0; i < obj->len; i++) {
-        if (strcmp((const char *)key, (const char *)obj->keys[i]) == 0) {
-            return &obj->values[i];
-        }
-    }
-
-    return (FennecConfig_FennecValue *)0;
-}
-
-bool FennecConfig_FennecValue_IsArray(FennecConfig_FennecValue *fen) {
-    return fen->type == FennecConfig_FennecValue_Type_Array;
-}
-
-FennecConfig_FennecValue_Array *FennecConfig_FennecValue_GetArray(FennecConfig_FennecValue *fen) {
-    if (!FennecConfig_FennecValue_IsArray(fen)) {
-        return (FennecConfig_FennecValue_Array *)0;
-    }
-
-    return &fen->value.array;
-}
-
-size_t FennecConfig_FennecValue_Array_Len(FennecConfig_FennecValue_Array *arr) {
-    return arr->len;
-}
-
-FennecConfig_FennecValue *FennecConfig_FennecValue_Array_GetIdx(FennecConfig_FennecValue_Array *arr, size_t idx) {
-    if (idx >= arr->len) {
-        return (FennecConfig_FennecValue *)0;
-    }
-    
-    return &arr->arr[idx];
-}
-
-bool FennecConfig_FennecValue_IsString(FennecConfig_FennecValue *fen) {
-    return fen->type == FennecConfig_FennecValue_Type_String;
-}
-
-char *FennecConfig_FennecValue_GetString(FennecConfig_FennecValue *fen) {
-    if (!FennecConfig_FennecValue_IsString(fen)) {
-        return (char *)0;
-    }
-
-    return fen->value.string;
-}
-
-bool FennecConfig_FennecValue_IsFloat(FennecConfig_FennecValue *fen) {
-    return fen->type == FennecConfig_FennecValue_Type_Float;
-}
-
-double FennecConfig_FennecValue_GetFloat(FennecConfig_FennecValue *fen) {
-    if (!FennecConfig_FennecValue_IsFloat(fen)) {
-        return 0;
-    }
-
-    return fen->value.f;
-}
-
-bool FennecConfig_FennecValue_IsInt(FennecConfig_FennecValue *fen) {
-    return fen->type == FennecConfig_FennecValue_Type_Int;
-}
-
-long FennecConfig_FennecValue_GetInt(FennecConfig_FennecValue *fen) {
-    if (!FennecConfig_FennecValue_IsInt(fen)) {
-        return 0;
-    }
-
-    return fen->value.i;
-}
-
-bool FennecConfig_FennecValue_IsNumber(FennecConfig_FennecValue *fen) {
-    return fen->type == FennecConfig_FennecValue_Type_Float || fen->type == FennecConfig_FennecValue_Type_Int;
-}
-
-double FennecConfig_FennecValue_GetNumber(FennecConfig_FennecValue *fen) {
-    if (FennecConfig_FennecValue_IsInt(fen)) {
-        return (double)fen->value.i;
-    }
-
-    if (FennecConfig_FennecValue_IsFloat(fen)) {
-        return fen->value.f;
-    }
-    
-    return 0;
-}
-
-bool FennecConfig_FennecValue_IsBool(FennecConfig_FennecValue *fen) {
-    return fen->type == FennecConfig_FennecValue_Type_Bool;
-}
-
-bool FennecConfig_FennecValue_GetBool(FennecConfig_FennecValue *fen) {
-    if (!FennecConfig_FennecValue_IsBool(fen)) {
-        return false;
-    }
-    
-    return fen->value.b;
-}
-
-bool FennecConfig_FennecValue_IsNull(FennecConfig_FennecValue *fen) {
-    return fen->type == FennecConfig_FennecValue_Type_Null;
-}
-
-bool FennecConfig_FennecValue_IsError(FennecConfig_FennecValue *fen) {
-    return fen->type == FennecConfig_FennecValue_Type_Error;
-}
+// Only for the values that weren't handed to `FennecConfig_ObjectSet` or `FennecConfig_ArrayPush`
+void FennecConfig_Value_Free(struct FennecConfig_Value *value);
 
 #ifdef __cplusplus
-}
-#endif
+}  // extern "C"
+#endif  // __cplusplus
 
-#endif
\ No newline at end of file
+#endif  /* __FENNEC_HEADER__ */
@@ -0,0 +1,164 @@
+/*
+ * The stable C ABI. Values are only handed out as opaque pointers, so this header keeps working
+ * when the library changes how it stores them. `fennec_abi_version()` tells you which version the
+ * loaded library speaks, and with the `symbol-versioning` feature every function is also bound to
+ * the matching `FENNEC_<n>` symbol version.
+ */
+
+#ifndef __FENNEC_ABI_HEADER__
+#define __FENNEC_ABI_HEADER__
+
+/* Generated from the Rust sources by build.rs. Don't edit it by hand. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// Bumped whenever functions are added or one's signature or behaviour changes, with a new `FENNEC_<n>`
+// symbol version for the functions it added. 2 added the big int, date, time, duration and quantity types.
+#define FENNEC_ABI_VERSION 2
+
+// Why a parse failed
+typedef struct fennec_error_t fennec_error_t;
+
+// A parsed value. Only ever handed out as a pointer, so the layout behind it can change freely.
+typedef struct fennec_value_t fennec_value_t;
+
+// One of the `FENNEC_TYPE_*` constants. A plain integer since C compilers don't all agree on how big an enum is.
+// The numbers are part of the ABI, so new types only ever get added at the end.
+typedef uint32_t fennec_type_t;
+
+#define FENNEC_TYPE_OBJECT 0
+
+#define FENNEC_TYPE_ARRAY 1
+
+#define FENNEC_TYPE_STRING 2
+
+#define FENNEC_TYPE_FLOAT 3
+
+#define FENNEC_TYPE_INT 4
+
+#define FENNEC_TYPE_BOOL 5
+
+#define FENNEC_TYPE_NULL 6
+
+// An int too big for an `int64_t`, see `fennec_value_uint` and `fennec_value_bigint`.
+// This and the types after it are new in version 2.
+#define FENNEC_TYPE_BIGINT 7
+
+// The date and time types are read as text with `fennec_value_datetime`,
+// and a date-time with an offset as a Unix timestamp with `fennec_value_timestamp`
+#define FENNEC_TYPE_DATETIME 8
+
+#define FENNEC_TYPE_DATE 9
+
+#define FENNEC_TYPE_TIME 10
+
+#define FENNEC_TYPE_DURATION 11
+
+// A number with a unit like `512MiB`, see `fennec_value_quantity` and `fennec_value_bytes`
+#define FENNEC_TYPE_QUANTITY 12
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// The library keeps every older version's functions, so check that this is at least the
+// `FENNEC_ABI_VERSION` you built against
+uint32_t fennec_abi_version(void);
+
+// Returns null on failure. If `error` isn't null, it's set to null on success and to a new error on failure.
+// Free the result with `fennec_value_free`.
+fennec_value_t *fennec_parse(const char *str,
+                             fennec_error_t **error);
+
+// Like `fennec_parse`, but reads the document from a file
+fennec_value_t *fennec_parse_file(const char *filename, fennec_error_t **error);
+
+// Only call this on the values you get from `fennec_parse` and `fennec_parse_file`, never on their children
+void fennec_value_free(fennec_value_t *value);
+
+// Owned by the error, so it's freed along with it
+const char *fennec_error_message(const fennec_error_t *error);
+
+// Lines and columns count from 1.
+// All three are 0 when the error isn't about the input's contents, e.g. when a file can't be read.
+size_t fennec_error_line(const fennec_error_t *error);
+
+size_t fennec_error_column(const fennec_error_t *error);
+
+// In bytes from the start of the input
+size_t fennec_error_offset(const fennec_error_t *error);
+
+void fennec_error_free(fennec_error_t *error);
+
+// `FENNEC_TYPE_NULL` for a null pointer too
+fennec_type_t fennec_value_type(const fennec_value_t *val);
+
+// Entries in an object or elements in an array, 0 for anything else
+size_t fennec_value_len(const fennec_value_t *val);
+
+// Looks up a path like `server.ports[0]` or `a."quoted key"` under `val`, null if nothing is there.
+// The result is owned by `val`.
+const fennec_value_t *fennec_value_get(const fennec_value_t *val,
+                                       const char *path);
+
+// Null if `obj` isn't an object or doesn't have `key`
+const fennec_value_t *fennec_object_get(const fennec_value_t *obj, const char *key);
+
+// The key of the entry at `index`, null if `obj` isn't an object or `index` is out of range.
+// Entries keep their indices for as long as the value lives, so walk them with this and `fennec_value_at`:
+// ```c
+// for (size_t i = 0; i < fennec_value_len(obj); i++) {
+//     printf("%s\n", fennec_object_key(obj, i));
+// }
+// ```
+const char *fennec_object_key(const fennec_value_t *obj,
+                              size_t index);
+
+// The element at `index` of an array, or the value of the entry at `index` of an object.
+// Null if `index` is out of range.
+const fennec_value_t *fennec_value_at(const fennec_value_t *val, size_t index);
+
+// Sets `out` to a NUL-terminated string owned by `val`. `len` may be null, and is only needed for strings that contain NULs.
+// Returns false, leaving both alone, if `val` isn't a string.
+bool fennec_value_string(const fennec_value_t *val,
+                         const char **out,
+                         size_t *len);
+
+// Returns false, leaving `out` alone, if `val` isn't an int
+bool fennec_value_int(const fennec_value_t *val, int64_t *out);
+
+// Takes both kinds of int, returning false if `val` is negative or doesn't fit
+bool fennec_value_uint(const fennec_value_t *val, uint64_t *out);
+
+// Sets `out` to a `FENNEC_TYPE_BIGINT` written in decimal, NUL-terminated and owned by `val`
+bool fennec_value_bigint(const fennec_value_t *val, const char **out);
+
+// Ints are read as floats too
+bool fennec_value_float(const fennec_value_t *val, double *out);
+
+bool fennec_value_bool(const fennec_value_t *val, bool *out);
+
+// Sets `out` to a date-time, date or time in its literal form, e.g. `1979-05-27T07:32:00Z`,
+// NUL-terminated and owned by `val`
+bool fennec_value_datetime(const fennec_value_t *val, const char **out);
+
+// Seconds and nanoseconds since the Unix epoch.
+// Returns false for a date-time without an offset, since it isn't a point in time on its own.
+bool fennec_value_timestamp(const fennec_value_t *val, int64_t *secs, uint32_t *nanos);
+
+bool fennec_value_duration(const fennec_value_t *val, uint64_t *secs, uint32_t *nanos);
+
+// Sets `out` to the quantity as it was written, e.g. `512MiB`, NUL-terminated and owned by `val`
+bool fennec_value_quantity(const fennec_value_t *val, const char **out);
+
+// A size like `512MiB` in bytes.
+// Returns false for other units, and for sizes that aren't a whole number of bytes or don't fit.
+bool fennec_value_bytes(const fennec_value_t *val, uint64_t *out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* __FENNEC_ABI_HEADER__ */
@@ -0,0 +1,22 @@
+#![no_main]
+
+use fennec::parse::StringifyOptions;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let Ok(value) = fennec::parse(data) else {
+        return;
+    };
+    let str = value.to_string();
+    match fennec::parse(&str) {
+        // Compared as text, since `nan` never equals itself
+        Ok(again) => {
+            let options = StringifyOptions {
+                sort_keys: true,
+                ..StringifyOptions::default()
+            };
+            assert_eq!(value.stringify(&options), again.stringify(&options), "{str}");
+        }
+        Err(e) => panic!("{str:?} doesn't parse: {e}"),
+    }
+});
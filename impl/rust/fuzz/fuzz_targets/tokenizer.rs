@@ -0,0 +1,16 @@
+#![no_main]
+
+use fennec::token::{Token, Tokenizer};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let mut tokenizer = Tokenizer::new(data);
+    loop {
+        let before = tokenizer.index;
+        match tokenizer.next() {
+            // The parser stops at the first error, so there's nothing to resume from
+            Token::Eof | Token::Err(_) | Token::Invalid(..) => break,
+            token => assert!(tokenizer.index > before, "{token:?} didn't advance"),
+        }
+    }
+});
@@ -0,0 +1,409 @@
+use std::time::Duration;
+
+use fennec::{
+    datetime::{Date, DateTime, Time},
+    parse::{FennecType, StringifyOptions},
+    quantity,
+    token::Location,
+    BigInt,
+};
+use pyo3::{
+    create_exception,
+    exceptions::{PyTypeError, PyValueError},
+    prelude::*,
+    types::{
+        PyBool, PyDate, PyDateAccess, PyDateTime, PyDelta, PyDeltaAccess, PyDict, PyFloat, PyInt,
+        PyList, PyString, PyTime, PyTimeAccess, PyTuple, PyTzInfo, PyTzInfoAccess,
+    },
+};
+
+create_exception!(
+    fennec,
+    FennecError,
+    PyValueError,
+    "Raised by loads when a document doesn't parse. Has `line` and `column` attributes, both counting from 1."
+);
+
+fn error(py: Python<'_>, message: String, str: &str, index: usize) -> PyErr {
+    let Location(line, column, _) = Location::of(str, index);
+    let err = FennecError::new_err(format!("{message} at line {line}, column {column}"));
+    let value = err.value(py);
+    // Setting attributes on a fresh exception can't fail
+    let _ = value.setattr("line", line);
+    let _ = value.setattr("column", column);
+    err
+}
+
+/**
+ * A number with a unit, e.g. `512MiB`. `int()` gives it in the unit's base, so bytes for `512MiB`.
+ */
+#[pyclass(frozen, eq, hash, module = "fennec")]
+#[derive(PartialEq, Hash)]
+struct Quantity(quantity::Quantity);
+
+#[pymethods]
+impl Quantity {
+    #[new]
+    fn new(str: &str) -> PyResult<Self> {
+        str.parse()
+            .map(Self)
+            .map_err(|_| PyValueError::new_err(format!("{str:?} isn't a quantity")))
+    }
+
+    #[getter]
+    fn amount(&self) -> f64 {
+        self.0.amount()
+    }
+
+    #[getter]
+    fn unit(&self) -> &'static str {
+        self.0.unit().suffix
+    }
+
+    fn __int__(&self) -> PyResult<u64> {
+        self.0
+            .base_amount()
+            .ok_or_else(|| PyValueError::new_err("not a whole number in the base unit"))
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Quantity('{}')", self.0)
+    }
+}
+
+fn to_py<'py>(py: Python<'py>, value: &FennecType) -> PyResult<Bound<'py, PyAny>> {
+    match value {
+        FennecType::Object(obj) => {
+            let dict = PyDict::new(py);
+            for (key, value) in obj {
+                dict.set_item(key, to_py(py, value)?)?;
+            }
+            Ok(dict.into_any())
+        }
+        FennecType::Array(arr) => {
+            let list = PyList::empty(py);
+            for value in arr {
+                list.append(to_py(py, value)?)?;
+            }
+            Ok(list.into_any())
+        }
+        FennecType::String(str) => Ok(PyString::new(py, str).into_any()),
+        FennecType::Float(f) => Ok(PyFloat::new(py, *f).into_any()),
+        FennecType::Int(i) => Ok(i.into_pyobject(py)?.into_any()),
+        FennecType::BigInt(i) => Ok(i.into_pyobject(py)?.into_any()),
+        FennecType::Bool(b) => Ok(PyBool::new(py, *b).to_owned().into_any()),
+        FennecType::DateTime(DateTime { date, time, offset }) => {
+            let tzinfo = match offset {
+                Some(offset) => Some(PyTzInfo::fixed_offset(
+                    py,
+                    PyDelta::new(py, 0, i32::from(*offset) * 60, 0, true)?,
+                )?),
+                None => None,
+            };
+            let date_time = PyDateTime::new(
+                py,
+                date.year.into(),
+                date.month,
+                date.day,
+                time.hour,
+                time.minute,
+                time.second,
+                time.nanosecond / 1000,
+                tzinfo.as_ref(),
+            )?;
+            Ok(date_time.into_any())
+        }
+        FennecType::Date(date) => {
+            Ok(PyDate::new(py, date.year.into(), date.month, date.day)?.into_any())
+        }
+        FennecType::Time(time) => {
+            let time = PyTime::new(
+                py,
+                time.hour,
+                time.minute,
+                time.second,
+                time.nanosecond / 1000,
+                None,
+            )?;
+            Ok(time.into_any())
+        }
+        FennecType::Duration(duration) => Ok(duration.into_pyobject(py)?.into_any()),
+        FennecType::Quantity(quantity) => Ok(Bound::new(py, Quantity(*quantity))?.into_any()),
+        // Parsing resolves every reference, so there are none left by the time a value gets here
+        FennecType::Null | FennecType::Reference(..) => Ok(py.None().into_bound(py)),
+    }
+}
+
+fn from_py(value: &Bound<'_, PyAny>) -> PyResult<FennecType> {
+    if value.is_none() {
+        return Ok(FennecType::Null);
+    }
+    // bool is a subclass of int, so it has to be checked first
+    if let Ok(b) = value.cast::<PyBool>() {
+        return Ok(FennecType::Bool(b.is_true()));
+    }
+    if value.is_instance_of::<PyInt>() {
+        return Ok(value.extract::<BigInt>()?.into());
+    }
+    if let Ok(f) = value.cast::<PyFloat>() {
+        return Ok(FennecType::Float(f.value()));
+    }
+    if let Ok(str) = value.cast::<PyString>() {
+        return Ok(FennecType::String(str.to_str()?.to_string()));
+    }
+    // datetime is a subclass of date, so it has to be checked first
+    if let Ok(date_time) = value.cast::<PyDateTime>() {
+        let offset = match date_time.get_tzinfo() {
+            Some(_) => {
+                let offset = date_time.call_method0("utcoffset")?;
+                let offset = offset.cast::<PyDelta>()?;
+                let seconds = offset.get_days() * 86_400 + offset.get_seconds();
+                if seconds % 60 != 0 || offset.get_microseconds() != 0 {
+                    return Err(PyValueError::new_err("offsets have to be whole minutes"));
+                }
+                Some((seconds / 60) as i16)
+            }
+            None => None,
+        };
+        return Ok(FennecType::DateTime(DateTime {
+            date: date(date_time)?,
+            time: time(date_time)?,
+            offset,
+        }));
+    }
+    if let Ok(value) = value.cast::<PyDate>() {
+        return Ok(FennecType::Date(date(value)?));
+    }
+    if let Ok(value) = value.cast::<PyTime>() {
+        if value.get_tzinfo().is_some() {
+            return Err(PyValueError::new_err("times can't have a tzinfo"));
+        }
+        return Ok(FennecType::Time(time(value)?));
+    }
+    if value.is_instance_of::<PyDelta>() {
+        return Ok(FennecType::Duration(value.extract::<Duration>()?));
+    }
+    if let Ok(quantity) = value.cast::<Quantity>() {
+        return Ok(FennecType::Quantity(quantity.get().0));
+    }
+    if let Ok(dict) = value.cast::<PyDict>() {
+        let mut obj = std::collections::HashMap::new();
+        for (key, value) in dict.iter() {
+            let Ok(key) = key.cast::<PyString>() else {
+                return Err(PyTypeError::new_err("keys must be str"));
+            };
+            obj.insert(key.to_str()?.to_string(), from_py(&value)?);
+        }
+        return Ok(FennecType::Object(obj));
+    }
+    if value.is_instance_of::<PyList>() || value.is_instance_of::<PyTuple>() {
+        return value
+            .try_iter()?
+            .map(|value| from_py(&value?))
+            .collect::<PyResult<_>>()
+            .map(FennecType::Array);
+    }
+    Err(PyTypeError::new_err(format!(
+        "{} can't be written as Fennec",
+        value.get_type().name()?
+    )))
+}
+
+fn date(value: &impl PyDateAccess) -> PyResult<Date> {
+    u16::try_from(value.get_year())
+        .ok()
+        .and_then(|year| Date::new(year, value.get_month(), value.get_day()))
+        .ok_or_else(|| PyValueError::new_err("years have to be between 0 and 9999"))
+}
+
+fn time(value: &impl PyTimeAccess) -> PyResult<Time> {
+    Time::new(
+        value.get_hour(),
+        value.get_minute(),
+        value.get_second(),
+        value.get_microsecond() * 1000,
+    )
+    .ok_or_else(|| PyValueError::new_err("leap seconds aren't supported"))
+}
+
+/**
+ * Parses a document into dicts, lists, strs, ints, floats, bools and Nones,
+ * with dates, times and durations as objects from `datetime` and quantities as `Quantity`
+ */
+#[pyfunction]
+fn loads(py: Python<'_>, str: &str) -> PyResult<Py<PyAny>> {
+    match fennec::parse(str) {
+        Ok(value) => Ok(to_py(py, &value)?.unbind()),
+        Err(e) => {
+            let index = e.position().map_or(usize::MAX, |pos| pos.0);
+            Err(error(py, e.to_string(), str, index))
+        }
+    }
+}
+
+/**
+ * Writes the same kinds of values back out as a document. Tuples are written as arrays.
+ */
+#[pyfunction]
+#[pyo3(signature = (value, *, indent = 4, sort_keys = false, dotted_keys = false))]
+fn dumps(
+    value: &Bound<'_, PyAny>,
+    indent: usize,
+    sort_keys: bool,
+    dotted_keys: bool,
+) -> PyResult<String> {
+    let options = StringifyOptions {
+        indent,
+        sort_keys,
+        dotted_keys,
+    };
+    Ok(from_py(value)?.stringify(&options))
+}
+
+#[pymodule]
+#[pyo3(name = "fennec")]
+fn fennec_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(loads, m)?)?;
+    m.add_function(wrap_pyfunction!(dumps, m)?)?;
+    m.add_class::<Quantity>()?;
+    m.add("FennecError", m.py().get_type::<FennecError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use pyo3::{prelude::*, types::PyDict};
+
+    use super::{dumps, loads, FennecError, Quantity};
+
+    #[test]
+    fn loads_and_dumps() {
+        Python::initialize();
+        Python::attach(|py| {
+            let value = loads(py, "one = 1 half = 0.5 list [ true null \"d\" ]").unwrap();
+            let value = value.bind(py).cast::<PyDict>().unwrap();
+            let list = value.get_item("list").unwrap().unwrap();
+            assert_eq!(list.repr().unwrap().to_str().unwrap(), "[True, None, 'd']");
+            let one = value.get_item("one").unwrap().unwrap();
+            assert_eq!(one.get_type().name().unwrap(), "int");
+            assert_eq!(one.extract::<i64>().unwrap(), 1);
+
+            let str = dumps(value, 2, true, false).unwrap();
+            assert_eq!(
+                str,
+                "half = 0.5\nlist [\n  true\n  null\n  \"d\"\n]\none = 1\n"
+            );
+
+            let value = loads(py, "server.http.port = 80").unwrap();
+            assert_eq!(
+                dumps(value.bind(py), 4, false, true).unwrap(),
+                "server.http.port = 80\n"
+            );
+        });
+    }
+
+    #[test]
+    fn big_ints() {
+        Python::initialize();
+        Python::attach(|py| {
+            let value = loads(py, "mask = 0xFFFFFFFFFFFFFFFF").unwrap();
+            let value = value.bind(py);
+            let mask = value.get_item("mask").unwrap();
+            assert_eq!(mask.extract::<u64>().unwrap(), u64::MAX);
+            assert_eq!(
+                dumps(value, 4, false, false).unwrap(),
+                "mask = 18446744073709551615\n"
+            );
+        });
+    }
+
+    #[test]
+    fn dates_and_durations() {
+        Python::initialize();
+        Python::attach(|py| {
+            let value = loads(
+                py,
+                "at = 1979-05-27T07:32:00.5+02:00 day = 2024-02-29 wait = 1m30s",
+            )
+            .unwrap();
+            let value = value.bind(py);
+            let at = value.get_item("at").unwrap();
+            assert_eq!(
+                at.repr().unwrap().to_str().unwrap(),
+                "datetime.datetime(1979, 5, 27, 7, 32, 0, 500000, tzinfo=datetime.timezone(datetime.timedelta(seconds=7200)))"
+            );
+            let wait = value.get_item("wait").unwrap();
+            assert_eq!(wait.get_type().name().unwrap(), "timedelta");
+
+            assert_eq!(
+                dumps(value, 4, true, false).unwrap(),
+                "at = 1979-05-27T07:32:00.5+02:00\nday = 2024-02-29\nwait = 1m30s\n"
+            );
+        });
+    }
+
+    #[test]
+    fn quantities() {
+        Python::initialize();
+        Python::attach(|py| {
+            let value = loads(py, "limit = 512MiB rate = 1.5Gbps").unwrap();
+            let value = value.bind(py);
+            let limit = value.get_item("limit").unwrap();
+            assert_eq!(
+                limit.repr().unwrap().to_str().unwrap(),
+                "Quantity('512MiB')"
+            );
+            assert_eq!(
+                limit.getattr("unit").unwrap().extract::<String>().unwrap(),
+                "MiB"
+            );
+            let bytes = py
+                .get_type::<pyo3::types::PyInt>()
+                .call1((&limit,))
+                .unwrap();
+            assert_eq!(bytes.extract::<u64>().unwrap(), 512 * 1024 * 1024);
+            let rate = value.get_item("rate").unwrap();
+            assert_eq!(
+                rate.getattr("amount").unwrap().extract::<f64>().unwrap(),
+                1.5
+            );
+
+            value
+                .set_item(
+                    "floor",
+                    Bound::new(py, Quantity::new("2KB").unwrap()).unwrap(),
+                )
+                .unwrap();
+            assert_eq!(
+                dumps(value, 4, true, false).unwrap(),
+                "floor = 2KB\nlimit = 512MiB\nrate = 1.5Gbps\n"
+            );
+        });
+    }
+
+    #[test]
+    fn raises_with_location() {
+        Python::initialize();
+        Python::attach(|py| {
+            let err = loads(py, "a = 1\nb = ]").unwrap_err();
+            assert!(err.is_instance_of::<FennecError>(py));
+            let value = err.value(py);
+            assert_eq!(
+                value.getattr("line").unwrap().extract::<usize>().unwrap(),
+                2
+            );
+            assert_eq!(
+                value.getattr("column").unwrap().extract::<usize>().unwrap(),
+                5
+            );
+            assert_eq!(value.to_string(), "unexpected ']' at line 2, column 5");
+
+            let set = py.eval(c"{1}", None, None).unwrap();
+            assert!(dumps(&set, 4, false, false).is_err());
+        });
+    }
+}
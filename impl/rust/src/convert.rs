@@ -0,0 +1,451 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    time::{Duration, SystemTime},
+};
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use crate::{
+    datetime::{Date, DateTime, Time},
+    parse::FennecType,
+    path::Path,
+    quantity::Quantity,
+};
+
+/**
+ * (..., path of the value that failed to convert)
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConvertError {
+    MissingKey(Path),
+    /**
+     * (expected type, found type, path)
+     */
+    WrongType(&'static str, &'static str, Path),
+    /**
+     * (expected length, found length, path)
+     */
+    WrongLength(usize, usize, Path),
+    OutOfRange(BigInt, Path),
+    UnknownVariant(String, Path),
+}
+
+impl Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingKey(path) => write!(f, "missing key {path}"),
+            Self::WrongType(expected, found, path) => {
+                write!(f, "expected {expected} at {}, found {found}", at(path))
+            }
+            Self::WrongLength(expected, found, path) => write!(
+                f,
+                "expected {expected} elements at {}, found {found}",
+                at(path)
+            ),
+            Self::OutOfRange(val, path) => write!(f, "{val} is out of range at {}", at(path)),
+            Self::UnknownVariant(name, path) => {
+                write!(f, "unknown variant \"{name}\" at {}", at(path))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+fn at(path: &Path) -> String {
+    if path.is_root() {
+        return "the root".to_string();
+    }
+    path.to_string()
+}
+
+/**
+ * Types that can be read out of a parsed value.
+ * `#[derive(FennecConfig)]` implements this along with `TryFrom<&FennecType>`.
+ */
+pub trait FromFennec: Sized {
+    fn from_fennec(value: &FennecType, path: &Path) -> Result<Self, ConvertError>;
+
+    /**
+     * What a key that isn't in the object turns into, `None` if the key is required
+     */
+    fn missing() -> Option<Self> {
+        None
+    }
+}
+
+/**
+ * Types that can be written back out as a value.
+ * `#[derive(FennecConfig)]` implements this along with `From<T> for FennecType`.
+ */
+pub trait IntoFennec {
+    fn into_fennec(self) -> FennecType;
+
+    /**
+     * Whether the key holding this value is left out of its object
+     */
+    fn is_absent(&self) -> bool {
+        false
+    }
+}
+
+pub fn expect_object<'a>(
+    value: &'a FennecType,
+    path: &Path,
+) -> Result<&'a HashMap<String, FennecType>, ConvertError> {
+    match value {
+        FennecType::Object(obj) => Ok(obj),
+        _ => Err(wrong_type("object", value, path)),
+    }
+}
+
+/**
+ * Checks that `value` is an array of exactly `len` elements
+ */
+pub fn expect_tuple<'a>(
+    value: &'a FennecType,
+    len: usize,
+    path: &Path,
+) -> Result<&'a [FennecType], ConvertError> {
+    match value {
+        FennecType::Array(arr) if arr.len() == len => Ok(arr),
+        FennecType::Array(arr) => Err(ConvertError::WrongLength(len, arr.len(), path.clone())),
+        _ => Err(wrong_type("array", value, path)),
+    }
+}
+
+pub fn wrong_type(expected: &'static str, value: &FennecType, path: &Path) -> ConvertError {
+    ConvertError::WrongType(expected, value.type_name(), path.clone())
+}
+
+/**
+ * Reads `key` out of an object, falling back to `T::missing` if it isn't there
+ */
+pub fn field<T: FromFennec>(
+    obj: &HashMap<String, FennecType>,
+    key: &str,
+    path: &Path,
+) -> Result<T, ConvertError> {
+    let path = path.join_key(key);
+    match obj.get(key) {
+        Some(value) => T::from_fennec(value, &path),
+        None => T::missing().ok_or(ConvertError::MissingKey(path)),
+    }
+}
+
+/**
+ * Reads `key` out of an object, falling back to `default` if it isn't there
+ */
+pub fn field_or<T: FromFennec>(
+    obj: &HashMap<String, FennecType>,
+    key: &str,
+    path: &Path,
+    default: impl FnOnce() -> T,
+) -> Result<T, ConvertError> {
+    match obj.get(key) {
+        Some(value) => T::from_fennec(value, &path.join_key(key)),
+        None => Ok(default()),
+    }
+}
+
+/**
+ * Writes `value` into an object unless it says it should be left out
+ */
+pub fn insert<T: IntoFennec>(obj: &mut HashMap<String, FennecType>, key: &str, value: T) {
+    if !value.is_absent() {
+        obj.insert(key.to_string(), value.into_fennec());
+    }
+}
+
+impl FromFennec for FennecType {
+    fn from_fennec(value: &FennecType, _path: &Path) -> Result<Self, ConvertError> {
+        Ok(value.clone())
+    }
+}
+
+impl IntoFennec for FennecType {
+    fn into_fennec(self) -> FennecType {
+        self
+    }
+}
+
+impl FromFennec for String {
+    fn from_fennec(value: &FennecType, path: &Path) -> Result<Self, ConvertError> {
+        match value {
+            FennecType::String(str) => Ok(str.clone()),
+            _ => Err(wrong_type("string", value, path)),
+        }
+    }
+}
+
+impl IntoFennec for String {
+    fn into_fennec(self) -> FennecType {
+        FennecType::String(self)
+    }
+}
+
+impl IntoFennec for &str {
+    fn into_fennec(self) -> FennecType {
+        FennecType::String(self.to_string())
+    }
+}
+
+impl FromFennec for bool {
+    fn from_fennec(value: &FennecType, path: &Path) -> Result<Self, ConvertError> {
+        match value {
+            FennecType::Bool(b) => Ok(*b),
+            _ => Err(wrong_type("bool", value, path)),
+        }
+    }
+}
+
+impl IntoFennec for bool {
+    fn into_fennec(self) -> FennecType {
+        FennecType::Bool(self)
+    }
+}
+
+macro_rules! int_conversions {
+    ($($ty:ty),*) => {$(
+        impl FromFennec for $ty {
+            fn from_fennec(value: &FennecType, path: &Path) -> Result<Self, ConvertError> {
+                let Some(i) = value.as_big_int() else {
+                    return Err(wrong_type("int", value, path));
+                };
+                <$ty>::try_from(&i).map_err(|_| ConvertError::OutOfRange(i, path.clone()))
+            }
+        }
+
+        impl IntoFennec for $ty {
+            fn into_fennec(self) -> FennecType {
+                BigInt::from(self).into()
+            }
+        }
+    )*};
+}
+
+int_conversions!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+macro_rules! float_conversions {
+    ($($ty:ty),*) => {$(
+        impl FromFennec for $ty {
+            /**
+             * Ints are accepted too, since `1` is a perfectly good float
+             */
+            fn from_fennec(value: &FennecType, path: &Path) -> Result<Self, ConvertError> {
+                match value {
+                    FennecType::Float(f) => Ok(*f as $ty),
+                    FennecType::Int(i) => Ok(*i as $ty),
+                    FennecType::BigInt(i) => Ok(i.to_f64().unwrap_or(f64::NAN) as $ty),
+                    _ => Err(wrong_type("float", value, path)),
+                }
+            }
+        }
+
+        impl IntoFennec for $ty {
+            fn into_fennec(self) -> FennecType {
+                FennecType::Float(self as f64)
+            }
+        }
+    )*};
+}
+
+float_conversions!(f32, f64);
+
+macro_rules! literal_conversions {
+    ($($ty:ident $name:literal),*) => {$(
+        impl FromFennec for $ty {
+            fn from_fennec(value: &FennecType, path: &Path) -> Result<Self, ConvertError> {
+                match value {
+                    FennecType::$ty(val) => Ok(*val),
+                    _ => Err(wrong_type($name, value, path)),
+                }
+            }
+        }
+
+        impl IntoFennec for $ty {
+            fn into_fennec(self) -> FennecType {
+                FennecType::$ty(self)
+            }
+        }
+    )*};
+}
+
+literal_conversions!(
+    DateTime "datetime",
+    Date "date",
+    Time "time",
+    Duration "duration",
+    Quantity "quantity"
+);
+
+impl FromFennec for SystemTime {
+    /**
+     * Only date-times with an offset are a point in time
+     */
+    fn from_fennec(value: &FennecType, path: &Path) -> Result<Self, ConvertError> {
+        value
+            .as_system_time()
+            .ok_or_else(|| wrong_type("datetime with an offset", value, path))
+    }
+}
+
+impl IntoFennec for SystemTime {
+    fn into_fennec(self) -> FennecType {
+        FennecType::DateTime(self.into())
+    }
+}
+
+impl<T: FromFennec> FromFennec for Option<T> {
+    fn from_fennec(value: &FennecType, path: &Path) -> Result<Self, ConvertError> {
+        match value {
+            FennecType::Null => Ok(None),
+            _ => T::from_fennec(value, path).map(Some),
+        }
+    }
+
+    fn missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: IntoFennec> IntoFennec for Option<T> {
+    fn into_fennec(self) -> FennecType {
+        match self {
+            Some(val) => val.into_fennec(),
+            None => FennecType::Null,
+        }
+    }
+
+    fn is_absent(&self) -> bool {
+        self.is_none()
+    }
+}
+
+impl<T: FromFennec> FromFennec for Vec<T> {
+    fn from_fennec(value: &FennecType, path: &Path) -> Result<Self, ConvertError> {
+        match value {
+            FennecType::Array(arr) => arr
+                .iter()
+                .enumerate()
+                .map(|(i, val)| T::from_fennec(val, &path.join_index(i)))
+                .collect(),
+            _ => Err(wrong_type("array", value, path)),
+        }
+    }
+}
+
+impl<T: IntoFennec> IntoFennec for Vec<T> {
+    fn into_fennec(self) -> FennecType {
+        FennecType::Array(self.into_iter().map(IntoFennec::into_fennec).collect())
+    }
+}
+
+impl<T: FromFennec> FromFennec for HashMap<String, T> {
+    fn from_fennec(value: &FennecType, path: &Path) -> Result<Self, ConvertError> {
+        expect_object(value, path)?
+            .iter()
+            .map(|(key, val)| Ok((key.clone(), T::from_fennec(val, &path.join_key(key))?)))
+            .collect()
+    }
+}
+
+impl<T: IntoFennec> IntoFennec for HashMap<String, T> {
+    fn into_fennec(self) -> FennecType {
+        FennecType::Object(
+            self.into_iter()
+                .map(|(key, val)| (key, val.into_fennec()))
+                .collect(),
+        )
+    }
+}
+
+impl<T: FromFennec> FromFennec for Box<T> {
+    fn from_fennec(value: &FennecType, path: &Path) -> Result<Self, ConvertError> {
+        T::from_fennec(value, path).map(Box::new)
+    }
+}
+
+impl<T: IntoFennec> IntoFennec for Box<T> {
+    fn into_fennec(self) -> FennecType {
+        (*self).into_fennec()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::HashMap,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    use crate::{parse, path::Path};
+
+    use super::{field, ConvertError, FromFennec, IntoFennec};
+
+    #[test]
+    fn converts_std_types() {
+        let fen = parse("ports [ 80 443 ] ratio = 1 names { a = \"x\" } missing = null").unwrap();
+        let obj = fen.as_object().unwrap();
+        let root = Path::new();
+
+        let ports: Vec<u16> = field(obj, "ports", &root).unwrap();
+        let ratio: f32 = field(obj, "ratio", &root).unwrap();
+        let names: HashMap<String, String> = field(obj, "names", &root).unwrap();
+        let missing: Option<i64> = field(obj, "missing", &root).unwrap();
+        let absent: Option<i64> = field(obj, "absent", &root).unwrap();
+
+        assert_eq!(ports, vec![80, 443]);
+        assert_eq!(ratio, 1.0);
+        assert_eq!(names["a"], "x");
+        assert_eq!(missing, None);
+        assert_eq!(absent, None);
+        assert_eq!(ports.into_fennec(), fen.get_key("ports").unwrap().clone());
+    }
+
+    #[test]
+    fn converts_dates_and_durations() {
+        let fen =
+            parse("timeout = 1m30s started = 1970-01-01T00:01:00Z local = 1970-01-01T00:01:00")
+                .unwrap();
+        let obj = fen.as_object().unwrap();
+        let root = Path::new();
+
+        let timeout: Duration = field(obj, "timeout", &root).unwrap();
+        let started: SystemTime = field(obj, "started", &root).unwrap();
+
+        assert_eq!(timeout, Duration::from_secs(90));
+        assert_eq!(started, UNIX_EPOCH + Duration::from_secs(60));
+        assert_eq!(started.into_fennec(), fen.get_key("started").unwrap().clone());
+        assert_eq!(
+            field::<SystemTime>(obj, "local", &root).unwrap_err().to_string(),
+            "expected datetime with an offset at local, found datetime"
+        );
+    }
+
+    #[test]
+    fn reports_paths() {
+        let fen = parse("ports [ 80 70000 ] name = 1").unwrap();
+        let obj = fen.as_object().unwrap();
+        let root = Path::new();
+
+        assert_eq!(
+            field::<Vec<u16>>(obj, "ports", &root),
+            Err(ConvertError::OutOfRange(70000.into(), "ports[1]".parse().unwrap()))
+        );
+        assert_eq!(
+            field::<String>(obj, "name", &root).unwrap_err().to_string(),
+            "expected string at name, found int"
+        );
+        assert_eq!(
+            field::<String>(obj, "host", &root),
+            Err(ConvertError::MissingKey("host".parse().unwrap()))
+        );
+        assert_eq!(
+            String::from_fennec(&fen, &root).unwrap_err().to_string(),
+            "expected string at the root, found object"
+        );
+    }
+}
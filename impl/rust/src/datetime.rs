@@ -0,0 +1,390 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::token::{Position, Token, Tokenizer};
+
+/**
+ * A calendar date, `1979-05-27`
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+/**
+ * A time of day, `07:32:00.25`. Leap seconds aren't supported.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+}
+
+/**
+ * An RFC 3339 date-time, `1979-05-27T07:32:00Z`.
+ * Without an offset it's a local date-time, which isn't any one point in time.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateTime {
+    pub date: Date,
+    pub time: Time,
+    /**
+     * In minutes east of UTC
+     */
+    pub offset: Option<i16>,
+}
+
+/**
+ * The units a duration can be written in, biggest first, with their length in nanoseconds
+ */
+pub const DURATION_UNITS: [(&str, u128); 7] = [
+    ("d", 86_400_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidDateTime;
+
+impl Display for InvalidDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid date or time")
+    }
+}
+
+impl std::error::Error for InvalidDateTime {}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl Date {
+    /**
+     * `None` unless it's a real day between the years 0 and 9999
+     */
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        if year > 9999 || !(1..=12).contains(&month) {
+            return None;
+        }
+        if day == 0 || day > days_in_month(year as i64, month) {
+            return None;
+        }
+        Some(Self { year, month, day })
+    }
+
+    /**
+     * Days since 1970-01-01, negative before it
+     */
+    pub fn days_since_epoch(&self) -> i64 {
+        // Howard Hinnant's days_from_civil, counting years from March so the leap day comes last
+        let month = self.month as i64;
+        let year = self.year as i64 - (month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /**
+     * The inverse of `days_since_epoch`, clamped to the years 0 to 9999
+     */
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let days = days.clamp(
+            Self::new(0, 1, 1).unwrap().days_since_epoch(),
+            Self::new(9999, 12, 31).unwrap().days_since_epoch(),
+        ) + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month + 2) / 5 + 1;
+        let month = (month + 2) % 12 + 1;
+        let year = year_of_era + era * 400 + (month <= 2) as i64;
+        Self {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+}
+
+impl Time {
+    pub fn new(hour: u8, minute: u8, second: u8, nanosecond: u32) -> Option<Self> {
+        if hour > 23 || minute > 59 || second > 59 || nanosecond >= 1_000_000_000 {
+            return None;
+        }
+        Some(Self {
+            hour,
+            minute,
+            second,
+            nanosecond,
+        })
+    }
+
+    pub fn seconds_since_midnight(&self) -> i64 {
+        self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+    }
+}
+
+impl DateTime {
+    /**
+     * Seconds and nanoseconds since the Unix epoch, `None` for a local date-time
+     */
+    pub fn timestamp(&self) -> Option<(i64, u32)> {
+        let offset = self.offset? as i64 * 60;
+        let secs = self.date.days_since_epoch() * SECONDS_PER_DAY
+            + self.time.seconds_since_midnight()
+            - offset;
+        Some((secs, self.time.nanosecond))
+    }
+
+    /**
+     * `None` for a local date-time, and for one outside what `SystemTime` holds on this platform,
+     * e.g. before 1601 on Windows
+     */
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        let (secs, nanos) = self.timestamp()?;
+        let time = if secs >= 0 {
+            UNIX_EPOCH.checked_add(Duration::from_secs(secs as u64))?
+        } else {
+            UNIX_EPOCH.checked_sub(Duration::from_secs(secs.unsigned_abs()))?
+        };
+        time.checked_add(Duration::from_nanos(nanos as u64))
+    }
+}
+
+/**
+ * In UTC, clamped to the years 0 to 9999 that RFC 3339 can write
+ */
+impl From<SystemTime> for DateTime {
+    fn from(value: SystemTime) -> Self {
+        let (secs, nanos) = match value.duration_since(UNIX_EPOCH) {
+            Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+            Err(e) => {
+                let before = e.duration();
+                let secs = -(before.as_secs() as i64);
+                match before.subsec_nanos() {
+                    0 => (secs, 0),
+                    nanos => (secs - 1, 1_000_000_000 - nanos),
+                }
+            }
+        };
+        let days = secs.div_euclid(SECONDS_PER_DAY);
+        let date = Date::from_days_since_epoch(days);
+        let (secs, nanos) = match date.days_since_epoch().cmp(&days) {
+            Ordering::Equal => (secs.rem_euclid(SECONDS_PER_DAY), nanos),
+            // Clamped to the start of year 0 or the end of 9999
+            Ordering::Greater => (0, 0),
+            Ordering::Less => (SECONDS_PER_DAY - 1, 999_999_999),
+        };
+        Self {
+            date,
+            time: Time {
+                hour: (secs / 3600) as u8,
+                minute: (secs / 60 % 60) as u8,
+                second: (secs % 60) as u8,
+                nanosecond: nanos,
+            },
+            offset: Some(0),
+        }
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+        if self.nanosecond > 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}T{}", self.date, self.time)?;
+        match self.offset {
+            None => Ok(()),
+            Some(0) => f.write_str("Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                write!(f, "{sign}{:02}:{:02}", offset / 60, offset % 60)
+            }
+        }
+    }
+}
+
+/**
+ * The token for `str`, as long as it's the whole of `str`
+ */
+pub(crate) fn read(str: &str) -> Option<Token> {
+    let token = Tokenizer::new(str).next_token();
+    match token.position() {
+        Some(Position(0, end)) if end == str.chars().count() => Some(token),
+        _ => None,
+    }
+}
+
+impl FromStr for Date {
+    type Err = InvalidDateTime;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match read(str) {
+            Some(Token::Date(date, _)) => Ok(date),
+            _ => Err(InvalidDateTime),
+        }
+    }
+}
+
+impl FromStr for Time {
+    type Err = InvalidDateTime;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match read(str) {
+            Some(Token::Time(time, _)) => Ok(time),
+            _ => Err(InvalidDateTime),
+        }
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = InvalidDateTime;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match read(str) {
+            Some(Token::DateTime(date_time, _)) => Ok(date_time),
+            _ => Err(InvalidDateTime),
+        }
+    }
+}
+
+/**
+ * Reads a duration written like `1h30m`
+ */
+pub fn parse_duration(str: &str) -> Option<Duration> {
+    match read(str) {
+        Some(Token::Duration(duration, _)) => Some(duration),
+        _ => None,
+    }
+}
+
+/**
+ * Writes a duration with the biggest units that fit, e.g. `1h30m` rather than `90m`
+ */
+pub fn format_duration(duration: &Duration) -> String {
+    let mut nanos = duration.as_nanos();
+    if nanos == 0 {
+        return "0s".to_string();
+    }
+    let mut out = String::new();
+    for (unit, size) in DURATION_UNITS {
+        if nanos >= size {
+            out.push_str(&format!("{}{unit}", nanos / size));
+            nanos %= size;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{Date, DateTime, Time, format_duration, parse_duration};
+
+    #[test]
+    fn counts_days() {
+        for (date, days) in [
+            ("1970-01-01", 0),
+            ("2000-03-01", 11_017),
+            ("1969-12-31", -1),
+            ("0000-01-01", -719_528),
+            ("9999-12-31", 2_932_896),
+        ] {
+            let date: Date = date.parse().unwrap();
+            assert_eq!(date.days_since_epoch(), days, "{date}");
+            assert_eq!(Date::from_days_since_epoch(days), date);
+        }
+        assert_eq!(Date::new(2023, 2, 29), None);
+        assert!(Date::new(2024, 2, 29).is_some());
+        assert_eq!(Date::new(1900, 2, 29), None);
+    }
+
+    #[test]
+    fn converts_to_system_time() {
+        let date_time: DateTime = "1979-05-27T00:32:00.5-07:00".parse().unwrap();
+        assert_eq!(date_time.timestamp(), Some((296_638_320, 500_000_000)));
+        let time = date_time.to_system_time().unwrap();
+        assert_eq!(DateTime::from(time).to_string(), "1979-05-27T07:32:00.5Z");
+
+        let before: DateTime = "1969-12-31T23:59:59.25Z".parse().unwrap();
+        let time = before.to_system_time().unwrap();
+        assert_eq!(
+            UNIX_EPOCH.duration_since(time).unwrap(),
+            Duration::from_millis(750)
+        );
+        assert_eq!(DateTime::from(time), before);
+
+        let local: DateTime = "1979-05-27T07:32:00".parse().unwrap();
+        assert_eq!(local.to_system_time(), None);
+
+        // Not every platform's SystemTime reaches these, but they mustn't panic on any of them
+        for edge in ["0000-01-01T00:00:00.5Z", "9999-12-31T23:59:59.999999999Z"] {
+            let date_time: DateTime = edge.parse().unwrap();
+            if let Some(time) = date_time.to_system_time() {
+                assert_eq!(DateTime::from(time), date_time);
+            }
+        }
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(
+            Time::new(7, 5, 0, 120_000_000).unwrap().to_string(),
+            "07:05:00.12"
+        );
+        let date_time: DateTime = "1979-05-27t07:32:00+05:30".parse().unwrap();
+        assert_eq!(date_time.to_string(), "1979-05-27T07:32:00+05:30");
+        assert!("1979-05-27 ".parse::<Date>().is_err());
+
+        let duration = parse_duration("1d2h30m1s500ms").unwrap();
+        assert_eq!(duration, Duration::new(95_401, 500_000_000));
+        assert_eq!(format_duration(&duration), "1d2h30m1s500ms");
+        assert_eq!(format_duration(&Duration::from_secs(90)), "1m30s");
+        assert_eq!(format_duration(&Duration::ZERO), "0s");
+    }
+}
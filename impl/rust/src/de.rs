@@ -0,0 +1,566 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    marker::PhantomData,
+    str::FromStr,
+};
+
+use num_bigint::BigInt;
+use serde::{
+    de::{
+        self,
+        value::{MapDeserializer, StrDeserializer},
+        DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+        VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any, ser, Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    datetime::{self, Date, DateTime, Time},
+    parse::{FennecType, ParseError},
+    quantity::Quantity,
+};
+
+#[derive(Debug)]
+pub enum DeError {
+    Parse(ParseError),
+    Custom(String),
+}
+
+impl Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeError::Parse(e) => write!(f, "{e}"),
+            DeError::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        DeError::Custom(msg.to_string())
+    }
+}
+
+/**
+ * Deserializes any serde type out of a parsed value
+ */
+pub fn from_value<T: DeserializeOwned>(value: &FennecType) -> Result<T, DeError> {
+    T::deserialize(value)
+}
+
+pub fn from_str<T: DeserializeOwned>(str: &str) -> Result<T, DeError> {
+    from_value(&crate::parse(str).map_err(DeError::Parse)?)
+}
+
+impl<'de> Deserializer<'de> for &'de FennecType {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            FennecType::Object(obj) => visitor.visit_map(ObjectAccess {
+                iter: obj.iter(),
+                value: None,
+            }),
+            FennecType::Array(arr) => visitor.visit_seq(ArrayAccess { iter: arr.iter() }),
+            FennecType::String(str) => visitor.visit_borrowed_str(str),
+            FennecType::Float(f) => visitor.visit_f64(*f),
+            FennecType::Int(i) => visitor.visit_i64(*i),
+            FennecType::BigInt(i) => {
+                if let Ok(u) = u64::try_from(i) {
+                    return visitor.visit_u64(u);
+                }
+                if let Ok(i) = i128::try_from(i) {
+                    return visitor.visit_i128(i);
+                }
+                if let Ok(u) = u128::try_from(i) {
+                    return visitor.visit_u128(u);
+                }
+                Err(DeError::Custom(format!("{i} is too big for any int type")))
+            }
+            FennecType::Bool(b) => visitor.visit_bool(*b),
+            FennecType::Null => visitor.visit_unit(),
+            FennecType::DateTime(date_time) => visitor.visit_string(date_time.to_string()),
+            FennecType::Date(date) => visitor.visit_string(date.to_string()),
+            FennecType::Time(time) => visitor.visit_string(time.to_string()),
+            FennecType::Duration(duration) => {
+                visitor.visit_string(datetime::format_duration(duration))
+            }
+            FennecType::Quantity(quantity) => visitor.visit_string(quantity.to_string()),
+            FennecType::Reference(path, _) => {
+                Err(DeError::Custom(format!("unresolved reference to {path}")))
+            }
+        }
+    }
+
+    /**
+     * `Duration` and `SystemTime` come out of the matching literals in the shape serde gives them,
+     * a struct of their seconds and nanoseconds
+     */
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        let parts = match (name, self) {
+            ("Duration", FennecType::Duration(duration)) => {
+                (duration.as_secs(), duration.subsec_nanos())
+            }
+            ("SystemTime", FennecType::DateTime(date_time)) => {
+                match date_time
+                    .timestamp()
+                    .map(|(secs, nanos)| (u64::try_from(secs), nanos))
+                {
+                    Some((Ok(secs), nanos)) => (secs, nanos),
+                    _ => {
+                        return Err(de::Error::invalid_value(
+                            unexpected(self),
+                            &"a date-time with an offset, after 1970",
+                        ))
+                    }
+                }
+            }
+            _ => return self.deserialize_any(visitor),
+        };
+        let [secs, nanos] = fields else {
+            return self.deserialize_any(visitor);
+        };
+        visitor.visit_map(MapDeserializer::new(
+            [(*secs, parts.0), (*nanos, parts.1.into())].into_iter(),
+        ))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            FennecType::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /**
+     * Unit variants are written as strings, other variants as an object with the variant's name as its only key
+     */
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        match self {
+            FennecType::String(str) => visitor.visit_enum(str.as_str().into_deserializer()),
+            FennecType::Object(obj) if obj.len() == 1 => {
+                let (variant, value) = obj.iter().next().expect("We just checked the length!");
+                visitor.visit_enum(EnumValue { variant, value })
+            }
+            _ => Err(de::Error::invalid_type(
+                unexpected(self),
+                &"a string or an object with one key",
+            )),
+        }
+    }
+
+    /**
+     * Sizes and rates come out in their base unit, so `512MiB` is 536870912
+     */
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            FennecType::Quantity(quantity) => match quantity.base_amount() {
+                Some(amount) => visitor.visit_u64(amount),
+                None => Err(de::Error::invalid_value(
+                    unexpected(self),
+                    &"a whole number in the unit's base unit",
+                )),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map identifier ignored_any
+    }
+}
+
+fn unexpected(value: &FennecType) -> de::Unexpected<'_> {
+    match value {
+        FennecType::Object(_) => de::Unexpected::Map,
+        FennecType::Array(_) => de::Unexpected::Seq,
+        FennecType::String(str) => de::Unexpected::Str(str),
+        FennecType::Float(f) => de::Unexpected::Float(*f),
+        FennecType::Int(i) => de::Unexpected::Signed(*i),
+        FennecType::BigInt(i) => match u64::try_from(i) {
+            Ok(u) => de::Unexpected::Unsigned(u),
+            Err(_) => de::Unexpected::Other("int"),
+        },
+        FennecType::Bool(b) => de::Unexpected::Bool(*b),
+        FennecType::Null => de::Unexpected::Unit,
+        FennecType::DateTime(_) => de::Unexpected::Other("date-time"),
+        FennecType::Date(_) => de::Unexpected::Other("date"),
+        FennecType::Time(_) => de::Unexpected::Other("time"),
+        FennecType::Duration(_) => de::Unexpected::Other("duration"),
+        FennecType::Quantity(_) => de::Unexpected::Other("quantity"),
+        FennecType::Reference(_, _) => de::Unexpected::Other("reference"),
+    }
+}
+
+struct ObjectAccess<'de> {
+    iter: std::collections::hash_map::Iter<'de, String, FennecType>,
+    value: Option<&'de FennecType>,
+}
+
+impl<'de> MapAccess<'de> for ObjectAccess<'de> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeError> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                let key: StrDeserializer<DeError> = key.as_str().into_deserializer();
+                seed.deserialize(key).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(value),
+            None => Err(DeError::Custom(
+                "value asked for before its key".to_string(),
+            )),
+        }
+    }
+}
+
+struct ArrayAccess<'de> {
+    iter: std::slice::Iter<'de, FennecType>,
+}
+
+impl<'de> SeqAccess<'de> for ArrayAccess<'de> {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, DeError> {
+        match self.iter.next() {
+            Some(value) => seed.deserialize(value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct EnumValue<'de> {
+    variant: &'de str,
+    value: &'de FennecType,
+}
+
+impl<'de> EnumAccess<'de> for EnumValue<'de> {
+    type Error = DeError;
+    type Variant = &'de FennecType;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), DeError> {
+        let variant: StrDeserializer<DeError> = self.variant.into_deserializer();
+        Ok((seed.deserialize(variant)?, self.value))
+    }
+}
+
+impl<'de> VariantAccess<'de> for &'de FennecType {
+    type Error = DeError;
+
+    fn unit_variant(self) -> Result<(), DeError> {
+        Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, DeError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.deserialize_map(visitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for FennecType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(FennecVisitor)
+    }
+}
+
+struct FennecVisitor;
+
+impl<'de> Visitor<'de> for FennecVisitor {
+    type Value = FennecType;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any Fennec value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<FennecType, E> {
+        Ok(FennecType::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<FennecType, E> {
+        Ok(FennecType::Int(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<FennecType, E> {
+        Ok(BigInt::from(v).into())
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<FennecType, E> {
+        Ok(BigInt::from(v).into())
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<FennecType, E> {
+        Ok(BigInt::from(v).into())
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<FennecType, E> {
+        Ok(FennecType::Float(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<FennecType, E> {
+        Ok(FennecType::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<FennecType, E> {
+        Ok(FennecType::String(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<FennecType, E> {
+        Ok(FennecType::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<FennecType, E> {
+        Ok(FennecType::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<FennecType, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<FennecType, A::Error> {
+        let mut out = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(val) = seq.next_element()? {
+            out.push(val);
+        }
+        Ok(FennecType::Array(out))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<FennecType, A::Error> {
+        let mut out = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((key, val)) = map.next_entry()? {
+            out.insert(key, val);
+        }
+        Ok(FennecType::Object(out))
+    }
+}
+
+impl Serialize for FennecType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            FennecType::Object(obj) => serializer.collect_map(obj),
+            FennecType::Array(arr) => serializer.collect_seq(arr),
+            FennecType::String(str) => serializer.serialize_str(str),
+            FennecType::Float(f) => serializer.serialize_f64(*f),
+            FennecType::Int(i) => serializer.serialize_i64(*i),
+            FennecType::BigInt(i) => {
+                if let Ok(u) = u64::try_from(i) {
+                    return serializer.serialize_u64(u);
+                }
+                if let Ok(i) = i128::try_from(i) {
+                    return serializer.serialize_i128(i);
+                }
+                if let Ok(u) = u128::try_from(i) {
+                    return serializer.serialize_u128(u);
+                }
+                Err(ser::Error::custom(format!("{i} is too big for any int type")))
+            }
+            FennecType::Bool(b) => serializer.serialize_bool(*b),
+            FennecType::Null => serializer.serialize_unit(),
+            FennecType::DateTime(date_time) => date_time.serialize(serializer),
+            FennecType::Date(date) => date.serialize(serializer),
+            FennecType::Time(time) => time.serialize(serializer),
+            FennecType::Duration(duration) => {
+                serializer.serialize_str(&datetime::format_duration(duration))
+            }
+            FennecType::Quantity(quantity) => quantity.serialize(serializer),
+            FennecType::Reference(path, _) => serializer.serialize_str(&format!("&{path}")),
+        }
+    }
+}
+
+/**
+ * Dates, times and quantities go through serde as the text of their literal
+ */
+macro text_serde($($ty:ident $expecting:literal)*) {$(
+    impl Serialize for $ty {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for $ty {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_str(TextVisitor($expecting, PhantomData))
+        }
+    }
+)*}
+
+text_serde!(Date "a date" Time "a time" DateTime "a date-time" Quantity "a number with a unit");
+
+struct TextVisitor<T>(&'static str, PhantomData<T>);
+
+impl<T: FromStr> Visitor<'_> for TextVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        v.parse()
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::HashMap,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    use serde::Deserialize;
+
+    use crate::{datetime::Date, parse::FennecType, quantity::Quantity};
+
+    use super::from_str;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "snake_case")]
+    enum Mode {
+        Dev,
+        Prod,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Server {
+        host: String,
+        port: u16,
+        ratio: f32,
+        mode: Mode,
+        #[serde(default)]
+        debug: bool,
+        timeout: Option<i64>,
+        tags: Vec<String>,
+        extra: HashMap<String, FennecType>,
+    }
+
+    #[test]
+    fn deserializes_structs() {
+        let server: Server = from_str(
+            "host = \"a\" port = 80 ratio = 1 mode = \"prod\" -debug timeout = null tags [ \"x\" ] extra { a = 1 }",
+        )
+        .unwrap();
+
+        assert_eq!(server.host, "a");
+        assert_eq!(server.port, 80);
+        assert_eq!(server.ratio, 1.0);
+        assert_eq!(server.mode, Mode::Prod);
+        assert!(server.debug);
+        assert_eq!(server.timeout, None);
+        assert_eq!(server.tags, vec!["x"]);
+        assert_eq!(server.extra["a"], FennecType::Int(1));
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Ids {
+        mask: u64,
+        wide: i128,
+    }
+
+    #[test]
+    fn deserializes_big_ints() {
+        let ids: Ids = from_str("mask = 0xFFFFFFFFFFFFFFFF wide = -99999999999999999999").unwrap();
+
+        assert_eq!(ids.mask, u64::MAX);
+        assert_eq!(ids.wide, -99999999999999999999);
+        assert!(from_str::<Ids>("mask = 0x1FFFFFFFFFFFFFFFF wide = 0").is_err());
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Schedule {
+        timeout: Duration,
+        started: SystemTime,
+        day: Date,
+    }
+
+    #[test]
+    fn deserializes_dates_and_durations() {
+        let schedule: Schedule =
+            from_str("timeout = 1m30s started = 1970-01-01T01:00:00+01:00 day = 2024-02-29")
+                .unwrap();
+
+        assert_eq!(schedule.timeout, Duration::from_secs(90));
+        assert_eq!(schedule.started, UNIX_EPOCH);
+        assert_eq!(schedule.day, Date::new(2024, 2, 29).unwrap());
+        assert!(from_str::<Schedule>(
+            "timeout = 1s started = 1970-01-01T00:00:00 day = 2024-02-29"
+        )
+        .is_err());
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Limits {
+        max_body: u64,
+        bandwidth: Quantity,
+    }
+
+    #[test]
+    fn deserializes_quantities() {
+        let limits: Limits = from_str("max_body = 512MiB bandwidth = 1.5Gbps").unwrap();
+
+        assert_eq!(limits.max_body, 512 * 1024 * 1024);
+        assert_eq!(limits.bandwidth.to_string(), "1.5Gbps");
+        assert!(from_str::<Limits>("max_body = 0.5B bandwidth = 1Gbps").is_err());
+    }
+
+    #[test]
+    fn reports_mismatches() {
+        let err = from_str::<Server>("host = 1").unwrap_err();
+
+        assert!(err.to_string().contains("invalid type"));
+    }
+}
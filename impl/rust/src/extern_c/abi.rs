@@ -0,0 +1,738 @@
+use std::{
+    ffi::{c_char, CStr},
+    fs, ptr,
+    time::Duration,
+};
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use crate::{
+    datetime::DateTime,
+    parse::FennecType,
+    path::{Path, PathSegment},
+    quantity::Quantity,
+    token::Location,
+};
+
+use super::types::CFennecError;
+
+/**
+ * Bumped whenever functions are added or one's signature or behaviour changes, with a new `FENNEC_<n>`
+ * symbol version for the functions it added. 2 added the big int, date, time, duration and quantity types.
+ */
+pub const FENNEC_ABI_VERSION: u32 = 2;
+
+/**
+ * A parsed value. Only ever handed out as a pointer, so the layout behind it can change freely.
+ */
+pub struct Value(Node);
+
+enum Node {
+    /**
+     * Kept in a list so indices stay put for as long as the value lives
+     */
+    Object(Vec<(Text, Value)>),
+    Array(Vec<Value>),
+    String(Text),
+    Float(f64),
+    Int(i64),
+    /**
+     * Kept as text too, since that's how C gets at it
+     */
+    BigInt(BigInt, Text),
+    Bool(bool),
+    Null,
+    DateTime(DateTime, Text),
+    Date(Text),
+    Time(Text),
+    Duration(Duration),
+    Quantity(Quantity, Text),
+}
+
+/**
+ * Stored with a trailing NUL so C can read it in place
+ */
+struct Text(Box<[u8]>);
+
+impl Text {
+    fn new(str: &str) -> Self {
+        let mut bytes = Vec::with_capacity(str.len() + 1);
+        bytes.extend_from_slice(str.as_bytes());
+        bytes.push(0);
+        Self(bytes.into_boxed_slice())
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.0[..self.0.len() - 1]
+    }
+}
+
+impl From<FennecType> for Value {
+    fn from(value: FennecType) -> Self {
+        Self(match value {
+            FennecType::Object(obj) => Node::Object(
+                obj.into_iter()
+                    .map(|(key, value)| (Text::new(&key), value.into()))
+                    .collect(),
+            ),
+            FennecType::Array(arr) => Node::Array(arr.into_iter().map(Self::from).collect()),
+            FennecType::String(str) => Node::String(Text::new(&str)),
+            FennecType::Float(f) => Node::Float(f),
+            FennecType::Int(i) => Node::Int(i),
+            FennecType::BigInt(i) => {
+                let text = Text::new(&i.to_string());
+                Node::BigInt(i, text)
+            }
+            FennecType::Bool(b) => Node::Bool(b),
+            FennecType::DateTime(date_time) => {
+                let text = Text::new(&date_time.to_string());
+                Node::DateTime(date_time, text)
+            }
+            FennecType::Date(date) => Node::Date(Text::new(&date.to_string())),
+            FennecType::Time(time) => Node::Time(Text::new(&time.to_string())),
+            FennecType::Duration(duration) => Node::Duration(duration),
+            FennecType::Quantity(quantity) => {
+                let text = Text::new(&quantity.to_string());
+                Node::Quantity(quantity, text)
+            }
+            // Parsing resolves every reference, so there are none left by the time a value gets here
+            FennecType::Null | FennecType::Reference(..) => Node::Null,
+        })
+    }
+}
+
+impl Value {
+    fn entry(&self, index: usize) -> Option<(Option<&Text>, &Value)> {
+        match &self.0 {
+            Node::Object(obj) => obj.get(index).map(|(key, value)| (Some(key), value)),
+            Node::Array(arr) => arr.get(index).map(|value| (None, value)),
+            _ => None,
+        }
+    }
+
+    fn get_key(&self, key: &[u8]) -> Option<&Value> {
+        match &self.0 {
+            Node::Object(obj) => obj
+                .iter()
+                .find(|(k, _)| k.as_bytes() == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn get_path(&self, path: &Path) -> Option<&Value> {
+        let mut current = self;
+        for segment in &path.0 {
+            current = match segment {
+                PathSegment::Key(key) => current.get_key(key.as_bytes())?,
+                PathSegment::Index(index) => match &current.0 {
+                    Node::Array(arr) => arr.get(*index)?,
+                    _ => return None,
+                },
+            };
+        }
+        Some(current)
+    }
+}
+
+/**
+ * One of the `FENNEC_TYPE_*` constants. A plain integer since C compilers don't all agree on how big an enum is.
+ * The numbers are part of the ABI, so new types only ever get added at the end.
+ */
+pub type ValueType = u32;
+
+pub const FENNEC_TYPE_OBJECT: ValueType = 0;
+pub const FENNEC_TYPE_ARRAY: ValueType = 1;
+pub const FENNEC_TYPE_STRING: ValueType = 2;
+pub const FENNEC_TYPE_FLOAT: ValueType = 3;
+pub const FENNEC_TYPE_INT: ValueType = 4;
+pub const FENNEC_TYPE_BOOL: ValueType = 5;
+pub const FENNEC_TYPE_NULL: ValueType = 6;
+/**
+ * An int too big for an `int64_t`, see `fennec_value_uint` and `fennec_value_bigint`.
+ * This and the types after it are new in version 2.
+ */
+pub const FENNEC_TYPE_BIGINT: ValueType = 7;
+/**
+ * The date and time types are read as text with `fennec_value_datetime`,
+ * and a date-time with an offset as a Unix timestamp with `fennec_value_timestamp`
+ */
+pub const FENNEC_TYPE_DATETIME: ValueType = 8;
+pub const FENNEC_TYPE_DATE: ValueType = 9;
+pub const FENNEC_TYPE_TIME: ValueType = 10;
+pub const FENNEC_TYPE_DURATION: ValueType = 11;
+/**
+ * A number with a unit like `512MiB`, see `fennec_value_quantity` and `fennec_value_bytes`
+ */
+pub const FENNEC_TYPE_QUANTITY: ValueType = 12;
+
+/**
+ * Why a parse failed
+ */
+pub struct Error(CFennecError);
+
+unsafe fn value<'a>(value: *const Value) -> Option<&'a Value> {
+    if value.is_null() {
+        return None;
+    }
+    Some(&*value)
+}
+
+fn found(value: Option<&Value>) -> *const Value {
+    match value {
+        Some(value) => value,
+        None => ptr::null(),
+    }
+}
+
+unsafe fn parse(str: &str, error: *mut *mut Error) -> *mut Value {
+    match crate::parse(str) {
+        Ok(fen) => opaque_pointer::raw(fen.into()),
+        Err(e) => fail(error, CFennecError::from_parse_error(&e, str)),
+    }
+}
+
+unsafe fn fail(error: *mut *mut Error, e: CFennecError) -> *mut Value {
+    if !error.is_null() {
+        *error = opaque_pointer::raw(Error(e));
+    }
+    ptr::null_mut()
+}
+
+/**
+ * The library keeps every older version's functions, so check that this is at least the
+ * `FENNEC_ABI_VERSION` you built against
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_abi_version() -> u32 {
+    FENNEC_ABI_VERSION
+}
+
+/**
+ * Returns null on failure. If `error` isn't null, it's set to null on success and to a new error on failure.
+ * Free the result with `fennec_value_free`.
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_parse(str: *const c_char, error: *mut *mut Error) -> *mut Value {
+    if !error.is_null() {
+        *error = ptr::null_mut();
+    }
+
+    if str.is_null() {
+        return fail(error, CFennecError::new("str is null", Location(0, 0, 0)));
+    }
+
+    let str = CStr::from_ptr(str);
+    match str.to_str() {
+        Ok(str) => parse(str, error),
+        Err(e) => {
+            let valid = std::str::from_utf8_unchecked(&str.to_bytes()[..e.valid_up_to()]);
+            let location = Location::of(valid, usize::MAX);
+            fail(error, CFennecError::new("invalid UTF-8", location))
+        }
+    }
+}
+
+/**
+ * Like `fennec_parse`, but reads the document from a file
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_parse_file(
+    filename: *const c_char,
+    error: *mut *mut Error,
+) -> *mut Value {
+    if !error.is_null() {
+        *error = ptr::null_mut();
+    }
+
+    if filename.is_null() {
+        return fail(
+            error,
+            CFennecError::new("filename is null", Location(0, 0, 0)),
+        );
+    }
+
+    let Ok(filename) = CStr::from_ptr(filename).to_str() else {
+        return fail(
+            error,
+            CFennecError::new("filename isn't UTF-8", Location(0, 0, 0)),
+        );
+    };
+
+    match fs::read_to_string(filename) {
+        Ok(file) => parse(&file, error),
+        Err(e) => fail(error, CFennecError::new(&e.to_string(), Location(0, 0, 0))),
+    }
+}
+
+/**
+ * Only call this on the values you get from `fennec_parse` and `fennec_parse_file`, never on their children
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_free(value: *mut Value) {
+    if value.is_null() {
+        return;
+    }
+    let _ = opaque_pointer::own_back(value);
+}
+
+/**
+ * Owned by the error, so it's freed along with it
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_error_message(error: *const Error) -> *const c_char {
+    match error.as_ref() {
+        Some(error) => error.0.message.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/**
+ * Lines and columns count from 1.
+ * All three are 0 when the error isn't about the input's contents, e.g. when a file can't be read.
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_error_line(error: *const Error) -> usize {
+    error.as_ref().map_or(0, |error| error.0.location.0)
+}
+
+#[no_mangle]
+unsafe extern "C" fn fennec_error_column(error: *const Error) -> usize {
+    error.as_ref().map_or(0, |error| error.0.location.1)
+}
+
+/**
+ * In bytes from the start of the input
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_error_offset(error: *const Error) -> usize {
+    error.as_ref().map_or(0, |error| error.0.location.2)
+}
+
+#[no_mangle]
+unsafe extern "C" fn fennec_error_free(error: *mut Error) {
+    if error.is_null() {
+        return;
+    }
+    let _ = opaque_pointer::own_back(error);
+}
+
+/**
+ * `FENNEC_TYPE_NULL` for a null pointer too
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_type(val: *const Value) -> ValueType {
+    let Some(val) = value(val) else {
+        return FENNEC_TYPE_NULL;
+    };
+    match val.0 {
+        Node::Object(_) => FENNEC_TYPE_OBJECT,
+        Node::Array(_) => FENNEC_TYPE_ARRAY,
+        Node::String(_) => FENNEC_TYPE_STRING,
+        Node::Float(_) => FENNEC_TYPE_FLOAT,
+        Node::Int(_) => FENNEC_TYPE_INT,
+        Node::BigInt(..) => FENNEC_TYPE_BIGINT,
+        Node::Bool(_) => FENNEC_TYPE_BOOL,
+        Node::Null => FENNEC_TYPE_NULL,
+        Node::DateTime(..) => FENNEC_TYPE_DATETIME,
+        Node::Date(_) => FENNEC_TYPE_DATE,
+        Node::Time(_) => FENNEC_TYPE_TIME,
+        Node::Duration(_) => FENNEC_TYPE_DURATION,
+        Node::Quantity(..) => FENNEC_TYPE_QUANTITY,
+    }
+}
+
+/**
+ * Entries in an object or elements in an array, 0 for anything else
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_len(val: *const Value) -> usize {
+    match value(val).map(|val| &val.0) {
+        Some(Node::Object(obj)) => obj.len(),
+        Some(Node::Array(arr)) => arr.len(),
+        _ => 0,
+    }
+}
+
+/**
+ * Looks up a path like `server.ports[0]` or `a."quoted key"` under `val`, null if nothing is there.
+ * The result is owned by `val`.
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_get(val: *const Value, path: *const c_char) -> *const Value {
+    if path.is_null() {
+        return ptr::null();
+    }
+    let Ok(path) = CStr::from_ptr(path).to_str().map(str::parse::<Path>) else {
+        return ptr::null();
+    };
+    let (Some(val), Ok(path)) = (value(val), path) else {
+        return ptr::null();
+    };
+    found(val.get_path(&path))
+}
+
+/**
+ * Null if `obj` isn't an object or doesn't have `key`
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_object_get(obj: *const Value, key: *const c_char) -> *const Value {
+    if key.is_null() {
+        return ptr::null();
+    }
+    found(value(obj).and_then(|obj| obj.get_key(CStr::from_ptr(key).to_bytes())))
+}
+
+/**
+ * The key of the entry at `index`, null if `obj` isn't an object or `index` is out of range.
+ * Entries keep their indices for as long as the value lives, so walk them with this and `fennec_value_at`:
+ * ```c
+ * for (size_t i = 0; i < fennec_value_len(obj); i++) {
+ *     printf("%s\n", fennec_object_key(obj, i));
+ * }
+ * ```
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_object_key(obj: *const Value, index: usize) -> *const c_char {
+    match value(obj).and_then(|obj| obj.entry(index)) {
+        Some((Some(key), _)) => key.0.as_ptr() as *const c_char,
+        _ => ptr::null(),
+    }
+}
+
+/**
+ * The element at `index` of an array, or the value of the entry at `index` of an object.
+ * Null if `index` is out of range.
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_at(val: *const Value, index: usize) -> *const Value {
+    found(
+        value(val)
+            .and_then(|val| val.entry(index))
+            .map(|(_, val)| val),
+    )
+}
+
+/**
+ * Sets `out` to a NUL-terminated string owned by `val`. `len` may be null, and is only needed for strings that contain NULs.
+ * Returns false, leaving both alone, if `val` isn't a string.
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_string(
+    val: *const Value,
+    out: *mut *const c_char,
+    len: *mut usize,
+) -> bool {
+    let Some(Node::String(str)) = value(val).map(|val| &val.0) else {
+        return false;
+    };
+    if !out.is_null() {
+        *out = str.0.as_ptr() as *const c_char;
+    }
+    if !len.is_null() {
+        *len = str.as_bytes().len();
+    }
+    true
+}
+
+/**
+ * Returns false, leaving `out` alone, if `val` isn't an int
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_int(val: *const Value, out: *mut i64) -> bool {
+    let Some(Node::Int(i)) = value(val).map(|val| &val.0) else {
+        return false;
+    };
+    if !out.is_null() {
+        *out = *i;
+    }
+    true
+}
+
+/**
+ * Takes both kinds of int, returning false if `val` is negative or doesn't fit
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_uint(val: *const Value, out: *mut u64) -> bool {
+    let u = match value(val).map(|val| &val.0) {
+        Some(Node::Int(i)) => u64::try_from(*i).ok(),
+        Some(Node::BigInt(i, _)) => u64::try_from(i).ok(),
+        _ => None,
+    };
+    let Some(u) = u else {
+        return false;
+    };
+    if !out.is_null() {
+        *out = u;
+    }
+    true
+}
+
+/**
+ * Sets `out` to a `FENNEC_TYPE_BIGINT` written in decimal, NUL-terminated and owned by `val`
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_bigint(val: *const Value, out: *mut *const c_char) -> bool {
+    let Some(Node::BigInt(_, text)) = value(val).map(|val| &val.0) else {
+        return false;
+    };
+    if !out.is_null() {
+        *out = text.0.as_ptr() as *const c_char;
+    }
+    true
+}
+
+/**
+ * Ints are read as floats too
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_float(val: *const Value, out: *mut f64) -> bool {
+    let f = match value(val).map(|val| &val.0) {
+        Some(Node::Float(f)) => *f,
+        Some(Node::Int(i)) => *i as f64,
+        Some(Node::BigInt(i, _)) => i.to_f64().unwrap_or(f64::NAN),
+        _ => return false,
+    };
+    if !out.is_null() {
+        *out = f;
+    }
+    true
+}
+
+#[no_mangle]
+unsafe extern "C" fn fennec_value_bool(val: *const Value, out: *mut bool) -> bool {
+    let Some(Node::Bool(b)) = value(val).map(|val| &val.0) else {
+        return false;
+    };
+    if !out.is_null() {
+        *out = *b;
+    }
+    true
+}
+
+/**
+ * Sets `out` to a date-time, date or time in its literal form, e.g. `1979-05-27T07:32:00Z`,
+ * NUL-terminated and owned by `val`
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_datetime(val: *const Value, out: *mut *const c_char) -> bool {
+    let text = match value(val).map(|val| &val.0) {
+        Some(Node::DateTime(_, text) | Node::Date(text) | Node::Time(text)) => text,
+        _ => return false,
+    };
+    if !out.is_null() {
+        *out = text.0.as_ptr() as *const c_char;
+    }
+    true
+}
+
+/**
+ * Seconds and nanoseconds since the Unix epoch.
+ * Returns false for a date-time without an offset, since it isn't a point in time on its own.
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_timestamp(
+    val: *const Value,
+    secs: *mut i64,
+    nanos: *mut u32,
+) -> bool {
+    let Some(Node::DateTime(date_time, _)) = value(val).map(|val| &val.0) else {
+        return false;
+    };
+    let Some((s, n)) = date_time.timestamp() else {
+        return false;
+    };
+    if !secs.is_null() {
+        *secs = s;
+    }
+    if !nanos.is_null() {
+        *nanos = n;
+    }
+    true
+}
+
+#[no_mangle]
+unsafe extern "C" fn fennec_value_duration(
+    val: *const Value,
+    secs: *mut u64,
+    nanos: *mut u32,
+) -> bool {
+    let Some(Node::Duration(duration)) = value(val).map(|val| &val.0) else {
+        return false;
+    };
+    if !secs.is_null() {
+        *secs = duration.as_secs();
+    }
+    if !nanos.is_null() {
+        *nanos = duration.subsec_nanos();
+    }
+    true
+}
+
+/**
+ * Sets `out` to the quantity as it was written, e.g. `512MiB`, NUL-terminated and owned by `val`
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_quantity(val: *const Value, out: *mut *const c_char) -> bool {
+    let Some(Node::Quantity(_, text)) = value(val).map(|val| &val.0) else {
+        return false;
+    };
+    if !out.is_null() {
+        *out = text.0.as_ptr() as *const c_char;
+    }
+    true
+}
+
+/**
+ * A size like `512MiB` in bytes.
+ * Returns false for other units, and for sizes that aren't a whole number of bytes or don't fit.
+ */
+#[no_mangle]
+unsafe extern "C" fn fennec_value_bytes(val: *const Value, out: *mut u64) -> bool {
+    let Some(Node::Quantity(quantity, _)) = value(val).map(|val| &val.0) else {
+        return false;
+    };
+    let Some(bytes) = quantity.as_bytes() else {
+        return false;
+    };
+    if !out.is_null() {
+        *out = bytes;
+    }
+    true
+}
+
+/**
+ * Binds each function to the node of `fennec_abi.map` for the version that added it, so a plugin built
+ * against version 1 asks for `fennec_parse@FENNEC_1` and keeps working next to a library that also
+ * exports a `FENNEC_2` one. Needs an ELF linker that takes `.symver` with a version script, which
+ * `rust-lld` does.
+ */
+#[cfg(all(feature = "symbol-versioning", target_os = "linux"))]
+macro_rules! versioned {
+    ($version:ident: $($name:ident)*) => {$(
+        std::arch::global_asm!(concat!(
+            ".symver ", stringify!($name), ", ", stringify!($name), "@@@", stringify!($version)
+        ));
+    )*};
+}
+
+#[cfg(all(feature = "symbol-versioning", target_os = "linux"))]
+versioned!(FENNEC_1:
+    fennec_abi_version
+    fennec_parse fennec_parse_file fennec_value_free
+    fennec_error_message fennec_error_line fennec_error_column fennec_error_offset fennec_error_free
+    fennec_value_type fennec_value_len fennec_value_get fennec_object_get fennec_object_key fennec_value_at
+    fennec_value_string fennec_value_int fennec_value_float fennec_value_bool
+);
+
+#[cfg(all(feature = "symbol-versioning", target_os = "linux"))]
+versioned!(FENNEC_2:
+    fennec_value_uint fennec_value_bigint fennec_value_datetime fennec_value_timestamp
+    fennec_value_duration fennec_value_quantity fennec_value_bytes
+);
+
+#[cfg(test)]
+mod test {
+    use std::{
+        ffi::{CStr, CString},
+        ptr,
+    };
+
+    use super::{
+        fennec_error_free, fennec_error_line, fennec_error_message, fennec_object_get,
+        fennec_object_key, fennec_parse, fennec_value_at, fennec_value_datetime,
+        fennec_value_duration, fennec_value_float, fennec_value_free, fennec_value_get,
+        fennec_value_int, fennec_value_len, fennec_value_string, fennec_value_timestamp,
+        fennec_value_type, FENNEC_TYPE_ARRAY, FENNEC_TYPE_DATE, FENNEC_TYPE_DATETIME,
+        FENNEC_TYPE_NULL,
+    };
+
+    #[test]
+    fn reads_values() {
+        unsafe {
+            let doc = CString::new("server { name = \"a\" ports [ 80 443 ] } ratio = 1").unwrap();
+            let root = fennec_parse(doc.as_ptr(), ptr::null_mut());
+            let path = |str: &str| CString::new(str).unwrap();
+
+            let ports = fennec_value_get(root, path("server.ports").as_ptr());
+            assert_eq!(fennec_value_type(ports), FENNEC_TYPE_ARRAY);
+            assert_eq!(fennec_value_len(ports), 2);
+            let mut port = 0;
+            assert!(fennec_value_int(fennec_value_at(ports, 1), &mut port));
+            assert_eq!(port, 443);
+
+            let mut ratio = 0.0;
+            let ratio_value = fennec_object_get(root, path("ratio").as_ptr());
+            assert!(fennec_value_float(ratio_value, &mut ratio));
+            assert_eq!(ratio, 1.0);
+
+            let server = fennec_object_get(root, path("server").as_ptr());
+            let keys = (0..fennec_value_len(server))
+                .map(|i| {
+                    CStr::from_ptr(fennec_object_key(server, i))
+                        .to_str()
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            assert!(keys.contains(&"name") && keys.contains(&"ports"));
+
+            let (mut name, mut len) = (ptr::null(), 0);
+            let name_value = fennec_value_get(root, path("server.name").as_ptr());
+            assert!(fennec_value_string(name_value, &mut name, &mut len));
+            assert_eq!((CStr::from_ptr(name).to_str(), len), (Ok("a"), 1));
+            assert!(!fennec_value_string(ports, &mut name, ptr::null_mut()));
+
+            assert!(fennec_value_get(root, path("server.missing").as_ptr()).is_null());
+            assert!(fennec_object_key(ports, 0).is_null());
+            assert_eq!(fennec_value_type(ptr::null()), FENNEC_TYPE_NULL);
+
+            fennec_value_free(root);
+        }
+    }
+
+    #[test]
+    fn reads_dates_and_durations() {
+        unsafe {
+            let doc =
+                CString::new("at = 1970-01-02T00:00:01.5Z day = 2024-02-29 wait = 1m30s").unwrap();
+            let root = fennec_parse(doc.as_ptr(), ptr::null_mut());
+            let path = |str: &str| CString::new(str).unwrap();
+
+            let at = fennec_value_get(root, path("at").as_ptr());
+            assert_eq!(fennec_value_type(at), FENNEC_TYPE_DATETIME);
+            let (mut secs, mut nanos) = (0, 0);
+            assert!(fennec_value_timestamp(at, &mut secs, &mut nanos));
+            assert_eq!((secs, nanos), (86401, 500_000_000));
+
+            let day = fennec_value_get(root, path("day").as_ptr());
+            assert_eq!(fennec_value_type(day), FENNEC_TYPE_DATE);
+            let mut text = ptr::null();
+            assert!(fennec_value_datetime(day, &mut text));
+            assert_eq!(CStr::from_ptr(text).to_str(), Ok("2024-02-29"));
+            assert!(!fennec_value_timestamp(day, &mut secs, &mut nanos));
+
+            let (mut secs, mut nanos) = (0, 0);
+            let wait = fennec_value_get(root, path("wait").as_ptr());
+            assert!(fennec_value_duration(wait, &mut secs, &mut nanos));
+            assert_eq!((secs, nanos), (90, 0));
+
+            fennec_value_free(root);
+        }
+    }
+
+    #[test]
+    fn reports_errors() {
+        unsafe {
+            let doc = CString::new("a = 1\nb = ]").unwrap();
+            let mut error = ptr::null_mut();
+            assert!(fennec_parse(doc.as_ptr(), &mut error).is_null());
+            assert_eq!(
+                CStr::from_ptr(fennec_error_message(error)).to_str(),
+                Ok("unexpected ']'")
+            );
+            assert_eq!(fennec_error_line(error), 2);
+            fennec_error_free(error);
+        }
+    }
+}
@@ -4,10 +4,19 @@ use std::{
     ptr,
 };
 
-use self::types::CFennecType;
+use crate::token::Location;
 
+use self::types::{CFennecError, CFennecType};
+
+/** cbindgen:ignore */
+mod abi;
+mod query;
 mod types;
+mod write;
 
+/**
+ * Returns an Error value if `str` doesn't parse, see `FennecConfig_ParseStringEx` for why
+ */
 #[no_mangle]
 unsafe extern "C" fn FennecConfig_ParseString(str: *const c_char) -> *const CFennecType {
     if str.is_null() {
@@ -38,6 +47,129 @@ unsafe extern "C" fn FennecConfig_ParseFile(filename: *const c_char) -> *const C
     opaque_pointer::raw(crate::parse(&file).into())
 }
 
+/**
+ * Returns null on failure. If `error` isn't null, it's set to null on success and to a new error on failure.
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_ParseStringEx(
+    str: *const c_char,
+    error: *mut *const CFennecError,
+) -> *const CFennecType {
+    if !error.is_null() {
+        *error = ptr::null();
+    }
+
+    if str.is_null() {
+        return fail(error, CFennecError::new("str is null", Location(0, 0, 0)));
+    }
+
+    let str = CStr::from_ptr(str);
+    match str.to_str() {
+        Ok(str) => parse_ex(str, error),
+        Err(e) => {
+            let valid = std::str::from_utf8_unchecked(&str.to_bytes()[..e.valid_up_to()]);
+            let location = Location::of(valid, usize::MAX);
+            fail(error, CFennecError::new("invalid UTF-8", location))
+        }
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_ParseFileEx(
+    filename: *const c_char,
+    error: *mut *const CFennecError,
+) -> *const CFennecType {
+    if !error.is_null() {
+        *error = ptr::null();
+    }
+
+    if filename.is_null() {
+        return fail(
+            error,
+            CFennecError::new("filename is null", Location(0, 0, 0)),
+        );
+    }
+
+    let Ok(filename) = CStr::from_ptr(filename).to_str() else {
+        return fail(
+            error,
+            CFennecError::new("filename isn't UTF-8", Location(0, 0, 0)),
+        );
+    };
+
+    match fs::read_to_string(filename) {
+        Ok(file) => parse_ex(&file, error),
+        Err(e) => fail(error, CFennecError::new(&e.to_string(), Location(0, 0, 0))),
+    }
+}
+
+unsafe fn parse_ex(str: &str, error: *mut *const CFennecError) -> *const CFennecType {
+    match crate::parse(str) {
+        Ok(fen) => opaque_pointer::raw(fen.into()),
+        Err(e) => fail(error, CFennecError::from_parse_error(&e, str)),
+    }
+}
+
+unsafe fn fail(error: *mut *const CFennecError, e: CFennecError) -> *const CFennecType {
+    if !error.is_null() {
+        *error = opaque_pointer::raw(e);
+    }
+    ptr::null()
+}
+
+/**
+ * Owned by the error, so it's freed along with it
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_Error_Message(error: *const CFennecError) -> *const c_char {
+    if error.is_null() {
+        return ptr::null();
+    }
+    (*error).message.as_ptr()
+}
+
+/**
+ * Lines and columns count from 1.
+ * All three are 0 when the error isn't about the input's contents, e.g. when a file can't be read.
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_Error_Line(error: *const CFennecError) -> usize {
+    if error.is_null() {
+        return 0;
+    }
+    (*error).location.0
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_Error_Column(error: *const CFennecError) -> usize {
+    if error.is_null() {
+        return 0;
+    }
+    (*error).location.1
+}
+
+/**
+ * In bytes from the start of the input
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_Error_Offset(error: *const CFennecError) -> usize {
+    if error.is_null() {
+        return 0;
+    }
+    (*error).location.2
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_Error_Free(error: *const CFennecError) {
+    if error.is_null() {
+        return;
+    }
+    let _ = opaque_pointer::own_back(error as *mut CFennecError);
+}
+
+/**
+ * Only call this on the values you get from the Parse* functions, never on their children
+ */
 #[no_mangle]
 unsafe extern "C" fn FennecConfig_FennecValue_Free(fen: *const CFennecType) {
     if fen.is_null() {
@@ -48,11 +180,18 @@ unsafe extern "C" fn FennecConfig_FennecValue_Free(fen: *const CFennecType) {
 
 #[cfg(test)]
 mod test {
-    use std::ffi::CString;
+    use std::{
+        ffi::{CStr, CString},
+        ptr,
+    };
 
-    use crate::extern_c::types::CFennecType;
+    use crate::extern_c::types::CFennecView;
 
-    use super::{FennecConfig_FennecValue_Free, FennecConfig_ParseString, FennecConfig_ParseFile};
+    use super::{
+        FennecConfig_Error_Column, FennecConfig_Error_Free, FennecConfig_Error_Line,
+        FennecConfig_Error_Message, FennecConfig_Error_Offset, FennecConfig_FennecValue_Free,
+        FennecConfig_ParseFile, FennecConfig_ParseString, FennecConfig_ParseStringEx,
+    };
 
     #[test]
     fn parse_file() {
@@ -65,7 +204,7 @@ mod test {
 
             assert!(!fen.is_null());
 
-            assert!(if let CFennecType::Object(_, _, _, _, _) = *fen { true } else { false });
+            assert!(if let CFennecView::Object(_) = (*fen).view() { true } else { false });
 
             FennecConfig_FennecValue_Free(fen);
         }
@@ -82,7 +221,7 @@ mod test {
 
             assert!(!fen.is_null());
 
-            assert!(if let CFennecType::Object(_, _, _, _, _) = *fen { true } else { false });
+            assert!(if let CFennecView::Object(_) = (*fen).view() { true } else { false });
 
             FennecConfig_FennecValue_Free(fen);
         }
@@ -99,7 +238,7 @@ mod test {
 
             assert!(!fen_1.is_null());
 
-            assert!(if let CFennecType::Object(_, _, _, _, _) = *fen_1 { true } else { false });
+            assert!(if let CFennecView::Object(_) = (*fen_1).view() { true } else { false });
 
             FennecConfig_FennecValue_Free(fen_1);
 
@@ -111,9 +250,38 @@ mod test {
 
             assert!(!fen_2.is_null());
 
-            assert!(if let CFennecType::Object(_, _, _, _, _) = *fen_2 { true } else { false });
+            assert!(if let CFennecView::Object(_) = (*fen_2).view() { true } else { false });
 
             println!("{fen_1:?} {fen_2:?}");
         }
     }
+
+    #[test]
+    fn parse_string_ex_reports_errors() {
+        unsafe {
+            let str = CString::new("a = 1\nb { c = ] }").unwrap();
+            let mut error = ptr::null();
+            let fen = FennecConfig_ParseStringEx(str.as_ptr(), &mut error);
+
+            assert!(fen.is_null());
+            assert!(!error.is_null());
+            assert_eq!(
+                CStr::from_ptr(FennecConfig_Error_Message(error)).to_str(),
+                Ok("unexpected ']'")
+            );
+            assert_eq!(FennecConfig_Error_Line(error), 2);
+            assert_eq!(FennecConfig_Error_Column(error), 9);
+            assert_eq!(FennecConfig_Error_Offset(error), 14);
+
+            FennecConfig_Error_Free(error);
+
+            let str = CString::new("a = 1").unwrap();
+            let fen = FennecConfig_ParseStringEx(str.as_ptr(), &mut error);
+
+            assert!(!fen.is_null());
+            assert!(error.is_null());
+
+            FennecConfig_FennecValue_Free(fen);
+        }
+    }
 }
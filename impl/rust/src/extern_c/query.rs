@@ -0,0 +1,637 @@
+use std::{
+    ffi::{c_char, CStr},
+    ptr,
+};
+
+use crate::{datetime::DateTime, path::Path, quantity::Quantity};
+
+use super::types::{CFennecArray, CFennecObject, CFennecType, CFennecView};
+
+/**
+ * Walks an object or array one entry at a time. Lives on the C side's stack, so there's nothing to free.
+ */
+#[repr(C)]
+pub struct CFennecIter {
+    value: *const CFennecType,
+    index: usize,
+}
+
+unsafe fn value<'a>(fen: *const CFennecType) -> Option<&'a CFennecType> {
+    if fen.is_null() {
+        return None;
+    }
+    Some(&*fen)
+}
+
+unsafe fn view<'a>(fen: *const CFennecType) -> Option<CFennecView<'a>> {
+    value(fen).map(CFennecType::view)
+}
+
+unsafe fn str<'a>(str: *const c_char) -> Option<&'a str> {
+    if str.is_null() {
+        return None;
+    }
+    CStr::from_ptr(str).to_str().ok()
+}
+
+/**
+ * Looks up a path like `a.b[0]` under `fen`
+ */
+unsafe fn lookup<'a>(fen: *const CFennecType, path: *const c_char) -> Option<&'a CFennecType> {
+    let path: Path = str(path)?.parse().ok()?;
+    value(fen)?.get_path(&path)
+}
+
+fn found(fen: Option<&CFennecType>) -> *const CFennecType {
+    match fen {
+        Some(fen) => fen,
+        None => ptr::null(),
+    }
+}
+
+/**
+ * Looks up a path like `server.ports[0]` or `a."quoted key"` under `fen`, null if nothing is there.
+ * The result is owned by `fen`.
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_Get(
+    fen: *const CFennecType,
+    path: *const c_char,
+) -> *const CFennecType {
+    found(lookup(fen, path))
+}
+
+/**
+ * Null if `fen` isn't an object or doesn't have the key
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_ObjectGet(
+    fen: *const CFennecType,
+    key: *const c_char,
+) -> *const CFennecType {
+    let Some(key) = str(key) else {
+        return ptr::null();
+    };
+    found(value(fen).and_then(|fen| fen.get_key(key)))
+}
+
+/**
+ * Null if `fen` isn't an array or `index` is out of bounds
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_ArrayGet(
+    fen: *const CFennecType,
+    index: usize,
+) -> *const CFennecType {
+    found(value(fen).and_then(|fen| fen.get_index(index)))
+}
+
+/**
+ * The number of entries in an object or array, 0 for anything else
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_Len(fen: *const CFennecType) -> usize {
+    value(fen).map_or(0, CFennecType::len)
+}
+
+/**
+ * Writes the value at `path` to `out` if it's there and has the right type
+ */
+unsafe fn get<T>(
+    fen: *const CFennecType,
+    path: *const c_char,
+    out: *mut T,
+    read: impl FnOnce(&CFennecType) -> Option<T>,
+) -> bool {
+    let Some(val) = lookup(fen, path).and_then(read) else {
+        return false;
+    };
+    if !out.is_null() {
+        *out = val;
+    }
+    true
+}
+
+/**
+ * Like `get`, for values that come out in two parts. Either of `a` and `b` may be null.
+ */
+unsafe fn get_pair<A, B>(
+    fen: *const CFennecType,
+    path: *const c_char,
+    a: *mut A,
+    b: *mut B,
+    read: impl FnOnce(&CFennecType) -> Option<(A, B)>,
+) -> bool {
+    let Some((val_a, val_b)) = lookup(fen, path).and_then(read) else {
+        return false;
+    };
+    if !a.is_null() {
+        *a = val_a;
+    }
+    if !b.is_null() {
+        *b = val_b;
+    }
+    true
+}
+
+/**
+ * The typed getters look up `path` like `FennecConfig_Get`, an empty path reading `fen` itself.
+ * They return false without touching `out` if the value isn't there or has another type.
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_GetString(
+    fen: *const CFennecType,
+    path: *const c_char,
+    out: *mut *const c_char,
+) -> bool {
+    get(fen, path, out, |fen| match fen.view() {
+        CFennecView::String(str) => Some(str),
+        _ => None,
+    })
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_GetInt(
+    fen: *const CFennecType,
+    path: *const c_char,
+    out: *mut i64,
+) -> bool {
+    get(fen, path, out, |fen| match fen.view() {
+        CFennecView::Int(i) => Some(i),
+        _ => None,
+    })
+}
+
+/**
+ * Ints are read as floats too
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_GetFloat(
+    fen: *const CFennecType,
+    path: *const c_char,
+    out: *mut f64,
+) -> bool {
+    get(fen, path, out, |fen| match fen.view() {
+        CFennecView::Float(f) => Some(f),
+        CFennecView::Int(i) => Some(i as f64),
+        CFennecView::BigInt(i) => CStr::from_ptr(i).to_str().ok()?.parse().ok(),
+        _ => None,
+    })
+}
+
+/**
+ * Reads ints that fit in a `uint64_t`, including the ones too big for `FennecConfig_GetInt`
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_GetUInt(
+    fen: *const CFennecType,
+    path: *const c_char,
+    out: *mut u64,
+) -> bool {
+    get(fen, path, out, |fen| match fen.view() {
+        CFennecView::Int(i) => u64::try_from(i).ok(),
+        CFennecView::BigInt(i) => CStr::from_ptr(i).to_str().ok()?.parse().ok(),
+        _ => None,
+    })
+}
+
+/**
+ * Only for ints too big for an `int64_t`, which are given as decimal strings owned by `fen`
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_GetBigInt(
+    fen: *const CFennecType,
+    path: *const c_char,
+    out: *mut *const c_char,
+) -> bool {
+    get(fen, path, out, |fen| match fen.view() {
+        CFennecView::BigInt(i) => Some(i),
+        _ => None,
+    })
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_GetBool(
+    fen: *const CFennecType,
+    path: *const c_char,
+    out: *mut bool,
+) -> bool {
+    get(fen, path, out, |fen| match fen.view() {
+        CFennecView::Bool(b) => Some(b),
+        _ => None,
+    })
+}
+
+/**
+ * Reads date-times, dates and times as text owned by `fen`, e.g. `1979-05-27T07:32:00Z`
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_GetDateTime(
+    fen: *const CFennecType,
+    path: *const c_char,
+    out: *mut *const c_char,
+) -> bool {
+    get(fen, path, out, |fen| match fen.view() {
+        CFennecView::DateTime(str) | CFennecView::Date(str) | CFennecView::Time(str) => Some(str),
+        _ => None,
+    })
+}
+
+/**
+ * Seconds and nanoseconds since the Unix epoch. Only date-times with an offset are a point in time.
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_GetTimestamp(
+    fen: *const CFennecType,
+    path: *const c_char,
+    secs: *mut i64,
+    nanos: *mut u32,
+) -> bool {
+    get_pair(fen, path, secs, nanos, |fen| match fen.view() {
+        CFennecView::DateTime(str) => CStr::from_ptr(str)
+            .to_str()
+            .ok()?
+            .parse::<DateTime>()
+            .ok()?
+            .timestamp(),
+        _ => None,
+    })
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_GetDuration(
+    fen: *const CFennecType,
+    path: *const c_char,
+    secs: *mut u64,
+    nanos: *mut u32,
+) -> bool {
+    get_pair(fen, path, secs, nanos, |fen| match fen.view() {
+        CFennecView::Duration(secs, nanos) => Some((secs, nanos)),
+        _ => None,
+    })
+}
+
+/**
+ * Reads a number with a unit as it was written, e.g. `512MiB`, owned by `fen`
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_GetQuantity(
+    fen: *const CFennecType,
+    path: *const c_char,
+    out: *mut *const c_char,
+) -> bool {
+    get(fen, path, out, |fen| match fen.view() {
+        CFennecView::Quantity(str) => Some(str),
+        _ => None,
+    })
+}
+
+/**
+ * Reads a size like `512MiB` in bytes.
+ * False for other units, and for sizes that aren't a whole number of bytes.
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_GetBytes(
+    fen: *const CFennecType,
+    path: *const c_char,
+    out: *mut u64,
+) -> bool {
+    get(fen, path, out, |fen| match fen.view() {
+        CFennecView::Quantity(str) => CStr::from_ptr(str)
+            .to_str()
+            .ok()?
+            .parse::<Quantity>()
+            .ok()?
+            .as_bytes(),
+        _ => None,
+    })
+}
+
+/**
+ * Walks the entries of an object or array:
+ *
+ * ```c
+ * FennecConfig_Iter iter = FennecConfig_Iterate(fen);
+ * const char *key;
+ * const FennecConfig_FennecValue *value;
+ * while (FennecConfig_IterNext(&iter, &key, &value)) { ... }
+ * ```
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_Iterate(fen: *const CFennecType) -> CFennecIter {
+    CFennecIter {
+        value: fen,
+        index: 0,
+    }
+}
+
+/**
+ * Moves to the next entry, returning false once there are none left.
+ * `key` is set to null for array elements.
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_IterNext(
+    iter: *mut CFennecIter,
+    key: *mut *const c_char,
+    out: *mut *const CFennecType,
+) -> bool {
+    if iter.is_null() {
+        return false;
+    }
+    let iter = &mut *iter;
+    let Some((k, v)) = value(iter.value).and_then(|fen| fen.entry(iter.index)) else {
+        return false;
+    };
+    iter.index += 1;
+
+    if !key.is_null() {
+        *key = k.map_or(ptr::null(), CStr::as_ptr);
+    }
+    if !out.is_null() {
+        *out = v;
+    }
+    true
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_IsObject(fen: *const CFennecType) -> bool {
+    matches!(view(fen), Some(CFennecView::Object(_)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_GetObject(
+    fen: *const CFennecType,
+) -> *const CFennecObject {
+    match view(fen) {
+        Some(CFennecView::Object(obj)) => obj,
+        _ => ptr::null(),
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_Object_HasKey(
+    obj: *const CFennecObject,
+    key: *const c_char,
+) -> bool {
+    !FennecConfig_FennecValue_Object_GetKey(obj, key).is_null()
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_Object_GetKey(
+    obj: *const CFennecObject,
+    key: *const c_char,
+) -> *const CFennecType {
+    let (Some(obj), Some(key)) = (obj.as_ref(), str(key)) else {
+        return ptr::null();
+    };
+    found(obj.get(key))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_IsArray(fen: *const CFennecType) -> bool {
+    matches!(view(fen), Some(CFennecView::Array(_)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_GetArray(
+    fen: *const CFennecType,
+) -> *const CFennecArray {
+    match view(fen) {
+        Some(CFennecView::Array(arr)) => arr,
+        _ => ptr::null(),
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_Array_Len(arr: *const CFennecArray) -> usize {
+    arr.as_ref().map_or(0, |arr| arr.len)
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_Array_GetIdx(
+    arr: *const CFennecArray,
+    idx: usize,
+) -> *const CFennecType {
+    found(arr.as_ref().and_then(|arr| arr.get(idx)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_IsString(fen: *const CFennecType) -> bool {
+    matches!(view(fen), Some(CFennecView::String(_)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_GetString(fen: *const CFennecType) -> *const c_char {
+    match view(fen) {
+        Some(CFennecView::String(str)) => str,
+        _ => ptr::null(),
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_IsFloat(fen: *const CFennecType) -> bool {
+    matches!(view(fen), Some(CFennecView::Float(_)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_GetFloat(fen: *const CFennecType) -> f64 {
+    match view(fen) {
+        Some(CFennecView::Float(f)) => f,
+        _ => 0.0,
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_IsInt(fen: *const CFennecType) -> bool {
+    matches!(view(fen), Some(CFennecView::Int(_)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_GetInt(fen: *const CFennecType) -> i64 {
+    match view(fen) {
+        Some(CFennecView::Int(i)) => i,
+        _ => 0,
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_IsNumber(fen: *const CFennecType) -> bool {
+    matches!(
+        view(fen),
+        Some(CFennecView::Int(_) | CFennecView::BigInt(_) | CFennecView::Float(_))
+    )
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_GetNumber(fen: *const CFennecType) -> f64 {
+    match view(fen) {
+        Some(CFennecView::Int(i)) => i as f64,
+        Some(CFennecView::BigInt(i)) => CStr::from_ptr(i)
+            .to_str()
+            .ok()
+            .and_then(|i| i.parse().ok())
+            .unwrap_or(0.0),
+        Some(CFennecView::Float(f)) => f,
+        _ => 0.0,
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_IsBool(fen: *const CFennecType) -> bool {
+    matches!(view(fen), Some(CFennecView::Bool(_)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_GetBool(fen: *const CFennecType) -> bool {
+    matches!(view(fen), Some(CFennecView::Bool(true)))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_IsNull(fen: *const CFennecType) -> bool {
+    matches!(view(fen), Some(CFennecView::Null))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_FennecValue_IsError(fen: *const CFennecType) -> bool {
+    matches!(view(fen), Some(CFennecView::Error))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        ffi::{c_char, CStr, CString},
+        ptr,
+    };
+
+    use crate::extern_c::types::{CFennecType, CFennecView};
+
+    use super::{
+        FennecConfig_ArrayGet, FennecConfig_Get, FennecConfig_GetFloat, FennecConfig_GetInt,
+        FennecConfig_GetString, FennecConfig_IterNext, FennecConfig_Iterate, FennecConfig_Len,
+        FennecConfig_ObjectGet,
+    };
+
+    fn value(str: &str) -> CFennecType {
+        crate::parse(str).unwrap().into()
+    }
+
+    #[test]
+    fn looks_up_paths() {
+        let fen = value("server { ports [ 80 443 ] host = \"a\" \"odd key\" = 1.5 }");
+        let path = |str: &str| CString::new(str).unwrap();
+
+        unsafe {
+            let mut i = 0;
+            assert!(FennecConfig_GetInt(
+                &fen,
+                path("server.ports[1]").as_ptr(),
+                &mut i
+            ));
+            assert_eq!(i, 443);
+            assert!(!FennecConfig_GetInt(
+                &fen,
+                path("server.host").as_ptr(),
+                &mut i
+            ));
+            assert!(!FennecConfig_GetInt(
+                &fen,
+                path("server.ports[2]").as_ptr(),
+                &mut i
+            ));
+            assert!(!FennecConfig_GetInt(
+                &fen,
+                path("server..").as_ptr(),
+                &mut i
+            ));
+            assert_eq!(i, 443);
+
+            let mut f = 0.0;
+            assert!(FennecConfig_GetFloat(
+                &fen,
+                path("server.\"odd key\"").as_ptr(),
+                &mut f
+            ));
+            assert_eq!(f, 1.5);
+            assert!(FennecConfig_GetFloat(
+                &fen,
+                path("server.ports[0]").as_ptr(),
+                &mut f
+            ));
+            assert_eq!(f, 80.0);
+
+            let server = FennecConfig_ObjectGet(&fen, path("server").as_ptr());
+            let mut str: *const c_char = ptr::null();
+            assert!(FennecConfig_GetString(
+                server,
+                path("host").as_ptr(),
+                &mut str
+            ));
+            assert_eq!(CStr::from_ptr(str).to_str(), Ok("a"));
+
+            let ports = FennecConfig_Get(&fen, path("server.ports").as_ptr());
+            assert_eq!(FennecConfig_Len(ports), 2);
+            assert!(matches!(
+                (*FennecConfig_ArrayGet(ports, 0)).view(),
+                CFennecView::Int(80)
+            ));
+            assert!(FennecConfig_ArrayGet(ports, 2).is_null());
+            assert!(FennecConfig_Get(&fen, path("missing").as_ptr()).is_null());
+        }
+    }
+
+    #[test]
+    fn iterates() {
+        let fen = value("a = 1 b [ 2 3 ]");
+
+        unsafe {
+            let mut iter = FennecConfig_Iterate(&fen);
+            let mut key = ptr::null();
+            let mut val = ptr::null();
+            let mut keys = Vec::new();
+            while FennecConfig_IterNext(&mut iter, &mut key, &mut val) {
+                keys.push(CStr::from_ptr(key).to_str().unwrap());
+                if let CFennecView::Array(_) = (*val).view() {
+                    let mut iter = FennecConfig_Iterate(val);
+                    let mut sum = 0;
+                    while FennecConfig_IterNext(&mut iter, &mut key, &mut val) {
+                        assert!(key.is_null());
+                        let CFennecView::Int(i) = (*val).view() else {
+                            panic!("expected an int");
+                        };
+                        sum += i;
+                    }
+                    assert_eq!(sum, 5);
+                }
+            }
+            keys.sort();
+            assert_eq!(keys, vec!["a", "b"]);
+        }
+    }
+
+    #[test]
+    fn rejects_nuls() {
+        let fen = value("ok = \"a\" nul = \"a\\0b\" keys { \"a\\0x\" = 1 \"a\\0y\" = 2 }");
+        let path = |str: &str| CString::new(str).unwrap();
+
+        unsafe {
+            let mut str: *const c_char = ptr::null();
+            assert!(FennecConfig_GetString(&fen, path("ok").as_ptr(), &mut str));
+            assert!(matches!(
+                (*FennecConfig_Get(&fen, path("nul").as_ptr())).view(),
+                CFennecView::Error
+            ));
+            assert!(matches!(
+                (*FennecConfig_Get(&fen, path("keys").as_ptr())).view(),
+                CFennecView::Error
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_a_nul_key_after_a_good_one() {
+        // The good key is converted first whenever the map happens to order it first,
+        // and has to be freed again when the second one fails
+        let fen = value("a = 1 \"b\\0c\" = 2");
+        assert!(matches!(fen.view(), CFennecView::Error));
+    }
+}
@@ -1,32 +1,251 @@
 use std::{
     collections::HashMap,
-    ffi::{c_char, CString},
+    ffi::{c_char, CStr, CString},
+    time::Duration,
 };
 
-use crate::parse::{FennecType, ParseError};
+use num_bigint::BigInt;
 
+use crate::{
+    parse::{FennecType, ParseError},
+    path::{Path, PathSegment},
+    token::{Location, Position},
+};
+
+/**
+ * The capacities are only kept to free the arrays without leaking
+ */
 #[repr(C)]
-#[derive(Debug)]
-pub enum CFennecType {
-    Object(usize, usize, *const *const c_char, usize, *const CFennecType),
-    Array(usize, usize, *const CFennecType),
+#[derive(Debug, Clone, Copy)]
+pub struct CFennecObject {
+    pub len: usize,
+    pub keys_capacity: usize,
+    pub keys: *const *const c_char,
+    pub values_capacity: usize,
+    pub values: *const CFennecType,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CFennecArray {
+    pub len: usize,
+    pub capacity: usize,
+    pub arr: *const CFennecType,
+}
+
+impl CFennecObject {
+    pub fn entry(&self, index: usize) -> Option<(&CStr, &CFennecType)> {
+        if index >= self.len {
+            return None;
+        }
+        unsafe {
+            Some((
+                CStr::from_ptr(*self.keys.add(index)),
+                &*self.values.add(index),
+            ))
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&CFennecType> {
+        (0..self.len)
+            .filter_map(|i| self.entry(i))
+            .find(|(k, _)| k.to_bytes() == key.as_bytes())
+            .map(|(_, value)| value)
+    }
+}
+
+impl CFennecArray {
+    pub fn get(&self, index: usize) -> Option<&CFennecType> {
+        if index >= self.len {
+            return None;
+        }
+        unsafe { Some(&*self.arr.add(index)) }
+    }
+}
+
+/**
+ * Which member of the value's union is set
+ */
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CFennecTag {
+    Object,
+    Array,
+    String,
+    Float,
+    Int,
+    Bool,
+    Null,
+    /**
+     * A document that didn't parse, or a string or object with a NUL in a key or value,
+     * since C would only read up to it
+     */
+    Error,
+    /**
+     * The Parse* functions resolve every reference, so these won't show up there
+     */
+    Reference,
+    BigInt,
+    DateTime,
+    Date,
+    Time,
+    Duration,
+    Quantity,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CFennecDuration {
+    pub secs: u64,
+    pub nanos: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union CFennecUnion {
+    pub object: CFennecObject,
+    pub array: CFennecArray,
+    pub string: *const c_char,
+    pub f: f64,
+    pub i: i64,
+    pub b: bool,
+    /**
+     * The path of an unresolved reference, e.g. `server.ports[0]`
+     */
+    pub reference: *const c_char,
+    /**
+     * An int too big for an `int64_t`, written out in decimal
+     */
+    pub big_int: *const c_char,
+    /**
+     * RFC 3339 text, e.g. `1979-05-27T07:32:00Z`, or without the offset for a local date-time
+     */
+    pub date_time: *const c_char,
+    /**
+     * `1979-05-27`
+     */
+    pub date: *const c_char,
+    /**
+     * `07:32:00`
+     */
+    pub time: *const c_char,
+    pub duration: CFennecDuration,
+    /**
+     * A number with its unit, as it was written, e.g. `512MiB`
+     */
+    pub quantity: *const c_char,
+}
+
+/**
+ * Read it with the lookup and iteration functions rather than walking the unions by hand
+ */
+/** cbindgen:field-names=[type, value] */
+#[repr(C)]
+pub struct CFennecType {
+    pub tag: CFennecTag,
+    pub value: CFennecUnion,
+}
+
+/**
+ * What a `CFennecType` holds, so Rust can match on it instead of reading the union
+ */
+#[derive(Debug, Clone, Copy)]
+pub enum CFennecView<'a> {
+    Object(&'a CFennecObject),
+    Array(&'a CFennecArray),
     String(*const c_char),
     Float(f64),
     Int(i64),
     Bool(bool),
     Null,
     Error,
+    Reference(*const c_char),
+    BigInt(*const c_char),
+    DateTime(*const c_char),
+    Date(*const c_char),
+    Time(*const c_char),
+    Duration(u64, u32),
+    Quantity(*const c_char),
+}
+
+impl std::fmt::Debug for CFennecType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.view().fmt(f)
+    }
 }
 
 impl CFennecType {
-    fn transform_str(str: &String) -> *const c_char {
-        CString::new(str.as_bytes()).unwrap().into_raw()
+    /**
+     * `None` for text with a NUL in it, which C would read as a shorter string.
+     * Two keys like `"a\0x"` and `"a\0y"` would both come out as `"a"`.
+     */
+    fn transform_str(str: &str) -> Option<*const c_char> {
+        CString::new(str)
+            .ok()
+            .map(|str| str.into_raw() as *const c_char)
+    }
+
+    /**
+     * Every text member of the union is a `const char *`, so they can all be set through `string`
+     */
+    fn text(tag: CFennecTag, str: &str) -> Self {
+        match Self::transform_str(str) {
+            Some(string) => Self::new(tag, CFennecUnion { string }),
+            None => Self::error(),
+        }
+    }
+
+    fn new(tag: CFennecTag, value: CFennecUnion) -> Self {
+        Self { tag, value }
+    }
+
+    pub fn null() -> Self {
+        Self::new(CFennecTag::Null, CFennecUnion { i: 0 })
+    }
+
+    pub fn error() -> Self {
+        Self::new(CFennecTag::Error, CFennecUnion { i: 0 })
+    }
+
+    pub fn view(&self) -> CFennecView<'_> {
+        unsafe {
+            match self.tag {
+                CFennecTag::Object => CFennecView::Object(&self.value.object),
+                CFennecTag::Array => CFennecView::Array(&self.value.array),
+                CFennecTag::String => CFennecView::String(self.value.string),
+                CFennecTag::Float => CFennecView::Float(self.value.f),
+                CFennecTag::Int => CFennecView::Int(self.value.i),
+                CFennecTag::Bool => CFennecView::Bool(self.value.b),
+                CFennecTag::Null => CFennecView::Null,
+                CFennecTag::Error => CFennecView::Error,
+                CFennecTag::Reference => CFennecView::Reference(self.value.reference),
+                CFennecTag::BigInt => CFennecView::BigInt(self.value.big_int),
+                CFennecTag::DateTime => CFennecView::DateTime(self.value.date_time),
+                CFennecTag::Date => CFennecView::Date(self.value.date),
+                CFennecTag::Time => CFennecView::Time(self.value.time),
+                CFennecTag::Duration => {
+                    CFennecView::Duration(self.value.duration.secs, self.value.duration.nanos)
+                }
+                CFennecTag::Quantity => CFennecView::Quantity(self.value.quantity),
+            }
+        }
     }
 
+    /**
+     * An Error value if any of the keys has a NUL in it, since C couldn't tell it apart from others
+     */
     fn from_object(obj: &HashMap<String, FennecType>) -> Self {
-        let (keys, _, c1) = obj
+        // Every key converts before any is handed over, so nothing leaks when a later one fails
+        let Ok(keys) = obj
             .keys()
-            .map(Self::transform_str)
+            .map(|key| CString::new(key.as_str()))
+            .collect::<Result<Vec<_>, _>>()
+        else {
+            return Self::error();
+        };
+        let (keys, _, c1) = keys
+            .into_iter()
+            .map(|key| key.into_raw() as *const c_char)
             .collect::<Vec<_>>()
             .into_raw_parts();
 
@@ -36,7 +255,14 @@ impl CFennecType {
             .collect::<Vec<_>>()
             .into_raw_parts();
 
-        Self::Object(len, c1, keys, c2, values)
+        let object = CFennecObject {
+            len,
+            keys_capacity: c1,
+            keys,
+            values_capacity: c2,
+            values,
+        };
+        Self::new(CFennecTag::Object, CFennecUnion { object })
     }
 
     fn from_array(arr: &Vec<FennecType>) -> Self {
@@ -46,13 +272,56 @@ impl CFennecType {
             .collect::<Vec<_>>()
             .into_raw_parts();
 
-        Self::Array(len, c, ptr)
+        let array = CFennecArray {
+            len,
+            capacity: c,
+            arr: ptr,
+        };
+        Self::new(CFennecTag::Array, CFennecUnion { array })
     }
 
-    fn from_string(str: &String) -> Self {
-        let c_str = Self::transform_str(str);
+    pub fn len(&self) -> usize {
+        match self.view() {
+            CFennecView::Object(obj) => obj.len,
+            CFennecView::Array(arr) => arr.len,
+            _ => 0,
+        }
+    }
+
+    /**
+     * The key (for objects) and value at `index`
+     */
+    pub fn entry(&self, index: usize) -> Option<(Option<&CStr>, &CFennecType)> {
+        match self.view() {
+            CFennecView::Object(obj) => obj.entry(index).map(|(key, value)| (Some(key), value)),
+            CFennecView::Array(arr) => arr.get(index).map(|value| (None, value)),
+            _ => None,
+        }
+    }
 
-        Self::String(c_str)
+    pub fn get_key(&self, key: &str) -> Option<&CFennecType> {
+        match self.view() {
+            CFennecView::Object(obj) => obj.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<&CFennecType> {
+        match self.view() {
+            CFennecView::Array(arr) => arr.get(index),
+            _ => None,
+        }
+    }
+
+    pub fn get_path(&self, path: &Path) -> Option<&CFennecType> {
+        let mut current = self;
+        for segment in &path.0 {
+            current = match segment {
+                PathSegment::Key(key) => current.get_key(key)?,
+                PathSegment::Index(index) => current.get_index(*index)?,
+            };
+        }
+        Some(current)
     }
 }
 
@@ -60,10 +329,7 @@ impl From<&Result<FennecType, ParseError>> for CFennecType {
     fn from(value: &Result<FennecType, ParseError>) -> Self {
         match value {
             Ok(fen) => fen.into(),
-            Err(e) => {
-                println!("{e:?}");
-                Self::Error
-            }
+            Err(_) => Self::error(),
         }
     }
 }
@@ -79,11 +345,30 @@ impl From<&FennecType> for CFennecType {
         match value {
             FennecType::Object(obj) => Self::from_object(obj),
             FennecType::Array(arr) => Self::from_array(arr),
-            FennecType::String(str) => Self::from_string(str),
-            FennecType::Float(f) => Self::Float(*f),
-            FennecType::Int(i) => Self::Int(*i),
-            FennecType::Bool(b) => Self::Bool(*b),
-            FennecType::Null => Self::Null,
+            FennecType::String(str) => Self::text(CFennecTag::String, str),
+            FennecType::Float(f) => Self::new(CFennecTag::Float, CFennecUnion { f: *f }),
+            FennecType::Int(i) => Self::new(CFennecTag::Int, CFennecUnion { i: *i }),
+            FennecType::BigInt(i) => Self::text(CFennecTag::BigInt, &i.to_string()),
+            FennecType::DateTime(date_time) => {
+                Self::text(CFennecTag::DateTime, &date_time.to_string())
+            }
+            FennecType::Date(date) => Self::text(CFennecTag::Date, &date.to_string()),
+            FennecType::Time(time) => Self::text(CFennecTag::Time, &time.to_string()),
+            FennecType::Duration(duration) => {
+                let duration = CFennecDuration {
+                    secs: duration.as_secs(),
+                    nanos: duration.subsec_nanos(),
+                };
+                Self::new(CFennecTag::Duration, CFennecUnion { duration })
+            }
+            FennecType::Quantity(quantity) => {
+                Self::text(CFennecTag::Quantity, &quantity.to_string())
+            }
+            FennecType::Bool(b) => Self::new(CFennecTag::Bool, CFennecUnion { b: *b }),
+            FennecType::Null => Self::null(),
+            FennecType::Reference(path, _) => {
+                Self::text(CFennecTag::Reference, &path.to_string())
+            }
         }
     }
 }
@@ -94,22 +379,116 @@ impl From<FennecType> for CFennecType {
     }
 }
 
+/**
+ * Why a parse failed. Only handed to C as an opaque pointer.
+ */
+#[derive(Debug)]
+pub struct CFennecError {
+    pub message: CString,
+    pub location: Location,
+}
+
+impl CFennecError {
+    pub fn new(message: &str, location: Location) -> Self {
+        Self {
+            message: CString::new(message.replace('\0', "\\0")).unwrap(),
+            location,
+        }
+    }
+
+    pub fn from_parse_error(e: &ParseError, str: &str) -> Self {
+        let location = match e.position() {
+            Some(pos) => Location::of(str, pos.0),
+            None => Location::of(str, usize::MAX),
+        };
+        Self::new(&e.to_string(), location)
+    }
+}
+
+impl From<&CFennecType> for FennecType {
+    fn from(value: &CFennecType) -> Self {
+        let string =
+            |str: *const c_char| unsafe { CStr::from_ptr(str).to_string_lossy().into_owned() };
+        match value.view() {
+            CFennecView::Object(_) => Self::Object(
+                (0..value.len())
+                    .filter_map(|i| value.entry(i))
+                    .map(|(key, val)| {
+                        let key = key.expect("Object entries have keys!");
+                        (key.to_string_lossy().into_owned(), val.into())
+                    })
+                    .collect(),
+            ),
+            CFennecView::Array(_) => Self::Array(
+                (0..value.len())
+                    .filter_map(|i| value.entry(i))
+                    .map(|(_, val)| val.into())
+                    .collect(),
+            ),
+            CFennecView::String(str) => Self::String(string(str)),
+            CFennecView::Float(f) => Self::Float(f),
+            CFennecView::Int(i) => Self::Int(i),
+            CFennecView::BigInt(i) => match string(i).parse::<BigInt>() {
+                Ok(i) => i.into(),
+                Err(_) => Self::Null,
+            },
+            CFennecView::DateTime(str) => match string(str).parse() {
+                Ok(date_time) => Self::DateTime(date_time),
+                Err(_) => Self::Null,
+            },
+            CFennecView::Date(str) => match string(str).parse() {
+                Ok(date) => Self::Date(date),
+                Err(_) => Self::Null,
+            },
+            CFennecView::Time(str) => match string(str).parse() {
+                Ok(time) => Self::Time(time),
+                Err(_) => Self::Null,
+            },
+            // Saturates rather than overflowing when `nanos` carries into `secs`
+            CFennecView::Duration(secs, nanos) => Self::Duration(
+                Duration::from_secs(secs).saturating_add(Duration::from_nanos(nanos as u64)),
+            ),
+            CFennecView::Quantity(str) => match string(str).parse() {
+                Ok(quantity) => Self::Quantity(quantity),
+                Err(_) => Self::Null,
+            },
+            CFennecView::Bool(b) => Self::Bool(b),
+            CFennecView::Null | CFennecView::Error => Self::Null,
+            CFennecView::Reference(path) => match string(path).parse() {
+                Ok(path) => Self::Reference(path, Position(0, 0)),
+                Err(_) => Self::Null,
+            },
+        }
+    }
+}
+
 impl Drop for CFennecType {
     fn drop(&mut self) {
         unsafe {
-            match self {
-                Self::Object(len, c1, keys, c2, values) => {
-                    let keys = Vec::from_raw_parts(*keys as *mut *const i8, *len, *c1);
+            match self.view() {
+                CFennecView::Object(obj) => {
+                    let keys =
+                        Vec::from_raw_parts(obj.keys as *mut *const i8, obj.len, obj.keys_capacity);
                     for key in keys {
                         let _ = CString::from_raw(key as *mut i8);
                     }
-                    let _ = Vec::from_raw_parts(*values as *mut CFennecType, *len, *c2);
+                    let _ = Vec::from_raw_parts(
+                        obj.values as *mut CFennecType,
+                        obj.len,
+                        obj.values_capacity,
+                    );
                 }
-                Self::Array(len, c, values) => {
-                    let _ = Vec::from_raw_parts(*values as *mut CFennecType, *len, *c);
+                CFennecView::Array(arr) => {
+                    let _ = Vec::from_raw_parts(arr.arr as *mut CFennecType, arr.len, arr.capacity);
                 }
-                Self::String(str) => {
-                    let _ = CString::from_raw(*str as *mut i8);
+                CFennecView::String(str)
+                | CFennecView::Reference(str)
+                | CFennecView::BigInt(str)
+                | CFennecView::DateTime(str)
+                | CFennecView::Date(str)
+                | CFennecView::Time(str)
+                | CFennecView::Quantity(str) => {
+                    let _ = CString::from_raw(str as *mut i8);
                 }
                 _ => {}
             }
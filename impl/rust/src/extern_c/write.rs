@@ -0,0 +1,293 @@
+use std::{
+    collections::HashMap,
+    ffi::{c_char, CStr, CString},
+    ptr,
+    time::Duration,
+};
+
+use crate::{
+    datetime::{Date, DateTime, Time},
+    parse::{FennecType, StringifyOptions},
+    quantity::Quantity,
+};
+
+use super::types::{CFennecTag, CFennecType};
+
+/**
+ * Mirrors `StringifyOptions`
+ */
+#[repr(C)]
+pub struct CStringifyOptions {
+    /**
+     * Spaces per level of nesting, 4 by default
+     */
+    indent: usize,
+    /**
+     * Write keys in sorted order instead of the order they're stored in
+     */
+    sort_keys: bool,
+    /**
+     * Collapse objects that only hold one key into dotted keys like `a.b.c = 1`
+     */
+    dotted_keys: bool,
+}
+
+/**
+ * Values built from C are plain `FennecType`s behind an opaque pointer
+ */
+fn new(value: FennecType) -> *mut FennecType {
+    opaque_pointer::raw(value)
+}
+
+unsafe fn str<'a>(str: *const c_char) -> Option<&'a str> {
+    if str.is_null() {
+        return None;
+    }
+    CStr::from_ptr(str).to_str().ok()
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_NewObject() -> *mut FennecType {
+    new(FennecType::Object(HashMap::new()))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_NewArray() -> *mut FennecType {
+    new(FennecType::Array(Vec::new()))
+}
+
+/**
+ * Copies `value`, null if it isn't valid UTF-8
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_NewString(value: *const c_char) -> *mut FennecType {
+    match str(value) {
+        Some(value) => new(FennecType::String(value.to_string())),
+        None => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_NewFloat(value: f64) -> *mut FennecType {
+    new(FennecType::Float(value))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_NewInt(value: i64) -> *mut FennecType {
+    new(FennecType::Int(value))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_NewBool(value: bool) -> *mut FennecType {
+    new(FennecType::Bool(value))
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_NewNull() -> *mut FennecType {
+    new(FennecType::Null)
+}
+
+/**
+ * Takes a date-time, date or time in its literal form, e.g. `1979-05-27T07:32:00Z`.
+ * Null if it's none of them.
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_NewDateTime(value: *const c_char) -> *mut FennecType {
+    let Some(value) = str(value) else {
+        return ptr::null_mut();
+    };
+    if let Ok(date_time) = value.parse::<DateTime>() {
+        return new(FennecType::DateTime(date_time));
+    }
+    if let Ok(date) = value.parse::<Date>() {
+        return new(FennecType::Date(date));
+    }
+    match value.parse::<Time>() {
+        Ok(time) => new(FennecType::Time(time)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_NewDuration(secs: u64, nanos: u32) -> *mut FennecType {
+    let duration = Duration::from_secs(secs).saturating_add(Duration::from_nanos(nanos.into()));
+    new(FennecType::Duration(duration))
+}
+
+/**
+ * Takes a number with a unit, e.g. `512MiB`. Null if the unit isn't a known one.
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_NewQuantity(value: *const c_char) -> *mut FennecType {
+    match str(value).map(str::parse::<Quantity>) {
+        Some(Ok(quantity)) => new(FennecType::Quantity(quantity)),
+        _ => ptr::null_mut(),
+    }
+}
+
+/**
+ * Copies a parsed value so it can be changed and written back out, null for Error values
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_Edit(fen: *const CFennecType) -> *mut FennecType {
+    if fen.is_null() || (*fen).tag == CFennecTag::Error {
+        return ptr::null_mut();
+    }
+    new((&*fen).into())
+}
+
+/**
+ * Replaces the key if it's already there.
+ * Takes ownership of `value`, even when it fails because `obj` isn't an object.
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_ObjectSet(
+    obj: *mut FennecType,
+    key: *const c_char,
+    value: *mut FennecType,
+) -> bool {
+    let Ok(value) = opaque_pointer::own_back(value) else {
+        return false;
+    };
+    let (Ok(FennecType::Object(obj)), Some(key)) = (opaque_pointer::mut_object(obj), str(key))
+    else {
+        return false;
+    };
+    obj.insert(key.to_string(), value);
+    true
+}
+
+/**
+ * Takes ownership of `value`, even when it fails because `arr` isn't an array
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_ArrayPush(arr: *mut FennecType, value: *mut FennecType) -> bool {
+    let Ok(value) = opaque_pointer::own_back(value) else {
+        return false;
+    };
+    let Ok(FennecType::Array(arr)) = opaque_pointer::mut_object(arr) else {
+        return false;
+    };
+    arr.push(value);
+    true
+}
+
+/**
+ * `options` may be null for the defaults. Free the result with `FennecConfig_String_Free`.
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_Stringify(
+    value: *const FennecType,
+    options: *const CStringifyOptions,
+) -> *mut c_char {
+    let Ok(value) = opaque_pointer::object(value) else {
+        return ptr::null_mut();
+    };
+    let options = match options.as_ref() {
+        Some(options) => StringifyOptions {
+            indent: options.indent,
+            sort_keys: options.sort_keys,
+            dotted_keys: options.dotted_keys,
+        },
+        None => StringifyOptions::default(),
+    };
+    match CString::new(value.stringify(&options)) {
+        Ok(str) => str.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_String_Free(str: *mut c_char) {
+    if str.is_null() {
+        return;
+    }
+    let _ = CString::from_raw(str);
+}
+
+/**
+ * Only for the values that weren't handed to `FennecConfig_ObjectSet` or `FennecConfig_ArrayPush`
+ */
+#[no_mangle]
+unsafe extern "C" fn FennecConfig_Value_Free(value: *mut FennecType) {
+    if value.is_null() {
+        return;
+    }
+    let _ = opaque_pointer::own_back(value);
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        ffi::{CStr, CString},
+        ptr,
+    };
+
+    use crate::{extern_c::types::CFennecType, parse::FennecType};
+
+    use super::{
+        CStringifyOptions, FennecConfig_ArrayPush, FennecConfig_Edit, FennecConfig_NewArray,
+        FennecConfig_NewBool, FennecConfig_NewInt, FennecConfig_NewObject, FennecConfig_NewString,
+        FennecConfig_ObjectSet, FennecConfig_String_Free, FennecConfig_Stringify,
+        FennecConfig_Value_Free,
+    };
+
+    #[test]
+    fn builds_and_stringifies() {
+        unsafe {
+            let obj = FennecConfig_NewObject();
+            let arr = FennecConfig_NewArray();
+            assert!(FennecConfig_ArrayPush(arr, FennecConfig_NewInt(1)));
+            assert!(FennecConfig_ArrayPush(arr, FennecConfig_NewBool(true)));
+
+            let key = |str: &str| CString::new(str).unwrap();
+            let name = key("fennec");
+            assert!(FennecConfig_ObjectSet(obj, key("list").as_ptr(), arr));
+            assert!(FennecConfig_ObjectSet(
+                obj,
+                key("name").as_ptr(),
+                FennecConfig_NewString(name.as_ptr())
+            ));
+            assert!(!FennecConfig_ArrayPush(obj, FennecConfig_NewInt(2)));
+
+            let options = CStringifyOptions {
+                indent: 2,
+                sort_keys: true,
+                dotted_keys: false,
+            };
+            let str = FennecConfig_Stringify(obj, &options);
+            assert_eq!(
+                CStr::from_ptr(str).to_str(),
+                Ok("list [\n  1\n  true\n]\nname = \"fennec\"\n")
+            );
+            assert_eq!(
+                crate::parse(CStr::from_ptr(str).to_str().unwrap()).unwrap(),
+                *obj
+            );
+
+            FennecConfig_String_Free(str);
+            FennecConfig_Value_Free(obj);
+        }
+    }
+
+    #[test]
+    fn edits_parsed_values() {
+        unsafe {
+            let parsed: CFennecType = crate::parse("a = 1 b { c = \"d\" }").unwrap().into();
+            let edit = FennecConfig_Edit(&parsed);
+            let key = CString::new("e").unwrap();
+            assert!(FennecConfig_ObjectSet(
+                edit,
+                key.as_ptr(),
+                FennecConfig_NewInt(2)
+            ));
+
+            assert_eq!(*edit, crate::parse("a = 1 b { c = \"d\" } e = 2").unwrap());
+            assert!(FennecConfig_Edit(&CFennecType::error()).is_null());
+            assert!(FennecConfig_Stringify(ptr::null(), ptr::null()).is_null());
+            assert!(matches!(*edit, FennecType::Object(_)));
+
+            FennecConfig_Value_Free(edit);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::{collections::HashMap, env};
+
+use crate::{parse::FennecType, path::Path};
+
+/**
+ * Where `${VAR}` lookups get their values from
+ */
+pub trait VariableSource {
+    fn get(&self, name: &str) -> Option<String>;
+}
+
+/**
+ * Reads variables from the process environment
+ */
+pub struct Environment;
+
+impl VariableSource for Environment {
+    fn get(&self, name: &str) -> Option<String> {
+        env::var(name).ok()
+    }
+}
+
+impl VariableSource for HashMap<String, String> {
+    fn get(&self, name: &str) -> Option<String> {
+        HashMap::get(self, name).cloned()
+    }
+}
+
+/**
+ * (variable?, path of the string it was found in)
+ */
+#[derive(Debug)]
+pub enum InterpolateError {
+    Undefined(String, Path),
+    Unterminated(Path),
+}
+
+/**
+ * Expands `${VAR}` and `${VAR:-default}` in every string value of the tree.
+ * `$$` is written out as a literal `$`, and a `$` not followed by `{` or `$` is left alone.
+ * Keys are never expanded.
+ */
+pub fn interpolate(
+    value: &mut FennecType,
+    source: &impl VariableSource,
+) -> Result<(), InterpolateError> {
+    interpolate_at(value, source, &Path::new())
+}
+
+fn interpolate_at(
+    value: &mut FennecType,
+    source: &impl VariableSource,
+    path: &Path,
+) -> Result<(), InterpolateError> {
+    match value {
+        FennecType::String(str) => {
+            *str = expand(str, source, path)?;
+        }
+        FennecType::Object(obj) => {
+            for (key, val) in obj.iter_mut() {
+                interpolate_at(val, source, &path.join_key(key))?;
+            }
+        }
+        FennecType::Array(arr) => {
+            for (i, val) in arr.iter_mut().enumerate() {
+                interpolate_at(val, source, &path.join_index(i))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn expand(
+    str: &str,
+    source: &impl VariableSource,
+    path: &Path,
+) -> Result<String, InterpolateError> {
+    let mut out = String::with_capacity(str.len());
+    let mut rest = str;
+
+    while let Some(idx) = rest.find('$') {
+        out.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+
+        if rest.starts_with("$$") {
+            out.push('$');
+            rest = &rest[2..];
+            continue;
+        }
+
+        if !rest.starts_with("${") {
+            out.push('$');
+            rest = &rest[1..];
+            continue;
+        }
+
+        let Some(end) = rest.find('}') else {
+            return Err(InterpolateError::Unterminated(path.clone()));
+        };
+
+        let inner = &rest[2..end];
+        rest = &rest[end + 1..];
+
+        let (name, default) = match inner.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (inner, None),
+        };
+
+        match source.get(name) {
+            Some(val) => out.push_str(&val),
+            None => match default {
+                Some(default) => out.push_str(default),
+                None => return Err(InterpolateError::Undefined(name.to_string(), path.clone())),
+            },
+        }
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::parse;
+
+    use super::{interpolate, InterpolateError};
+
+    fn vars() -> HashMap<String, String> {
+        HashMap::from([
+            ("HOST".to_string(), "example.com".to_string()),
+            ("PORT".to_string(), "8080".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_expands_variables() {
+        let mut fen = parse("url = \"http://${HOST}:${PORT}/\" list [ \"${HOST}\" ]").unwrap();
+        interpolate(&mut fen, &vars()).unwrap();
+
+        assert_eq!(
+            fen.get_key("url").unwrap().as_string().unwrap(),
+            "http://example.com:8080/"
+        );
+        let list = fen.get_key("list").unwrap();
+        assert_eq!(
+            list.get_index(0).unwrap().as_string().unwrap(),
+            "example.com"
+        );
+    }
+
+    #[test]
+    fn test_defaults_and_escapes() {
+        let mut fen = parse("a = \"${USER:-nobody}\" b = \"$$HOME costs $5\"").unwrap();
+        interpolate(&mut fen, &vars()).unwrap();
+
+        assert_eq!(fen.get_key("a").unwrap().as_string().unwrap(), "nobody");
+        assert_eq!(
+            fen.get_key("b").unwrap().as_string().unwrap(),
+            "$HOME costs $5"
+        );
+    }
+
+    #[test]
+    fn test_undefined_reports_path() {
+        let mut fen = parse("server { hosts [ \"${HOST}\" \"${MISSING}\" ] }").unwrap();
+        let Err(InterpolateError::Undefined(name, path)) = interpolate(&mut fen, &vars()) else {
+            panic!("expected an undefined variable");
+        };
+
+        assert_eq!(name, "MISSING");
+        assert_eq!(path.to_string(), "server.hosts[1]");
+    }
+}
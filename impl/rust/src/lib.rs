@@ -1,23 +1,86 @@
 #![feature(decl_macro, let_chains, ascii_char, vec_into_raw_parts)]
+pub mod convert;
+pub mod datetime;
+#[cfg(feature = "serde")]
+pub mod de;
 pub mod extern_c;
+pub mod interpolate;
 pub mod parse;
+pub mod path;
+pub mod quantity;
+pub mod reference;
+pub mod schema;
 pub mod token;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "derive")]
+pub use fennec_derive::FennecConfig;
+pub use num_bigint::BigInt;
 
 use once_cell::sync::Lazy;
-use parse::{FennecType, ParseError, Parser};
+use parse::{Document, FennecType, Limit, ParseError, ParseOptions, Parser};
 
 pub macro lazy($t:expr) {
     Lazy::new(|| $t)
 }
 
+/**
+ * Parses a document and resolves its references.
+ * Use `Parser::parse_root` directly to keep the references in the tree.
+ */
 pub fn parse(str: &str) -> Result<FennecType, ParseError> {
-    Parser::new(token::Tokenizer::new(str)).parse_root()
+    parse_with(str, ParseOptions::default())
+}
+
+/**
+ * Like `parse`, with limits for untrusted input
+ */
+pub fn parse_with(str: &str, options: ParseOptions) -> Result<FennecType, ParseError> {
+    let root = parser(str, &options)?.parse_root()?;
+    reference::resolve_with(&root, &options)
+}
+
+/**
+ * Like `parse`, but also keeps where every key and array element was written
+ */
+pub fn parse_document(str: &str) -> Result<Document, ParseError> {
+    parse_document_with(str, ParseOptions::default())
+}
+
+pub fn parse_document_with(str: &str, options: ParseOptions) -> Result<Document, ParseError> {
+    let mut doc = parser(str, &options)?.parse_document()?;
+    doc.value = reference::resolve_with(&doc.value, &options)?;
+    Ok(doc)
+}
+
+/**
+ * Checks `max_size` before the tokenizer makes its own copy of the input
+ */
+fn parser(str: &str, options: &ParseOptions) -> Result<Parser, ParseError> {
+    if str.len() > options.max_size {
+        return Err(ParseError::LimitExceeded(
+            Limit::Size(options.max_size),
+            None,
+        ));
+    }
+    Ok(Parser::with_options(
+        token::Tokenizer::new(str),
+        options.clone(),
+    ))
 }
 
 #[cfg(test)]
 mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
     use crate::parse;
-    use crate::parse::ParseError;
+    use crate::parse::{
+        DuplicateKeys, FennecType, Limit, ParseError, ParseOptions, StringifyOptions,
+    };
+    use crate::path::Path;
+    use crate::token::Position;
+    use crate::{parse_document, parse_document_with, parse_with, BigInt};
 
     #[test]
     fn test_spec_file() -> Result<(), ParseError> {
@@ -37,4 +100,417 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_hostile_input() {
+        let errors = [
+            ("a = \"b\\", "unterminated string"),
+            ("a = \"\"\"b", "unterminated string"),
+        ];
+        for (input, error) in errors {
+            assert_eq!(parse(input).unwrap_err().to_string(), error, "{input}");
+        }
+
+        let huge = format!("a = {}.0", "9".repeat(400));
+        assert_eq!(parse(&huge).unwrap_err().to_string(), "float out of range");
+    }
+
+    #[test]
+    fn test_big_ints() -> Result<(), ParseError> {
+        const INPUT: &str = "
+            mask = 0xFFFFFFFFFFFFFFFF
+            id = 170141183460469231731687303715884105727
+            negative = 1x8000000000000001
+            huge = 340282366920938463463374607431768211456
+            small = 0x7FFFFFFFFFFFFFFF
+        ";
+        let value = parse(INPUT)?;
+        let get = |key| value.get_key(key).unwrap();
+
+        assert_eq!(get("mask").as_u64(), Some(u64::MAX));
+        assert_eq!(get("mask").as_int(), None);
+        assert_eq!(get("id").as_i128(), Some(i128::MAX));
+        assert_eq!(get("negative").as_i128(), Some(-(i64::MAX as i128) - 2));
+        assert_eq!(get("negative").as_u64(), None);
+        assert_eq!(get("huge").as_u128(), None);
+        assert_eq!(get("huge").as_big_int(), Some(BigInt::from(1) << 128));
+        assert_eq!(get("small"), &FennecType::Int(i64::MAX));
+        assert_eq!(get("small").as_u64(), Some(i64::MAX as u64));
+
+        assert_eq!(parse(&value.to_string())?, value);
+        assert!(value.to_string().contains("mask = 18446744073709551615"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_number_syntax() -> Result<(), ParseError> {
+        let numbers = [
+            ("1e6", FennecType::Float(1e6)),
+            ("6.02E+23", FennecType::Float(6.02e23)),
+            ("-2.5e-3", FennecType::Float(-2.5e-3)),
+            (".5", FennecType::Float(0.5)),
+            ("+1.5", FennecType::Float(1.5)),
+            ("+inf", FennecType::Float(f64::INFINITY)),
+            ("-inf", FennecType::Float(f64::NEG_INFINITY)),
+            ("1_000.000_1", FennecType::Float(1000.0001)),
+            ("+42", FennecType::Int(42)),
+            ("1_000_000", FennecType::Int(1_000_000)),
+            ("0xFF_FF", FennecType::Int(0xFFFF)),
+            ("0o7_7", FennecType::Int(0o77)),
+            ("0b1010_1010", FennecType::Int(0b1010_1010)),
+            ("1x1_0", FennecType::Int(-0x10)),
+        ];
+        for (str, expected) in numbers {
+            let value = parse(&format!("a = {str}"))?;
+            assert_eq!(value.get_key("a"), Some(&expected), "{str}");
+            assert_eq!(parse(&value.to_string())?, value, "{str}");
+        }
+
+        let nan = parse("ratio = nan")?;
+        assert!(nan.get_key("ratio").and_then(FennecType::as_float).unwrap().is_nan());
+        assert_eq!(nan.to_string(), "ratio = nan\n");
+        assert_eq!(parse("ratio = 1e300")?.to_string(), "ratio = 1e300\n");
+
+        for str in ["1__0", "1_", "_1", "0x_1", "1e", "1.5_"] {
+            assert!(parse(&format!("a = {str}")).is_err(), "{str}");
+        }
+        // Only a whole `inf` or `nan` is a float
+        assert_eq!(parse("info = 1 -nanny")?, parse("\"info\" = 1 \"nanny\" = true")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_escapes() -> Result<(), ParseError> {
+        const INPUT: &str = r#"
+            braced = "\u{1F98A}"
+            pair = "\uD83E\uDD8A"
+            accent = "\u00e9"
+            nul = "a\0b"
+        "#;
+        let value = parse(INPUT)?;
+        let get = |key| value.get_key(key).and_then(FennecType::as_string).unwrap();
+        assert_eq!(get("braced"), "🦊");
+        assert_eq!(get("pair"), "🦊");
+        assert_eq!(get("accent"), "é");
+        assert_eq!(get("nul"), "a\0b");
+
+        let e = parse(r#"a = "b \q c""#).unwrap_err();
+        assert_eq!(e.to_string(), "invalid escape");
+        assert_eq!(e.position(), Some(Position(7, 9)));
+        for str in [r"\u{110000}", r"\uD800", r"\u{}", r"\u12", r"\x41"] {
+            let e = parse(&format!(r#"a = "{str}""#)).unwrap_err();
+            assert_eq!(e.to_string(), "invalid escape", "{str}");
+        }
+
+        // Control and bidi characters are written back out as escapes
+        const TEXT: &str = r#"text = "bell\u{7} esc\u{1B} rtl\u{202E} tab\t""#;
+        let value = parse(TEXT)?;
+        assert_eq!(value.to_string(), format!("{TEXT}\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_raw_strings() -> Result<(), ParseError> {
+        const INPUT: &str = r#"
+            path = 'C:\Users\fennec'
+            regex = '^\d+"?$'
+            'raw\key' = 1
+            block = '''
+                \n stays as is
+            '''
+        "#;
+        let value = parse(INPUT)?;
+        let get = |key| value.get_key(key).and_then(FennecType::as_string).unwrap();
+        assert_eq!(get("path"), r"C:\Users\fennec");
+        assert_eq!(get("regex"), r#"^\d+"?$"#);
+        assert_eq!(get("block"), r"\n stays as is");
+        assert!(value.get_key(r"raw\key").is_some());
+        assert_eq!(parse(&value.to_string())?, value);
+
+        assert_eq!(parse("a = 'never closed").unwrap_err().to_string(), "unterminated string");
+        assert_eq!(parse("a = '''never closed").unwrap_err().to_string(), "unterminated string");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedent() -> Result<(), ParseError> {
+        const INPUT: &str = "
+            yaml = -\"\"\"
+                server:
+                  port: 80
+
+                  hosts:
+                    - a
+            \"\"\"
+            code = -'''
+                    indented(\"first\")
+                back()
+            '''
+        ";
+        let value = parse(INPUT)?;
+        let get = |key| value.get_key(key).and_then(FennecType::as_string).unwrap();
+        assert_eq!(get("yaml"), "server:\n  port: 80\n\n  hosts:\n    - a");
+        assert_eq!(get("code"), "    indented(\"first\")\nback()");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dates_and_durations() -> Result<(), ParseError> {
+        const INPUT: &str = "
+            released = 1979-05-27T07:32:00.25-07:00
+            local = 1979-05-27T07:32:00
+            day = 2024-02-29
+            alarm = 06:30:00
+            timeout = 1h30m
+            backoff [ 100ms 2s ]
+        ";
+        let value = parse(INPUT)?;
+        let released = value.get_key("released").and_then(FennecType::as_date_time).unwrap();
+        assert_eq!(released.offset, Some(-420));
+        assert_eq!(released.time.nanosecond, 250_000_000);
+        assert_eq!(
+            value.get_key("released").and_then(FennecType::as_system_time),
+            Some(UNIX_EPOCH + Duration::new(296_663_520, 250_000_000))
+        );
+        assert_eq!(value.get_key("local").and_then(FennecType::as_system_time), None);
+        assert_eq!(value.get_key("day").unwrap().type_name(), "date");
+        assert_eq!(value.get_key("alarm").unwrap().type_name(), "time");
+        assert_eq!(
+            value.get_key("timeout").and_then(FennecType::as_duration),
+            Some(Duration::from_secs(5400))
+        );
+        assert_eq!(parse(&value.to_string())?, value);
+
+        assert_eq!(parse("a = 2023-02-30").unwrap_err().to_string(), "invalid date or time");
+        assert_eq!(parse("a = 24:00:00").unwrap_err().to_string(), "invalid date or time");
+        assert_eq!(
+            parse("a = 30s1m").unwrap_err().to_string(),
+            "duration units have to go from biggest to smallest"
+        );
+        assert!(parse("a = 10sec").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_quantities() -> Result<(), ParseError> {
+        const INPUT: &str = "
+            cache = 512MiB
+            disk = 1.50TB
+            link = 10Gbps
+            limits [ 1_000kB 20MB/s ]
+        ";
+        let value = parse(INPUT)?;
+        assert_eq!(value.get_key("cache").and_then(FennecType::as_bytes), Some(512 << 20));
+        assert_eq!(value.get_key("disk").and_then(FennecType::as_bytes), Some(1_500_000_000_000));
+        assert_eq!(value.get_key("link").and_then(FennecType::as_bytes), None);
+        assert_eq!(value.get_key("link").unwrap().type_name(), "quantity");
+        assert_eq!(parse(&value.to_string())?, value);
+        assert!(value.to_string().contains("disk = 1.50TB"));
+
+        assert_eq!(parse("a = 10mb").unwrap_err().to_string(), "unknown unit");
+        assert_eq!(parse("a = -5GB").unwrap_err().to_string(), "quantities can't be negative");
+        // Suffixes that already meant something still do
+        assert_eq!(parse("a = 1e+5")?.get_key("a"), Some(&FennecType::Float(1e5)));
+        assert_eq!(parse("a = 0x1B")?.get_key("a"), Some(&FennecType::Int(27)));
+        assert_eq!(parse("a = 1b")?.get_key("a"), Some(&FennecType::Bool(true)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<(), ParseError> {
+        const INPUT: &str = r#"
+            "null" = 1.0
+            "quote\"and\\backslash" = "tab\there \"quoted\""
+            empty {}
+            list [ {} [] -2.0 ]
+        "#;
+        let value = parse(INPUT)?;
+        assert_eq!(parse(&value.to_string())?, value);
+        assert_eq!(parse(&parse("{}")?.to_string())?, parse("{}")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_limits() {
+        let deep = "a ".to_string() + &"[".repeat(100_000);
+        let Err(ParseError::LimitExceeded(Limit::Depth(128), Some(pos))) = parse(&deep) else {
+            panic!("deep nesting should hit the depth limit");
+        };
+        assert_eq!(pos.0, 2 + 128);
+        let nested = format!("a {}{}", "[".repeat(128), "]".repeat(128));
+        assert!(parse(&nested).is_ok());
+
+        let options = ParseOptions {
+            max_depth: 2,
+            max_size: 64,
+            max_string_length: 3,
+            max_elements: 2,
+            ..ParseOptions::default()
+        };
+        let errors = [
+            ("a { b { c [] } }", "nested more than 2 deep"),
+            (
+                "a = 1 b = 2 # a comment that is long enough to go over the size limit",
+                "document is larger than 64 bytes",
+            ),
+            ("a = \"four\"", "string is longer than 3 chars"),
+            ("\"long\" = 1", "string is longer than 3 chars"),
+            ("\"long\"", "string is longer than 3 chars"),
+            ("a = 1 b = 2 c = 3", "more than 2 elements"),
+            ("a [1 2 3]", "more than 2 elements"),
+            ("a.b.c.d = 1", "nested more than 2 deep"),
+            ("a.b = 1 a.c = 2 a.d = 3", "more than 2 elements"),
+        ];
+        for (input, error) in errors {
+            let e = parse_with(input, options.clone()).unwrap_err();
+            assert_eq!(e.to_string(), error, "{input}");
+        }
+        assert!(parse_with("a { b [1 2] } c = \"abc\"", options).is_ok());
+    }
+
+    #[test]
+    fn test_reference_limits() {
+        // Small enough for every other limit, but it expands to 10^8 values
+        let mut doc = "a0 = 1".to_string();
+        for level in 1..=8 {
+            doc += &format!(" a{level} [{}]", format!(" &a{}", level - 1).repeat(10));
+        }
+        let options = ParseOptions {
+            max_depth: 32,
+            max_size: 1 << 20,
+            max_elements: 100_000,
+            ..ParseOptions::default()
+        };
+        let Err(ParseError::LimitExceeded(Limit::Expanded(_), _)) =
+            parse_with(&doc, options.clone())
+        else {
+            panic!("parse_with should hit the expansion limit");
+        };
+        let Err(ParseError::LimitExceeded(Limit::Expanded(_), _)) =
+            parse_document_with(&doc, options)
+        else {
+            panic!("parse_document_with should hit the expansion limit");
+        };
+
+        let options = ParseOptions {
+            max_expanded: 10,
+            ..ParseOptions::default()
+        };
+        let Err(ParseError::LimitExceeded(Limit::Expanded(10), _)) =
+            parse_with("a [1 2 3] b [&a &a &a &a]", options)
+        else {
+            panic!("max_expanded should come from the options");
+        };
+    }
+
+    #[test]
+    fn test_duplicate_keys() -> Result<(), ParseError> {
+        const INPUT: &str = "a = 1 -b a { c = 2 } b = false a [3]";
+        let with = |duplicate_keys| ParseOptions {
+            duplicate_keys,
+            ..ParseOptions::default()
+        };
+        let a: Path = "a".parse().unwrap();
+
+        let Err(ParseError::DuplicateKey(duplicate)) = parse(INPUT) else {
+            panic!("duplicate keys should be an error by default");
+        };
+        assert_eq!(duplicate.path, a);
+        assert_eq!(duplicate.first, Position(0, 5));
+        assert_eq!(duplicate.second, Position(9, 20));
+
+        let first = parse_with(INPUT, with(DuplicateKeys::FirstWins))?;
+        assert_eq!(first, parse("a = 1 -b")?);
+        let last = parse_with(INPUT, with(DuplicateKeys::LastWins))?;
+        assert_eq!(last, parse("a [3] b = false")?);
+
+        let doc = parse_document_with(INPUT, with(DuplicateKeys::Warn))?;
+        assert_eq!(doc.value, last);
+        assert_eq!(doc.duplicates.len(), 3);
+        assert_eq!(doc.spans[&a], Position(31, 36));
+        assert!(!doc.spans.contains_key(&"a.c".parse().unwrap()));
+
+        let doc = parse_document_with(INPUT, with(DuplicateKeys::Collect))?;
+        assert_eq!(doc.value, parse("a [1 { c = 2 } [3]] b [true false]")?);
+        assert_eq!(doc.spans[&"a[0]".parse().unwrap()], Position(0, 5));
+        assert_eq!(doc.spans[&"a[1].c".parse().unwrap()], Position(13, 18));
+        assert_eq!(doc.spans[&"a[2]".parse().unwrap()], Position(31, 36));
+        assert_eq!(doc.spans[&a], Position(0, 36));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dotted_keys() -> Result<(), ParseError> {
+        const INPUT: &str = r#"
+            server.http.port = 80
+            server { name = "main" http { host = "localhost" } }
+            server."tls".enabled = true
+            -server.debug
+            list [ { a.b = 1 } ]
+        "#;
+        let expected = r#"
+            server {
+                http { port = 80 host = "localhost" }
+                name = "main"
+                tls { enabled = true }
+                debug = true
+            }
+            list [ { a { b = 1 } } ]
+        "#;
+        assert_eq!(parse(INPUT)?, parse(expected)?);
+
+        let doc = parse_document("a.b = 1 a.c [2]")?;
+        assert_eq!(doc.spans[&"a".parse().unwrap()], Position(0, 1));
+        assert_eq!(doc.spans[&"a.c".parse().unwrap()], Position(8, 15));
+
+        let errors = [
+            (
+                "port = 80 port.tls = true",
+                "can't add keys to port, it's an int",
+            ),
+            ("a [1] a.b.c = 2", "can't add keys to a, it's an array"),
+            ("a.b = 1 a.b = 2", "duplicate key a.b"),
+            ("a.b = 1 a = 2", "duplicate key a"),
+            ("a { b = 1 } a { c = 2 }", "duplicate key a"),
+            ("a. b = 1", "unexpected key b"),
+            ("a.1 = 2", "unexpected int 1"),
+        ];
+        for (input, error) in errors {
+            assert_eq!(parse(input).unwrap_err().to_string(), error, "{input}");
+        }
+        let e = parse("port = 80 port.tls = true").unwrap_err();
+        assert_eq!(e.position(), Some(Position(10, 14)));
+
+        let options = ParseOptions {
+            duplicate_keys: DuplicateKeys::Collect,
+            ..ParseOptions::default()
+        };
+        let value = parse_with("a.b = 1 a { b = 2 } a.b = 3", options)?;
+        assert_eq!(value, parse("a { b [1 2 3] }")?);
+
+        let options = StringifyOptions {
+            sort_keys: true,
+            dotted_keys: true,
+            ..StringifyOptions::default()
+        };
+        let value =
+            parse("app { db { port = 1 } } log { level = 1 file.path = 2 } tmp { dir {} }")?;
+        let str = value.stringify(&options);
+        assert_eq!(
+            str,
+            "app.db.port = 1\nlog {\n    file.path = 2\n    level = 1\n}\ntmp.dir {\n}\n"
+        );
+        assert_eq!(parse(&str)?, value);
+
+        Ok(())
+    }
 }
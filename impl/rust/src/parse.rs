@@ -1,55 +1,262 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    time::{Duration, SystemTime},
+};
 
+use num_bigint::BigInt;
 use once_cell::sync::Lazy;
 use regex::Regex;
 
 use crate::{
+    datetime::{self, Date, DateTime, Time},
     lazy,
-    token::{Token, Tokenizer},
+    path::{Path, PathSegment},
+    quantity::Quantity,
+    reference::ReferenceError,
+    token::{self, Position, Token, Tokenizer},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum FennecType {
     Object(HashMap<String, FennecType>),
     Array(Vec<FennecType>),
     String(String),
     Float(f64),
     Int(i64),
+    /**
+     * An int that doesn't fit in an i64. Ints that do are always `Int`, which `From<BigInt>` takes care of.
+     */
+    BigInt(BigInt),
+    /**
+     * `1979-05-27T07:32:00Z`, or a local date-time without the offset
+     */
+    DateTime(DateTime),
+    Date(Date),
+    Time(Time),
+    /**
+     * `1h30m`
+     */
+    Duration(Duration),
+    /**
+     * `512MiB`, kept in the unit it was written in
+     */
+    Quantity(Quantity),
     Bool(bool),
     Null,
+    /**
+     * `&some.path`, only present until the references are resolved
+     */
+    Reference(Path, Position),
+}
+
+impl From<BigInt> for FennecType {
+    fn from(value: BigInt) -> Self {
+        match i64::try_from(&value) {
+            Ok(i) => Self::Int(i),
+            Err(_) => Self::BigInt(value),
+        }
+    }
+}
+
+impl From<Duration> for FennecType {
+    fn from(value: Duration) -> Self {
+        Self::Duration(value)
+    }
+}
+
+impl From<DateTime> for FennecType {
+    fn from(value: DateTime) -> Self {
+        Self::DateTime(value)
+    }
+}
+
+impl From<Quantity> for FennecType {
+    fn from(value: Quantity) -> Self {
+        Self::Quantity(value)
+    }
 }
 
 impl ToString for FennecType {
     fn to_string(&self) -> String {
-        self.to_string_internal(0, true)
+        self.stringify(&StringifyOptions::default())
     }
 }
 
 const INDENT: usize = 4;
-const IDENTIFIER: Lazy<Regex> = lazy! { Regex::new(r"^([a-zA-Z$_][a-zA-Z$_\-0-9]+)$").unwrap() };
+
+/**
+ * How `FennecType::stringify` lays out its output
+ */
+#[derive(Debug, Clone)]
+pub struct StringifyOptions {
+    /**
+     * Spaces per level of nesting
+     */
+    pub indent: usize,
+    /**
+     * Write keys in sorted order, rather than whatever order the map keeps them in
+     */
+    pub sort_keys: bool,
+    /**
+     * Write objects that only hold one key as a dotted key,
+     * so `a { b { c = 1 } }` comes out as `a.b.c = 1`
+     */
+    pub dotted_keys: bool,
+}
+
+impl Default for StringifyOptions {
+    fn default() -> Self {
+        Self {
+            indent: INDENT,
+            sort_keys: false,
+            dotted_keys: false,
+        }
+    }
+}
+/**
+ * Limits on what `Parser` accepts, for documents that come from somewhere you don't trust.
+ * `usize::MAX` turns a limit off.
+ */
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /**
+     * How many `{` and `[` can be open at once, with each dot in a dotted key counting as one more.
+     * Parsing recurses once per level, so this is what keeps deep nesting from overflowing the stack.
+     */
+    pub max_depth: usize,
+    /**
+     * In bytes
+     */
+    pub max_size: usize,
+    /**
+     * In chars, for both string values and quoted keys
+     */
+    pub max_string_length: usize,
+    /**
+     * Keys in a single object, or elements in a single array
+     */
+    pub max_elements: usize,
+    /**
+     * Values that references copy into the document, counting everything inside copied objects
+     * and arrays. Keeps a small document from referencing its way into a huge one.
+     */
+    pub max_expanded: usize,
+    pub duplicate_keys: DuplicateKeys,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 128,
+            max_size: usize::MAX,
+            max_string_length: usize::MAX,
+            max_elements: usize::MAX,
+            max_expanded: 1_000_000,
+            duplicate_keys: DuplicateKeys::Error,
+        }
+    }
+}
+
+/**
+ * What to do when an object has the same key more than once, flags included
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DuplicateKeys {
+    /**
+     * Fail with `ParseError::DuplicateKey`
+     */
+    #[default]
+    Error,
+    /**
+     * Keep the last value, and list the key in `Document::duplicates`
+     */
+    Warn,
+    FirstWins,
+    LastWins,
+    /**
+     * Put every value for the key into an array, in the order they were written
+     */
+    Collect,
+}
+
+/**
+ * A key written twice in the same object, with the span of each
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateKey {
+    pub path: Path,
+    pub first: Position,
+    pub second: Position,
+}
+
+static IDENTIFIER: Lazy<Regex> = lazy! { Regex::new(r"^([a-zA-Z$_][a-zA-Z$_\-0-9]+)$").unwrap() };
+
+/**
+ * Whether a key can be written without quotes, which rules out ones like `null` that read as something else
+ */
+fn is_bare_key(key: &str) -> bool {
+    if !IDENTIFIER.is_match(key) {
+        return false;
+    }
+    match Tokenizer::new(key).next_token() {
+        Token::Identifier(name, _) => name == key,
+        _ => false,
+    }
+}
+
+fn write_key(out: &mut String, key: &str) {
+    if is_bare_key(key) {
+        out.push_str(key);
+    } else {
+        out.push('"');
+        out.push_str(&token::escape(key));
+        out.push('"');
+    }
+}
 
 impl FennecType {
-    fn replace_escapes(str: &str) -> String {
-        str.replace('\n', "\\n")
-            .replace('\t', "\\t")
-            .replace('\r', "\\r")
-            .replace(8 as char, "\\b")
-            .replace(12 as char, "\\f")
+    pub fn stringify(&self, options: &StringifyOptions) -> String {
+        self.to_string_internal(0, true, options)
     }
 
-    fn to_string_internal(&self, indent: usize, first: bool) -> String {
+    fn to_string_internal(
+        &self,
+        indent: usize,
+        first: bool,
+        options: &StringifyOptions,
+    ) -> String {
         match self {
             Self::String(str) => {
-                return format_args!("\"{}\"", &FennecType::replace_escapes(str)).to_string()
+                return format_args!("\"{}\"", token::escape(str)).to_string()
+            }
+            Self::Float(num) => {
+                if num.is_nan() {
+                    return "nan".to_string();
+                }
+                // Debug always writes a `.0` or an exponent, so whole floats don't come back as ints,
+                // and writes `1e300` rather than all 301 digits
+                return format!("{num:?}");
             }
-            Self::Float(num) => return num.to_string(),
             Self::Int(num) => return num.to_string(),
+            Self::BigInt(num) => return num.to_string(),
+            Self::DateTime(date_time) => return date_time.to_string(),
+            Self::Date(date) => return date.to_string(),
+            Self::Time(time) => return time.to_string(),
+            Self::Duration(duration) => return datetime::format_duration(duration),
+            Self::Quantity(quantity) => return quantity.to_string(),
             Self::Bool(bool) => return bool.to_string(),
             Self::Null => return "null".to_string(),
+            Self::Reference(path, _) => return format!("&{path}"),
 
             Self::Object(obj) => {
                 let mut out = "".to_string();
 
+                // An empty document doesn't parse, so an empty root keeps its braces
+                if first && obj.is_empty() {
+                    return "{}".to_string();
+                }
+
                 if !first {
                     out.push('{');
                     out.push('\n');
@@ -57,31 +264,40 @@ impl FennecType {
 
                 let idt = if first { indent } else { indent + 1 };
 
-                for key in obj.keys() {
-                    out.push_str(&" ".repeat(idt * INDENT));
-                    if IDENTIFIER.is_match(key) {
-                        out.push_str(key);
-                    } else {
-                        out.push('"');
-                        out.push_str(&FennecType::replace_escapes(key));
-                        out.push('"');
+                let mut keys: Vec<_> = obj.keys().collect();
+                if options.sort_keys {
+                    keys.sort();
+                }
+
+                for key in keys {
+                    out.push_str(&" ".repeat(idt * options.indent));
+                    write_key(&mut out, key);
+                    let mut val = &obj[key];
+                    if options.dotted_keys {
+                        while let Self::Object(obj) = val
+                            && let Some((key, inner)) = obj.iter().next()
+                            && obj.len() == 1
+                        {
+                            out.push('.');
+                            write_key(&mut out, key);
+                            val = inner;
+                        }
                     }
-                    let val = &obj[key];
                     match val {
                         Self::Object(_) | Self::Array(_) => {
                             out.push(' ');
-                            out.push_str(&val.to_string_internal(idt, false))
+                            out.push_str(&val.to_string_internal(idt, false, options))
                         }
                         _ => {
                             out.push_str(" = ");
-                            out.push_str(&val.to_string_internal(idt, false));
+                            out.push_str(&val.to_string_internal(idt, false, options));
                         }
                     }
                     out.push('\n');
                 }
 
                 if !first {
-                    out.push_str(&" ".repeat(indent * INDENT));
+                    out.push_str(&" ".repeat(indent * options.indent));
                     out.push('}');
                 }
 
@@ -93,18 +309,36 @@ impl FennecType {
                 let idt = indent + 1;
 
                 for val in arr {
-                    out.push_str(&" ".repeat(idt * INDENT));
-                    out.push_str(&val.to_string_internal(idt, false));
+                    out.push_str(&" ".repeat(idt * options.indent));
+                    out.push_str(&val.to_string_internal(idt, false, options));
                     out.push('\n');
                 }
 
-                out.push_str(&" ".repeat(indent * INDENT));
+                out.push_str(&" ".repeat(indent * options.indent));
                 out.push(']');
                 return out;
             }
         }
     }
 
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Object(_) => "object",
+            Self::Array(_) => "array",
+            Self::String(_) => "string",
+            Self::Float(_) => "float",
+            Self::Int(_) | Self::BigInt(_) => "int",
+            Self::DateTime(_) => "datetime",
+            Self::Date(_) => "date",
+            Self::Time(_) => "time",
+            Self::Duration(_) => "duration",
+            Self::Quantity(_) => "quantity",
+            Self::Bool(_) => "bool",
+            Self::Null => "null",
+            Self::Reference(_, _) => "reference",
+        }
+    }
+
     pub fn as_object(&self) -> Option<&HashMap<String, FennecType>> {
         if let Self::Object(var) = self {
             return Some(var);
@@ -117,6 +351,16 @@ impl FennecType {
         }
         None
     }
+    pub fn get_path(&self, path: &Path) -> Option<&FennecType> {
+        let mut current = self;
+        for segment in &path.0 {
+            current = match segment {
+                PathSegment::Key(key) => current.get_key(key)?,
+                PathSegment::Index(index) => current.get_index(*index)?,
+            };
+        }
+        Some(current)
+    }
     pub fn as_array(&self) -> Option<&Vec<FennecType>> {
         if let Self::Array(var) = self {
             return Some(var);
@@ -147,6 +391,74 @@ impl FennecType {
         }
         None
     }
+    /**
+     * Either kind of int, as long as it fits
+     */
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_int_of()
+    }
+    pub fn as_i128(&self) -> Option<i128> {
+        self.as_int_of()
+    }
+    pub fn as_u128(&self) -> Option<u128> {
+        self.as_int_of()
+    }
+    pub fn as_big_int(&self) -> Option<BigInt> {
+        match self {
+            Self::Int(var) => Some(BigInt::from(*var)),
+            Self::BigInt(var) => Some(var.clone()),
+            _ => None,
+        }
+    }
+    fn as_int_of<T: TryFrom<i64> + for<'a> TryFrom<&'a BigInt>>(&self) -> Option<T> {
+        match self {
+            Self::Int(var) => T::try_from(*var).ok(),
+            Self::BigInt(var) => T::try_from(var).ok(),
+            _ => None,
+        }
+    }
+    pub fn as_date_time(&self) -> Option<DateTime> {
+        if let Self::DateTime(var) = self {
+            return Some(*var);
+        }
+        None
+    }
+    pub fn as_date(&self) -> Option<Date> {
+        if let Self::Date(var) = self {
+            return Some(*var);
+        }
+        None
+    }
+    pub fn as_time(&self) -> Option<Time> {
+        if let Self::Time(var) = self {
+            return Some(*var);
+        }
+        None
+    }
+    pub fn as_duration(&self) -> Option<Duration> {
+        if let Self::Duration(var) = self {
+            return Some(*var);
+        }
+        None
+    }
+    /**
+     * Only date-times with an offset are a point in time
+     */
+    pub fn as_system_time(&self) -> Option<SystemTime> {
+        self.as_date_time()?.to_system_time()
+    }
+    pub fn as_quantity(&self) -> Option<Quantity> {
+        if let Self::Quantity(var) = self {
+            return Some(*var);
+        }
+        None
+    }
+    /**
+     * A size like `512MiB` in bytes, see `Quantity::as_bytes`
+     */
+    pub fn as_bytes(&self) -> Option<u64> {
+        self.as_quantity()?.as_bytes()
+    }
     pub fn as_bool(&self) -> Option<bool> {
         if let Self::Bool(var) = self {
             return Some(*var);
@@ -161,22 +473,205 @@ impl FennecType {
     }
 }
 
+/**
+ * Which of the `ParseOptions` limits was exceeded, along with its value
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Limit {
+    Depth(usize),
+    Size(usize),
+    StringLength(usize),
+    Elements(usize),
+    Expanded(usize),
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Depth(max) => write!(f, "nested more than {max} deep"),
+            Self::Size(max) => write!(f, "document is larger than {max} bytes"),
+            Self::StringLength(max) => write!(f, "string is longer than {max} chars"),
+            Self::Elements(max) => write!(f, "more than {max} elements"),
+            Self::Expanded(max) => write!(f, "references expand to more than {max} values"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ParseError {
     UnexpectedToken(Token),
+    Reference(ReferenceError),
+    /**
+     * There's no position for `Limit::Size`, since it's checked before anything is parsed
+     */
+    LimitExceeded(Limit, Option<Position>),
+    DuplicateKey(DuplicateKey),
+    /**
+     * A dotted key that goes through a value that isn't an object, e.g. `port = 80 port.tls = true`.
+     * Has the path of that value and the name of its type.
+     */
+    KeyConflict(Path, &'static str, Position),
+}
+
+impl ParseError {
+    /**
+     * Where the error happened, `None` if the input ended early
+     */
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            Self::UnexpectedToken(token) => token.position(),
+            Self::Reference(ReferenceError::Missing(_, pos)) => Some(*pos),
+            Self::Reference(ReferenceError::Cycle(cycle)) => cycle.first().map(|(_, pos)| *pos),
+            Self::LimitExceeded(_, pos) => *pos,
+            Self::DuplicateKey(duplicate) => Some(duplicate.second),
+            Self::KeyConflict(_, _, pos) => Some(*pos),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedToken(token) => match token {
+                Token::Identifier(name, _) => write!(f, "unexpected key {name}"),
+                Token::Flag(name, _) => write!(f, "unexpected flag -{name}"),
+                Token::Reference(path, _) => write!(f, "unexpected reference &{path}"),
+                Token::Symbol(char, _) => write!(f, "unexpected '{char}'"),
+                Token::Type(name, _) => write!(f, "unexpected type {name}"),
+                Token::Comment(_, _) => write!(f, "unexpected comment"),
+                Token::String(_, _) => write!(f, "unexpected string"),
+                Token::Float(num, _) => write!(f, "unexpected float {num}"),
+                Token::Int(num, _) => write!(f, "unexpected int {num}"),
+                Token::BigInt(num, _) => write!(f, "unexpected int {num}"),
+                Token::DateTime(date_time, _) => write!(f, "unexpected date-time {date_time}"),
+                Token::Date(date, _) => write!(f, "unexpected date {date}"),
+                Token::Time(time, _) => write!(f, "unexpected time {time}"),
+                Token::Duration(duration, _) => {
+                    write!(f, "unexpected duration {}", datetime::format_duration(duration))
+                }
+                Token::Quantity(quantity, _) => write!(f, "unexpected quantity {quantity}"),
+                Token::Bool(bool, _) => write!(f, "unexpected {bool}"),
+                Token::Null(_) => write!(f, "unexpected null"),
+                Token::Invalid(msg, _) => f.write_str(msg),
+                Token::Err(_) => write!(f, "invalid token"),
+                Token::Eof => write!(f, "unexpected end of input"),
+            },
+            Self::Reference(ReferenceError::Missing(path, _)) => {
+                write!(f, "reference to missing value &{path}")
+            }
+            Self::Reference(ReferenceError::Cycle(cycle)) => {
+                let paths: Vec<_> = cycle.iter().map(|(path, _)| format!("&{path}")).collect();
+                write!(f, "reference cycle {}", paths.join(" -> "))
+            }
+            Self::LimitExceeded(limit, _) => limit.fmt(f),
+            Self::DuplicateKey(duplicate) => write!(f, "duplicate key {}", duplicate.path),
+            Self::KeyConflict(path, found, _) => {
+                let article = if found.starts_with(['a', 'i', 'o']) {
+                    "an"
+                } else {
+                    "a"
+                };
+                write!(f, "can't add keys to {path}, it's {article} {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/**
+ * A parsed value, along with where each of its keys and array elements was written
+ */
+#[derive(Debug)]
+pub struct Document {
+    pub value: FennecType,
+    pub spans: HashMap<Path, Position>,
+    /**
+     * The comment lines written right above each key. A comment at the top, with a blank line
+     * after it, is kept under the root path instead.
+     */
+    pub comments: HashMap<Path, String>,
+    /**
+     * Keys that were written more than once, when `DuplicateKeys::Warn` let them through
+     */
+    pub duplicates: Vec<DuplicateKey>,
+}
+
+impl Document {
+    /**
+     * The span of the closest value at or above the path that has one
+     */
+    pub fn span_of(&self, path: &Path) -> Option<Position> {
+        let mut path = path.clone();
+        loop {
+            if let Some(pos) = self.spans.get(&path) {
+                return Some(*pos);
+            }
+            path.0.pop()?;
+        }
+    }
 }
 
 pub struct Parser {
     tokenizer: Tokenizer,
+    options: ParseOptions,
+    depth: usize,
+    path: Path,
+    spans: HashMap<Path, Position>,
+    comments: HashMap<Path, String>,
+    duplicates: Vec<DuplicateKey>,
+    /**
+     * Keys whose values `DuplicateKeys::Collect` has already turned into an array
+     */
+    collected: HashSet<Path>,
+    /**
+     * Objects that only dotted keys have added to, which a block for the same key merges into
+     */
+    implicit: HashSet<Path>,
+}
+
+/**
+ * The spans and comments of one value, set aside while a duplicate key is parsed
+ */
+struct Spans {
+    spans: HashMap<Path, Position>,
+    comments: HashMap<Path, String>,
 }
 
 impl Parser {
     pub fn new(tokenizer: Tokenizer) -> Self {
-        Self { tokenizer }
+        Self::with_options(tokenizer, ParseOptions::default())
+    }
+
+    pub fn with_options(tokenizer: Tokenizer, options: ParseOptions) -> Self {
+        Self {
+            tokenizer,
+            options,
+            depth: 0,
+            path: Path::new(),
+            spans: HashMap::new(),
+            comments: HashMap::new(),
+            duplicates: Vec::new(),
+            collected: HashSet::new(),
+            implicit: HashSet::new(),
+        }
+    }
+
+    pub fn parse_document(&mut self) -> Result<Document, ParseError> {
+        let value = self.parse_root()?;
+        Ok(Document {
+            value,
+            spans: std::mem::take(&mut self.spans),
+            comments: std::mem::take(&mut self.comments),
+            duplicates: std::mem::take(&mut self.duplicates),
+        })
     }
 
     pub fn parse_root(&mut self) -> Result<FennecType, ParseError> {
         let token = self.tokenizer.next();
+        if let Some(header) = self.tokenizer.header.take() {
+            self.comments.insert(Path::new(), header);
+        }
         match &token {
             Token::Identifier(_, pos) | Token::Flag(_, pos) => {
                 self.tokenizer.index = pos.0;
@@ -185,6 +680,7 @@ impl Parser {
 
             Token::String(val, pos) => {
                 if let Token::Eof = self.tokenizer.next() {
+                    self.check_string(val, *pos)?;
                     return Ok(FennecType::String(val.to_string()));
                 }
                 self.tokenizer.index = pos.0;
@@ -195,7 +691,21 @@ impl Parser {
             | Token::Float(_, _)
             | Token::Bool(_, _)
             | Token::Int(_, _)
-            | Token::Null(_) => return self.parse_value(token),
+            | Token::BigInt(_, _)
+            | Token::DateTime(_, _)
+            | Token::Date(_, _)
+            | Token::Time(_, _)
+            | Token::Duration(_, _)
+            | Token::Quantity(_, _)
+            | Token::Null(_)
+            | Token::Reference(_, _) => {
+                // Otherwise `inf = 1` would quietly read as just `inf`
+                let value = self.parse_value(token)?;
+                match self.tokenizer.next() {
+                    Token::Eof => return Ok(value),
+                    token => return Err(ParseError::UnexpectedToken(token)),
+                }
+            }
 
             _ => return Err(ParseError::UnexpectedToken(token)),
         };
@@ -207,11 +717,18 @@ impl Parser {
             | Token::Float(_, _)
             | Token::Bool(_, _)
             | Token::Int(_, _)
-            | Token::Null(_) => return self.parse_primitive(token),
+            | Token::BigInt(_, _)
+            | Token::DateTime(_, _)
+            | Token::Date(_, _)
+            | Token::Time(_, _)
+            | Token::Duration(_, _)
+            | Token::Quantity(_, _)
+            | Token::Null(_)
+            | Token::Reference(_, _) => return self.parse_primitive(token),
 
             Token::Symbol(char, _) => match char {
-                '{' => return self.parse_object(false),
-                '[' => return self.parse_array(),
+                '{' => return self.parse_nested(|parser| parser.parse_object(false)),
+                '[' => return self.parse_nested(Self::parse_array),
                 _ => return Err(ParseError::UnexpectedToken(token)),
             },
 
@@ -221,19 +738,43 @@ impl Parser {
 
     fn parse_primitive(&mut self, token: Token) -> Result<FennecType, ParseError> {
         match &token {
-            Token::String(str, _) => return Ok(FennecType::String(str.to_string())),
+            Token::String(str, pos) => {
+                self.check_string(str, *pos)?;
+                return Ok(FennecType::String(str.to_string()));
+            }
             Token::Float(val, _) => return Ok(FennecType::Float(*val)),
             Token::Int(val, _) => return Ok(FennecType::Int(*val)),
+            Token::BigInt(val, _) => return Ok(FennecType::BigInt(val.clone())),
+            Token::DateTime(val, _) => return Ok(FennecType::DateTime(*val)),
+            Token::Date(val, _) => return Ok(FennecType::Date(*val)),
+            Token::Time(val, _) => return Ok(FennecType::Time(*val)),
+            Token::Duration(val, _) => return Ok(FennecType::Duration(*val)),
+            Token::Quantity(val, _) => return Ok(FennecType::Quantity(*val)),
             Token::Bool(val, _) => return Ok(FennecType::Bool(*val)),
             Token::Null(_) => return Ok(FennecType::Null),
+            Token::Reference(path, pos) => {
+                if let Ok(path) = path.parse() {
+                    return Ok(FennecType::Reference(path, *pos));
+                }
+                return Err(ParseError::UnexpectedToken(token));
+            }
 
             _ => return Err(ParseError::UnexpectedToken(token)),
         };
     }
 
     fn parse_object(&mut self, expect_eof: bool) -> Result<FennecType, ParseError> {
-        let mut out = HashMap::new();
+        self.fill_object(HashMap::new(), expect_eof)
+    }
 
+    /**
+     * Parses an object's entries into `out`, which already holds any that dotted keys added earlier
+     */
+    fn fill_object(
+        &mut self,
+        mut out: HashMap<String, FennecType>,
+        expect_eof: bool,
+    ) -> Result<FennecType, ParseError> {
         loop {
             let token = self.tokenizer.next();
             match &token {
@@ -253,51 +794,53 @@ impl Parser {
                     }
                 }
 
-                Token::Flag(name, _) => {
-                    out.insert(name.to_string(), FennecType::Bool(true));
+                Token::Flag(name, pos) => {
+                    let (parents, name) = self.dotted_key(name, *pos)?;
+                    let target = self.enter_parents(&mut out, &parents, pos.0)?;
+                    let earlier = self.start_key(&name, target, *pos)?;
+                    self.record_comment(&self.path.clone(), *pos);
+                    self.spans.insert(self.path.clone(), Position(pos.0, self.tokenizer.index));
+                    let value = FennecType::Bool(true);
+                    self.finish_key(target, &name, value, earlier)?;
+                    self.leave_parents(&parents);
                 }
 
-                Token::Identifier(name, _) | Token::String(name, _) => {
+                Token::Identifier(name, pos) | Token::String(name, pos) => {
+                    self.check_string(name, *pos)?;
+                    let start = pos.0;
+                    let (parents, name) = self.dotted_key(name, *pos)?;
+                    let target = self.enter_parents(&mut out, &parents, start)?;
                     let next = self.tokenizer.next();
 
-                    if let Token::Symbol(symbol, _) = next {
-                        match symbol {
-                            '=' => {
-                                let prim_token = self.tokenizer.next();
-                                let primitive = self.parse_primitive(prim_token);
-                                if primitive.is_err() {
-                                    return primitive;
-                                }
-                                out.insert(
-                                    name.to_string(),
-                                    primitive.expect("We just checked! This shouldn't be Err."),
-                                );
-                            }
-                            '[' => {
-                                let arr = self.parse_array();
-                                if arr.is_err() {
-                                    return arr;
-                                }
-                                out.insert(
-                                    name.to_string(),
-                                    arr.expect("We just checked! This shouldn't be Err."),
-                                );
-                            }
-                            '{' => {
-                                let obj = self.parse_object(false);
-                                if obj.is_err() {
-                                    return obj;
-                                }
-                                out.insert(
-                                    name.to_string(),
-                                    obj.expect("We just checked! This shouldn't be Err."),
-                                );
-                            }
-                            _ => return Err(ParseError::UnexpectedToken(next)),
+                    // A block for an object that dotted keys started carries on filling it in
+                    let started = match next {
+                        Token::Symbol('{', _) => self.take_implicit(target, &name),
+                        _ => None,
+                    };
+                    let earlier = match started {
+                        Some(_) => {
+                            self.path.0.push(PathSegment::Key(name.clone()));
+                            None
                         }
-                    } else {
-                        return Err(ParseError::UnexpectedToken(next));
-                    }
+                        None => self.start_key(&name, target, *pos)?,
+                    };
+                    self.record_comment(&self.path.clone(), *pos);
+
+                    let value = match next {
+                        Token::Symbol('=', _) => {
+                            let prim_token = self.tokenizer.next();
+                            self.parse_primitive(prim_token)?
+                        }
+                        Token::Symbol('[', _) => self.parse_nested(Self::parse_array)?,
+                        Token::Symbol('{', _) => self.parse_nested(|parser| {
+                            parser.fill_object(started.unwrap_or_default(), false)
+                        })?,
+                        _ => return Err(ParseError::UnexpectedToken(next)),
+                    };
+
+                    self.spans.insert(self.path.clone(), Position(start, self.tokenizer.index));
+                    self.finish_key(target, &name, value, earlier)?;
+                    self.leave_parents(&parents);
                 }
 
                 _ => return Err(ParseError::UnexpectedToken(token)),
@@ -305,6 +848,220 @@ impl Parser {
         }
     }
 
+    /**
+     * Pushes the key onto `self.path`. If it's already in `out`, the spans and comments of
+     * the earlier value are taken out of the way and returned, so the new value can take their place.
+     */
+    fn start_key(
+        &mut self,
+        name: &str,
+        out: &HashMap<String, FennecType>,
+        pos: Position,
+    ) -> Result<Option<Spans>, ParseError> {
+        self.path.0.push(PathSegment::Key(name.to_string()));
+        if !out.contains_key(name) {
+            self.check_elements(out.len(), pos)?;
+            self.forget_below();
+            return Ok(None);
+        }
+        Ok(Some(self.take_spans(&self.path.clone())))
+    }
+
+    /**
+     * Called for a key that's new to its object, so nothing remembered about
+     * an earlier value at the same path carries over to this one
+     */
+    fn forget_below(&mut self) {
+        let under = |key: &Path| key.0.starts_with(&self.path.0);
+        self.collected.retain(|key| !under(key));
+        self.implicit.retain(|key| !under(key));
+    }
+
+    /**
+     * Reads the rest of a dotted key like `server.http.port`, right after its first part.
+     * Returns the parts before the last one, with where each was written, and the last one.
+     */
+    fn dotted_key(
+        &mut self,
+        name: &str,
+        pos: Position,
+    ) -> Result<(Vec<(String, Position)>, String), ParseError> {
+        let mut parents = Vec::new();
+        let mut last = (name.to_string(), pos);
+        let mut end = pos.1;
+        while !self.tokenizer.is_end() && self.tokenizer.get_char() == '.' {
+            self.tokenizer.index += 1;
+            let token = self.tokenizer.next_token();
+            match token {
+                // No space is allowed after the dot
+                Token::Identifier(name, pos) | Token::String(name, pos) if pos.0 == end + 1 => {
+                    self.check_string(&name, pos)?;
+                    end = pos.1;
+                    parents.push(std::mem::replace(&mut last, (name, pos)));
+                }
+                _ => return Err(ParseError::UnexpectedToken(token)),
+            }
+        }
+        Ok((parents, last.0))
+    }
+
+    /**
+     * Walks into the objects named by the first parts of a dotted key, pushing each onto `self.path`.
+     * The ones that aren't there yet are created, and remembered so a block can fill them in later.
+     */
+    fn enter_parents<'a>(
+        &mut self,
+        mut out: &'a mut HashMap<String, FennecType>,
+        parents: &[(String, Position)],
+        start: usize,
+    ) -> Result<&'a mut HashMap<String, FennecType>, ParseError> {
+        for (name, pos) in parents {
+            if self.depth >= self.options.max_depth {
+                return Err(ParseError::LimitExceeded(
+                    Limit::Depth(self.options.max_depth),
+                    Some(*pos),
+                ));
+            }
+            self.depth += 1;
+            self.path.0.push(PathSegment::Key(name.clone()));
+            if !out.contains_key(name) {
+                self.check_elements(out.len(), *pos)?;
+                self.forget_below();
+                self.implicit.insert(self.path.clone());
+                self.spans.insert(self.path.clone(), Position(start, pos.1));
+                out.insert(name.clone(), FennecType::Object(HashMap::new()));
+            }
+            out = match out.get_mut(name) {
+                Some(FennecType::Object(obj)) => obj,
+                value => {
+                    let found = value.map_or("null", |value| value.type_name());
+                    return Err(ParseError::KeyConflict(self.path.clone(), found, *pos));
+                }
+            };
+        }
+        Ok(out)
+    }
+
+    fn leave_parents(&mut self, parents: &[(String, Position)]) {
+        for _ in parents {
+            self.path.0.pop();
+            self.depth -= 1;
+        }
+    }
+
+    /**
+     * Takes `name` back out of `out` if it's an object that only dotted keys have added to so far
+     */
+    fn take_implicit(
+        &mut self,
+        out: &mut HashMap<String, FennecType>,
+        name: &str,
+    ) -> Option<HashMap<String, FennecType>> {
+        if !self.implicit.remove(&self.path.join_key(name)) {
+            return None;
+        }
+        match out.remove(name) {
+            Some(FennecType::Object(obj)) => Some(obj),
+            Some(value) => {
+                out.insert(name.to_string(), value);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /**
+     * Puts the value into `out` following `ParseOptions::duplicate_keys`, and pops the key back off
+     */
+    fn finish_key(
+        &mut self,
+        out: &mut HashMap<String, FennecType>,
+        name: &str,
+        value: FennecType,
+        earlier: Option<Spans>,
+    ) -> Result<(), ParseError> {
+        let path = self.path.clone();
+        self.path.0.pop();
+        let Some(earlier) = earlier else {
+            out.insert(name.to_string(), value);
+            return Ok(());
+        };
+
+        let first = earlier.spans[&path];
+        let second = self.spans[&path];
+        let duplicate = DuplicateKey {
+            path: path.clone(),
+            first,
+            second,
+        };
+        match self.options.duplicate_keys {
+            DuplicateKeys::Error => return Err(ParseError::DuplicateKey(duplicate)),
+            DuplicateKeys::Warn => {
+                self.duplicates.push(duplicate);
+                out.insert(name.to_string(), value);
+            }
+            DuplicateKeys::LastWins => {
+                out.insert(name.to_string(), value);
+            }
+            DuplicateKeys::FirstWins => {
+                self.take_spans(&path);
+                self.restore_spans(earlier, &path, &path);
+            }
+            DuplicateKeys::Collect => {
+                let later = self.take_spans(&path);
+                let values = out.get_mut(name).expect("Duplicates are always in the map");
+                if self.collected.insert(path.clone()) {
+                    let first = std::mem::replace(values, FennecType::Array(Vec::new()));
+                    self.restore_spans(earlier, &path, &path.join_index(0));
+                    if let FennecType::Array(values) = values {
+                        values.push(first);
+                    }
+                } else {
+                    self.restore_spans(earlier, &path, &path);
+                }
+                if let FennecType::Array(values) = values {
+                    self.restore_spans(later, &path, &path.join_index(values.len()));
+                    values.push(value);
+                }
+                self.spans.insert(path, Position(first.0, second.1));
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * Removes the spans and comments at and below `path`
+     */
+    fn take_spans(&mut self, path: &Path) -> Spans {
+        let under = |key: &Path| key.0.starts_with(&path.0);
+        let spans = self.spans.extract_if(|key, _| under(key)).collect();
+        let comments = self.comments.extract_if(|key, _| under(key)).collect();
+        Spans { spans, comments }
+    }
+
+    /**
+     * Puts back what `take_spans` removed, moved from below `from` to below `to`
+     */
+    fn restore_spans(&mut self, spans: Spans, from: &Path, to: &Path) {
+        let rebase = |key: Path| {
+            let mut path = to.clone();
+            path.0.extend_from_slice(&key.0[from.0.len()..]);
+            path
+        };
+        for (key, span) in spans.spans {
+            self.spans.insert(rebase(key), span);
+        }
+        for (key, comment) in spans.comments {
+            self.comments.insert(rebase(key), comment);
+        }
+    }
+
+    fn record_comment(&mut self, path: &Path, pos: Position) {
+        if let Some(doc) = self.tokenizer.docs.get(&pos.0) {
+            self.comments.insert(path.clone(), doc.to_string());
+        }
+    }
+
     fn parse_array(&mut self) -> Result<FennecType, ParseError> {
         let mut out = Vec::new();
 
@@ -313,11 +1070,59 @@ impl Parser {
             if let Token::Symbol(char, _) = token && char == ']' {
                 return Ok(FennecType::Array(out));
             }
+            let start = token.position().map_or(self.tokenizer.index, |pos| pos.0);
+            self.check_elements(out.len(), Position(start, start))?;
+            self.path.0.push(PathSegment::Index(out.len()));
             let val = self.parse_value(token);
             if let Err(err) = val {
                 return Err(err);
             }
+            self.spans.insert(self.path.clone(), Position(start, self.tokenizer.index));
+            self.path.0.pop();
             out.push(val.expect("We just checked! This shouldn't be Err."));
         }
     }
+
+    /**
+     * Runs `parse` one level deeper, right after its opening `{` or `[`
+     */
+    fn parse_nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<FennecType, ParseError>,
+    ) -> Result<FennecType, ParseError> {
+        if self.depth >= self.options.max_depth {
+            let start = self.tokenizer.index - 1;
+            return Err(ParseError::LimitExceeded(
+                Limit::Depth(self.options.max_depth),
+                Some(Position(start, start + 1)),
+            ));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn check_string(&self, str: &str, pos: Position) -> Result<(), ParseError> {
+        if str.chars().count() > self.options.max_string_length {
+            return Err(ParseError::LimitExceeded(
+                Limit::StringLength(self.options.max_string_length),
+                Some(pos),
+            ));
+        }
+        Ok(())
+    }
+
+    /**
+     * Called before adding another element to a container that already has `len`
+     */
+    fn check_elements(&self, len: usize, pos: Position) -> Result<(), ParseError> {
+        if len >= self.options.max_elements {
+            return Err(ParseError::LimitExceeded(
+                Limit::Elements(self.options.max_elements),
+                Some(pos),
+            ));
+        }
+        Ok(())
+    }
 }
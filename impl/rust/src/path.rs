@@ -0,0 +1,169 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{lazy, token};
+
+static KEY: Lazy<Regex> = lazy! { Regex::new(r"^[a-zA-Z$_][a-zA-Z$_\-0-9]*$").unwrap() };
+
+/**
+ * The location of a value inside a document, e.g. `server.ports[0]`
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Path(pub Vec<PathSegment>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/**
+ * (char index the path stopped making sense at)
+ */
+#[derive(Debug)]
+pub struct InvalidPath(pub usize);
+
+impl Path {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn join_key(&self, key: &str) -> Self {
+        let mut out = self.clone();
+        out.0.push(PathSegment::Key(key.to_string()));
+        out
+    }
+
+    pub fn join_index(&self, index: usize) -> Self {
+        let mut out = self.clone();
+        out.0.push(PathSegment::Index(index));
+        out
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) => {
+                    if i != 0 {
+                        f.write_str(".")?;
+                    }
+                    if KEY.is_match(key) {
+                        f.write_str(key)?;
+                    } else {
+                        write!(f, "\"{}\"", token::escape(key))?;
+                    }
+                }
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Path {
+    type Err = InvalidPath;
+
+    /**
+     * Parses `key.other_key[0]."quoted key"`, the same syntax `Display` writes
+     */
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = str.chars().collect();
+        let mut out = Path::new();
+        let mut index = 0;
+
+        while index < chars.len() {
+            let char = chars[index];
+
+            if char == '[' {
+                let start = index + 1;
+                let Some(len) = chars[start..].iter().position(|c| *c == ']') else {
+                    return Err(InvalidPath(index));
+                };
+                let digits: String = chars[start..start + len].iter().collect();
+                let Ok(idx) = digits.parse::<usize>() else {
+                    return Err(InvalidPath(start));
+                };
+                out.0.push(PathSegment::Index(idx));
+                index = start + len + 1;
+                continue;
+            }
+
+            if !out.is_root() {
+                if char != '.' {
+                    return Err(InvalidPath(index));
+                }
+                index += 1;
+            }
+
+            if chars.get(index) == Some(&'"') {
+                let mut key = String::new();
+                index += 1;
+                loop {
+                    match chars.get(index) {
+                        None => return Err(InvalidPath(index)),
+                        Some('"') => break,
+                        Some('\\') => {
+                            let Some((escape, len)) = token::read_escape(&chars[index + 1..]) else {
+                                return Err(InvalidPath(index));
+                            };
+                            key.push(escape);
+                            index += len + 1;
+                        }
+                        Some(char) => {
+                            key.push(*char);
+                            index += 1;
+                        }
+                    }
+                }
+                out.0.push(PathSegment::Key(key));
+                index += 1;
+                continue;
+            }
+
+            let start = index;
+            while index < chars.len() && !matches!(chars[index], '.' | '[') {
+                index += 1;
+            }
+            let key: String = chars[start..index].iter().collect();
+            if !KEY.is_match(&key) {
+                return Err(InvalidPath(start));
+            }
+            out.0.push(PathSegment::Key(key));
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Path, PathSegment};
+
+    #[test]
+    fn round_trip() {
+        let path: Path = "server.ports[0].\"Hello, World!\"[2][3]".parse().unwrap();
+
+        assert_eq!(path.0.len(), 6);
+        assert_eq!(path.0[3], PathSegment::Key("Hello, World!".to_string()));
+        assert_eq!(path.to_string(), "server.ports[0].\"Hello, World!\"[2][3]");
+    }
+
+    #[test]
+    fn invalid() {
+        assert!("a..b".parse::<Path>().is_err());
+        assert!("a[b]".parse::<Path>().is_err());
+        assert!("a[0]b".parse::<Path>().is_err());
+        assert!("\"unterminated".parse::<Path>().is_err());
+    }
+}
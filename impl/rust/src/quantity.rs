@@ -0,0 +1,235 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use crate::{datetime, token::Token};
+
+/**
+ * What a unit measures. Quantities are only ever converted within one dimension.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    /**
+     * Sizes, counted in bytes
+     */
+    Bytes,
+    /**
+     * Transfer rates, counted in bytes per second
+     */
+    ByteRate,
+    /**
+     * Transfer rates, counted in bits per second
+     */
+    BitRate,
+}
+
+/**
+ * A suffix a number can be written with, e.g. the `MiB` in `512MiB`
+ */
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Unit {
+    pub suffix: &'static str,
+    pub dimension: Dimension,
+    /**
+     * How many of the dimension's base unit one of these is
+     */
+    pub scale: u64,
+}
+
+const fn unit(suffix: &'static str, dimension: Dimension, scale: u64) -> Unit {
+    Unit {
+        suffix,
+        dimension,
+        scale,
+    }
+}
+
+/**
+ * Every unit that can be written after a number. Suffixes are case sensitive, so `Mb` isn't `MB`.
+ */
+pub const UNITS: &[Unit] = &[
+    unit("B", Dimension::Bytes, 1),
+    unit("KB", Dimension::Bytes, 1_000),
+    unit("kB", Dimension::Bytes, 1_000),
+    unit("MB", Dimension::Bytes, 1_000_000),
+    unit("GB", Dimension::Bytes, 1_000_000_000),
+    unit("TB", Dimension::Bytes, 1_000_000_000_000),
+    unit("PB", Dimension::Bytes, 1_000_000_000_000_000),
+    unit("KiB", Dimension::Bytes, 1 << 10),
+    unit("MiB", Dimension::Bytes, 1 << 20),
+    unit("GiB", Dimension::Bytes, 1 << 30),
+    unit("TiB", Dimension::Bytes, 1 << 40),
+    unit("PiB", Dimension::Bytes, 1 << 50),
+    unit("B/s", Dimension::ByteRate, 1),
+    unit("KB/s", Dimension::ByteRate, 1_000),
+    unit("kB/s", Dimension::ByteRate, 1_000),
+    unit("MB/s", Dimension::ByteRate, 1_000_000),
+    unit("GB/s", Dimension::ByteRate, 1_000_000_000),
+    unit("KiB/s", Dimension::ByteRate, 1 << 10),
+    unit("MiB/s", Dimension::ByteRate, 1 << 20),
+    unit("GiB/s", Dimension::ByteRate, 1 << 30),
+    unit("bps", Dimension::BitRate, 1),
+    unit("Kbps", Dimension::BitRate, 1_000),
+    unit("kbps", Dimension::BitRate, 1_000),
+    unit("Mbps", Dimension::BitRate, 1_000_000),
+    unit("Gbps", Dimension::BitRate, 1_000_000_000),
+    unit("Tbps", Dimension::BitRate, 1_000_000_000_000),
+];
+
+impl Unit {
+    pub fn find(suffix: &str) -> Option<&'static Unit> {
+        UNITS.iter().find(|unit| unit.suffix == suffix)
+    }
+}
+
+/**
+ * A number with a unit, `512MiB` or `1.5Gbps`.
+ * The number is kept exactly as it was written, so it goes back out the same way.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Quantity {
+    /**
+     * The number without its decimal point, so `1.5` is 15 with 1 decimal
+     */
+    digits: u64,
+    decimals: u32,
+    unit: &'static Unit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidQuantity;
+
+impl Display for InvalidQuantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid quantity")
+    }
+}
+
+impl std::error::Error for InvalidQuantity {}
+
+impl Quantity {
+    pub fn new(amount: u64, unit: &'static Unit) -> Self {
+        Self {
+            digits: amount,
+            decimals: 0,
+            unit,
+        }
+    }
+
+    /**
+     * Reads the number part, e.g. `1_024` or `1.5`. `None` if it doesn't fit.
+     */
+    pub fn from_number(number: &str, unit: &'static Unit) -> Option<Self> {
+        let number = number.replace('_', "");
+        let (whole, fraction) = number.split_once('.').unwrap_or((&number, ""));
+        let digits = format!("{whole}{fraction}").parse().ok()?;
+        let decimals = fraction.len().try_into().ok()?;
+        // `Display` splits the fraction back off with a power of 10, so that has to fit too
+        10u64.checked_pow(decimals)?;
+        Some(Self {
+            digits,
+            decimals,
+            unit,
+        })
+    }
+
+    pub fn unit(&self) -> &'static Unit {
+        self.unit
+    }
+
+    pub fn amount(&self) -> f64 {
+        self.digits as f64 / 10f64.powi(self.decimals as i32)
+    }
+
+    /**
+     * The amount in the dimension's base unit, so `1KiB` is 1024.
+     * `None` when that isn't a whole number or doesn't fit.
+     */
+    pub fn base_amount(&self) -> Option<u64> {
+        let scaled = u128::from(self.digits) * u128::from(self.unit.scale);
+        let divisor = 10u128.pow(self.decimals);
+        if scaled % divisor != 0 {
+            return None;
+        }
+        (scaled / divisor).try_into().ok()
+    }
+
+    pub fn as_bytes(&self) -> Option<u64> {
+        self.base_amount_in(Dimension::Bytes)
+    }
+
+    pub fn as_bytes_per_second(&self) -> Option<u64> {
+        self.base_amount_in(Dimension::ByteRate)
+    }
+
+    pub fn as_bits_per_second(&self) -> Option<u64> {
+        self.base_amount_in(Dimension::BitRate)
+    }
+
+    fn base_amount_in(&self, dimension: Dimension) -> Option<u64> {
+        if self.unit.dimension != dimension {
+            return None;
+        }
+        self.base_amount()
+    }
+}
+
+impl Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let divisor = 10u64.pow(self.decimals);
+        write!(f, "{}", self.digits / divisor)?;
+        if self.decimals > 0 {
+            let fraction = self.digits % divisor;
+            write!(f, ".{fraction:0width$}", width = self.decimals as usize)?;
+        }
+        f.write_str(self.unit.suffix)
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = InvalidQuantity;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match datetime::read(str) {
+            Some(Token::Quantity(quantity, _)) => Ok(quantity),
+            _ => Err(InvalidQuantity),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Quantity, Unit};
+
+    #[test]
+    fn scales_to_base_units() {
+        let quantity: Quantity = "512MiB".parse().unwrap();
+        assert_eq!(quantity.as_bytes(), Some(512 * 1024 * 1024));
+        assert_eq!(quantity.as_bits_per_second(), None);
+
+        let quantity: Quantity = "1.5KB".parse().unwrap();
+        assert_eq!(quantity.as_bytes(), Some(1500));
+        assert_eq!(quantity.amount(), 1.5);
+        assert_eq!("1.0001KB".parse::<Quantity>().unwrap().as_bytes(), None);
+        assert_eq!(
+            "100Mbps".parse::<Quantity>().unwrap().as_bits_per_second(),
+            Some(100_000_000)
+        );
+        assert_eq!("20000PiB".parse::<Quantity>().unwrap().as_bytes(), None);
+    }
+
+    #[test]
+    fn keeps_how_it_was_written() {
+        for str in ["1_024KiB", "1.50GB", "0.05MB/s", "10B"] {
+            let quantity: Quantity = str.parse().unwrap();
+            assert_eq!(quantity.to_string(), str.replace('_', ""));
+        }
+        assert_eq!(
+            Quantity::new(2, Unit::find("GiB").unwrap()).to_string(),
+            "2GiB"
+        );
+        assert!("10mb".parse::<Quantity>().is_err());
+        assert!("10".parse::<Quantity>().is_err());
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::HashMap;
+
+use crate::{
+    parse::{FennecType, Limit, ParseError, ParseOptions},
+    path::{Path, PathSegment},
+    token::Position,
+};
+
+/**
+ * (path of the reference, where the reference was written)
+ */
+#[derive(Debug)]
+pub enum ReferenceError {
+    Missing(Path, Position),
+    /**
+     * Every reference taking part in the cycle, in the order they were followed.
+     * The first and last entries name the same path.
+     */
+    Cycle(Vec<(Path, Position)>),
+}
+
+/**
+ * Replaces every `FennecType::Reference` in the tree with a copy of the value it points to.
+ * Paths are always looked up from the root of the document.
+ * Uses the limits from `ParseOptions::default()`, see `resolve_with`.
+ */
+pub fn resolve(root: &FennecType) -> Result<FennecType, ParseError> {
+    resolve_with(root, &ParseOptions::default())
+}
+
+/**
+ * Like `resolve`, with `max_depth` capping both how long a chain of references can get
+ * and how deep the resolved values can nest, and `max_expanded` capping how much gets copied
+ */
+pub fn resolve_with(root: &FennecType, options: &ParseOptions) -> Result<FennecType, ParseError> {
+    let mut resolver = Resolver {
+        root,
+        max_depth: options.max_depth,
+        max_expanded: options.max_expanded,
+        expanded: 0,
+        resolved: HashMap::new(),
+        stack: Vec::new(),
+    };
+    resolver.run()
+}
+
+/**
+ * A value with every reference in it replaced, and how big it is
+ */
+struct Resolved {
+    value: FennecType,
+    nodes: usize,
+    depth: usize,
+}
+
+/**
+ * A path being resolved, waiting on the references it needs
+ */
+struct Frame {
+    path: Path,
+    pos: Position,
+    /**
+     * The references in the value at `path`, once it's been found
+     */
+    pending: Option<Vec<(Path, Position)>>,
+}
+
+/**
+ * Where a path leads, as far as can be told without resolving anything else first
+ */
+enum Target<'a> {
+    Raw(&'a FennecType),
+    Resolved(FennecType),
+    Needs(Path, Position),
+    Missing,
+}
+
+/**
+ * Resolves each path once, keeping the result for every other reference to it.
+ * Chains of references go on `stack` rather than the native stack, so they can't overflow it.
+ */
+struct Resolver<'a> {
+    root: &'a FennecType,
+    max_depth: usize,
+    max_expanded: usize,
+    /**
+     * Values copied in for references so far
+     */
+    expanded: usize,
+    resolved: HashMap<Path, Resolved>,
+    stack: Vec<Frame>,
+}
+
+impl<'a> Resolver<'a> {
+    fn run(&mut self) -> Result<FennecType, ParseError> {
+        // The root goes first, under a path no reference can have
+        self.stack.push(Frame {
+            path: Path::new(),
+            pos: Position(0, 0),
+            pending: None,
+        });
+
+        while let Some(frame) = self.stack.last_mut() {
+            if self.resolved.contains_key(&frame.path) {
+                self.stack.pop();
+                continue;
+            }
+            if let Some(pending) = &mut frame.pending {
+                match pending.pop() {
+                    Some((path, _)) if self.resolved.contains_key(&path) => {}
+                    Some((path, pos)) => self.push(path, pos)?,
+                    None => self.finish()?,
+                }
+                continue;
+            }
+            let (path, pos) = (frame.path.clone(), frame.pos);
+            match self.walk(&path) {
+                Target::Raw(value) => {
+                    let mut pending = Vec::new();
+                    references_in(value, &mut pending);
+                    pending.reverse();
+                    if let Some(frame) = self.stack.last_mut() {
+                        frame.pending = Some(pending);
+                    }
+                }
+                Target::Resolved(value) => {
+                    let (nodes, depth) = measure(&value);
+                    self.expand(nodes, pos)?;
+                    self.resolved.insert(
+                        path,
+                        Resolved {
+                            value,
+                            nodes,
+                            depth,
+                        },
+                    );
+                    self.stack.pop();
+                }
+                Target::Needs(inner, inner_pos) => self.push(inner, inner_pos)?,
+                Target::Missing => {
+                    return Err(ParseError::Reference(ReferenceError::Missing(path, pos)));
+                }
+            }
+        }
+
+        let root = self
+            .resolved
+            .remove(&Path::new())
+            .expect("The root is resolved last");
+        Ok(root.value)
+    }
+
+    fn push(&mut self, path: Path, pos: Position) -> Result<(), ParseError> {
+        if let Some(idx) = self.stack.iter().position(|frame| frame.path == path) {
+            let mut cycle: Vec<_> = self.stack[idx..]
+                .iter()
+                .map(|frame| (frame.path.clone(), frame.pos))
+                .collect();
+            cycle.push((path, pos));
+            return Err(ParseError::Reference(ReferenceError::Cycle(cycle)));
+        }
+        // The root's frame doesn't count, it isn't a reference
+        if self.stack.len() > self.max_depth {
+            return Err(ParseError::LimitExceeded(
+                Limit::Depth(self.max_depth),
+                Some(pos),
+            ));
+        }
+        self.stack.push(Frame {
+            path,
+            pos,
+            pending: None,
+        });
+        Ok(())
+    }
+
+    /**
+     * Copies the value at the top frame's path now that everything it refers to is resolved
+     */
+    fn finish(&mut self) -> Result<(), ParseError> {
+        let frame = self.stack.pop().expect("Only called with a frame");
+        let Target::Raw(value) = self.walk(&frame.path) else {
+            unreachable!("The path led to a value the first time");
+        };
+        let resolved = self.copy(value)?;
+        // The parser doesn't count the root, so neither does `max_depth`
+        if resolved.depth > self.max_depth.saturating_add(1) {
+            return Err(ParseError::LimitExceeded(
+                Limit::Depth(self.max_depth),
+                Some(frame.pos),
+            ));
+        }
+        self.resolved.insert(frame.path, resolved);
+        Ok(())
+    }
+
+    fn copy(&mut self, value: &FennecType) -> Result<Resolved, ParseError> {
+        let mut nodes = 1;
+        let mut depth = 0;
+        let value = match value {
+            FennecType::Reference(path, pos) => {
+                self.expand(self.resolved[path].nodes, *pos)?;
+                let target = &self.resolved[path];
+                return Ok(Resolved {
+                    value: target.value.clone(),
+                    nodes: target.nodes,
+                    depth: target.depth,
+                });
+            }
+            FennecType::Object(obj) => {
+                let mut out = HashMap::with_capacity(obj.len());
+                for (key, val) in obj {
+                    let val = self.copy(val)?;
+                    nodes += val.nodes;
+                    depth = depth.max(val.depth + 1);
+                    out.insert(key.to_string(), val.value);
+                }
+                FennecType::Object(out)
+            }
+            FennecType::Array(arr) => {
+                let mut out = Vec::with_capacity(arr.len());
+                for val in arr {
+                    let val = self.copy(val)?;
+                    nodes += val.nodes;
+                    depth = depth.max(val.depth + 1);
+                    out.push(val.value);
+                }
+                FennecType::Array(out)
+            }
+            _ => value.clone(),
+        };
+        Ok(Resolved {
+            value,
+            nodes,
+            depth,
+        })
+    }
+
+    /**
+     * Counts `nodes` more values copied in for the reference at `pos`
+     */
+    fn expand(&mut self, nodes: usize, pos: Position) -> Result<(), ParseError> {
+        self.expanded = self.expanded.saturating_add(nodes);
+        if self.expanded > self.max_expanded {
+            return Err(ParseError::LimitExceeded(
+                Limit::Expanded(self.max_expanded),
+                Some(pos),
+            ));
+        }
+        Ok(())
+    }
+
+    /**
+     * Walks the path from the root. A reference part way along it has to be resolved first,
+     * after which the rest of the walk goes through its resolved value.
+     */
+    fn walk(&self, path: &Path) -> Target<'a> {
+        let mut current = self.root;
+
+        for (i, segment) in path.0.iter().enumerate() {
+            if let FennecType::Reference(inner, inner_pos) = current {
+                let Some(resolved) = self.resolved.get(inner) else {
+                    return Target::Needs(inner.clone(), *inner_pos);
+                };
+                let mut value = &resolved.value;
+                for segment in &path.0[i..] {
+                    let Some(next) = get_segment(value, segment) else {
+                        return Target::Missing;
+                    };
+                    value = next;
+                }
+                return Target::Resolved(value.clone());
+            }
+
+            let Some(next) = get_segment(current, segment) else {
+                return Target::Missing;
+            };
+            current = next;
+        }
+
+        Target::Raw(current)
+    }
+}
+
+fn references_in(value: &FennecType, out: &mut Vec<(Path, Position)>) {
+    match value {
+        FennecType::Reference(path, pos) => out.push((path.clone(), *pos)),
+        FennecType::Object(obj) => obj.values().for_each(|val| references_in(val, out)),
+        FennecType::Array(arr) => arr.iter().for_each(|val| references_in(val, out)),
+        _ => {}
+    }
+}
+
+/**
+ * (nodes, depth) of a value that's already been resolved
+ */
+fn measure(value: &FennecType) -> (usize, usize) {
+    let children: Box<dyn Iterator<Item = &FennecType>> = match value {
+        FennecType::Object(obj) => Box::new(obj.values()),
+        FennecType::Array(arr) => Box::new(arr.iter()),
+        _ => return (1, 0),
+    };
+    children.fold((1, 0), |(nodes, depth), child| {
+        let (child_nodes, child_depth) = measure(child);
+        (nodes + child_nodes, depth.max(child_depth + 1))
+    })
+}
+
+fn get_segment<'a>(value: &'a FennecType, segment: &PathSegment) -> Option<&'a FennecType> {
+    match segment {
+        PathSegment::Key(key) => value.get_key(key),
+        PathSegment::Index(index) => value.get_index(*index),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        parse::{FennecType, Limit, ParseError, Parser},
+        token::Tokenizer,
+    };
+
+    use super::ReferenceError;
+
+    #[test]
+    fn resolves_references() {
+        let fen = crate::parse(
+            "defaults { port = 80 hosts [ \"a\" \"b\" ] } server { port = &defaults.port host = &defaults.hosts[1] }",
+        )
+        .unwrap();
+
+        let server = fen.get_key("server").unwrap();
+        assert_eq!(server.get_key("port").unwrap().as_int(), Some(80));
+        assert_eq!(server.get_key("host").unwrap().as_string().unwrap(), "b");
+    }
+
+    #[test]
+    fn follows_chained_references() {
+        let fen = crate::parse("a { b = 1 } c = &a d = &c.b").unwrap();
+
+        assert_eq!(fen.get_key("d").unwrap().as_int(), Some(1));
+    }
+
+    #[test]
+    fn parser_keeps_references() {
+        let fen = Parser::new(Tokenizer::new("a = 1 b = &a"))
+            .parse_root()
+            .unwrap();
+
+        let b = fen.get_key("b").unwrap();
+        assert!(matches!(b, FennecType::Reference(_, _)));
+        assert_eq!(b.to_string(), "&a");
+    }
+
+    #[test]
+    fn reports_missing() {
+        let Err(ParseError::Reference(ReferenceError::Missing(path, pos))) =
+            crate::parse("a = &nowhere")
+        else {
+            panic!("expected a missing reference");
+        };
+
+        assert_eq!(path.to_string(), "nowhere");
+        assert_eq!((pos.0, pos.1), (4, 12));
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let Err(ParseError::Reference(ReferenceError::Cycle(cycle))) =
+            crate::parse("a = &b b = &a")
+        else {
+            panic!("expected a cycle");
+        };
+
+        assert_eq!(cycle.len(), 3);
+        assert_eq!(cycle.first().unwrap().0, cycle.last().unwrap().0);
+
+        let Err(ParseError::Reference(ReferenceError::Cycle(_))) = crate::parse("a { b = &a }")
+        else {
+            panic!("expected a cycle");
+        };
+    }
+
+    #[test]
+    fn resolves_each_path_once() {
+        // Each level refers to the one below ten times, so copying it out is 10^8 values
+        let mut doc = "a0 = 1".to_string();
+        for level in 1..=8 {
+            doc += &format!(" a{level} [{}]", format!(" &a{}", level - 1).repeat(10));
+        }
+        let Err(ParseError::LimitExceeded(Limit::Expanded(_), _)) = crate::parse(&doc) else {
+            panic!("expected the expansion limit");
+        };
+
+        let mut doc = "a0 = 1".to_string();
+        for level in 1..=4 {
+            doc += &format!(" a{level} [{}]", format!(" &a{}", level - 1).repeat(10));
+        }
+        let fen = crate::parse(&doc).unwrap();
+        assert_eq!(fen.get_key("a4").unwrap().as_array().unwrap().len(), 10);
+    }
+
+    #[test]
+    fn limits_chains() {
+        let chain = |len: usize| {
+            let mut doc: String = (0..len).map(|i| format!("k{i} = &k{} ", i + 1)).collect();
+            doc += &format!("k{len} = 1");
+            doc
+        };
+        assert_eq!(
+            crate::parse(&chain(100))
+                .unwrap()
+                .get_key("k0")
+                .unwrap()
+                .as_int(),
+            Some(1)
+        );
+        let Err(ParseError::LimitExceeded(Limit::Depth(128), _)) = crate::parse(&chain(5_000))
+        else {
+            panic!("expected the depth limit");
+        };
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use crate::{
+    parse::{Document, FennecType},
+    path::Path,
+};
+
+use super::{Range, Schema, SchemaType};
+
+/**
+ * Builds the narrowest schema that every sample matches.
+ * Keys missing from any sample become optional, and values that don't share a type become a union.
+ * Ints and floats in the same place merge into `number`.
+ */
+pub fn infer(samples: &[FennecType]) -> Schema {
+    samples
+        .iter()
+        .map(infer_value)
+        .reduce(merge)
+        .unwrap_or_else(Schema::any)
+}
+
+/**
+ * Like `infer`, also using the comments above each key in the samples as its description
+ */
+pub fn infer_documents(samples: &[Document]) -> Schema {
+    let values: Vec<_> = samples.iter().map(|doc| doc.value.clone()).collect();
+    let mut schema = infer(&values);
+    for doc in samples {
+        describe(&mut schema, &Path::new(), &doc.comments);
+    }
+    schema
+}
+
+fn describe(schema: &mut Schema, path: &Path, comments: &HashMap<Path, String>) {
+    if schema.description.is_none() {
+        schema.description = comments.get(path).cloned();
+    }
+    if let SchemaType::Object { keys, .. } = &mut schema.ty {
+        for (key, schema) in keys {
+            describe(schema, &path.join_key(key), comments);
+        }
+    }
+}
+
+fn infer_value(value: &FennecType) -> Schema {
+    let ty = match value {
+        FennecType::Object(obj) => SchemaType::Object {
+            keys: obj
+                .iter()
+                .map(|(key, val)| (key.to_string(), infer_value(val)))
+                .collect(),
+            additional_keys: false,
+        },
+        FennecType::Array(arr) => SchemaType::Array {
+            items: arr.iter().map(infer_value).reduce(merge).map(Box::new),
+            min_length: None,
+            max_length: None,
+        },
+        FennecType::String(_) => SchemaType::String { pattern: None },
+        FennecType::Float(_) => SchemaType::Float(Range::default()),
+        FennecType::Int(_) | FennecType::BigInt(_) => SchemaType::Int(Range::default()),
+        FennecType::DateTime(_) => SchemaType::DateTime,
+        FennecType::Date(_) => SchemaType::Date,
+        FennecType::Time(_) => SchemaType::Time,
+        FennecType::Duration(_) => SchemaType::Duration,
+        FennecType::Quantity(_) => SchemaType::Quantity,
+        FennecType::Bool(_) => SchemaType::Bool,
+        FennecType::Null => SchemaType::Null,
+        FennecType::Reference(_, _) => SchemaType::Any,
+    };
+    Schema::of(ty)
+}
+
+fn merge(a: Schema, b: Schema) -> Schema {
+    let optional = a.optional || b.optional;
+
+    let ty = match (a.ty, b.ty) {
+        (SchemaType::Any, _) | (_, SchemaType::Any) => SchemaType::Any,
+
+        (
+            SchemaType::Object { keys: a_keys, .. },
+            SchemaType::Object {
+                keys: mut b_keys, ..
+            },
+        ) => {
+            let mut keys = HashMap::new();
+            for (key, a_schema) in a_keys {
+                let schema = match b_keys.remove(&key) {
+                    Some(b_schema) => merge(a_schema, b_schema),
+                    None => Schema {
+                        optional: true,
+                        ..a_schema
+                    },
+                };
+                keys.insert(key, schema);
+            }
+            for (key, b_schema) in b_keys {
+                keys.insert(
+                    key,
+                    Schema {
+                        optional: true,
+                        ..b_schema
+                    },
+                );
+            }
+            SchemaType::Object {
+                keys,
+                additional_keys: false,
+            }
+        }
+
+        (SchemaType::Array { items: a_items, .. }, SchemaType::Array { items: b_items, .. }) => {
+            let items = match (a_items, b_items) {
+                (Some(a), Some(b)) => Some(Box::new(merge(*a, *b))),
+                (a, b) => a.or(b),
+            };
+            SchemaType::Array {
+                items,
+                min_length: None,
+                max_length: None,
+            }
+        }
+
+        (SchemaType::Union(mut schemas), other) | (other, SchemaType::Union(mut schemas)) => {
+            match other {
+                SchemaType::Union(others) => {
+                    for other in others {
+                        add_to_union(&mut schemas, other);
+                    }
+                }
+                other => add_to_union(&mut schemas, Schema::of(other)),
+            }
+            SchemaType::Union(schemas)
+        }
+
+        (a, b) => match merge_same_kind(a, b) {
+            Ok(ty) => ty,
+            Err((a, b)) => SchemaType::Union(vec![Schema::of(a), Schema::of(b)]),
+        },
+    };
+
+    Schema {
+        optional,
+        ..Schema::of(ty)
+    }
+}
+
+fn add_to_union(schemas: &mut Vec<Schema>, schema: Schema) {
+    let kind = kind(&schema.ty);
+    if let Some(idx) = schemas.iter().position(|s| self::kind(&s.ty) == kind) {
+        let existing = schemas.remove(idx);
+        schemas.insert(idx, merge(existing, schema));
+    } else {
+        schemas.push(schema);
+    }
+}
+
+/**
+ * Merges two scalar types, handing them back if they can't share a schema
+ */
+fn merge_same_kind(a: SchemaType, b: SchemaType) -> Result<SchemaType, (SchemaType, SchemaType)> {
+    if kind(&a) != kind(&b) {
+        return Err((a, b));
+    }
+    match (a, b) {
+        (SchemaType::Int(_), SchemaType::Int(_)) => Ok(SchemaType::Int(Range::default())),
+        (SchemaType::Float(_), SchemaType::Float(_)) => Ok(SchemaType::Float(Range::default())),
+        (
+            SchemaType::Int(_) | SchemaType::Float(_) | SchemaType::Number(_),
+            SchemaType::Int(_) | SchemaType::Float(_) | SchemaType::Number(_),
+        ) => Ok(SchemaType::Number(Range::default())),
+        (a, _) => Ok(a),
+    }
+}
+
+/**
+ * Types of the same kind merge into one schema instead of forming a union
+ */
+fn kind(ty: &SchemaType) -> &'static str {
+    match ty {
+        SchemaType::Int(_) | SchemaType::Float(_) | SchemaType::Number(_) => "number",
+        SchemaType::Any => "any",
+        SchemaType::Object { .. } => "object",
+        SchemaType::Array { .. } => "array",
+        SchemaType::String { .. } => "string",
+        SchemaType::Bool => "bool",
+        SchemaType::Null => "null",
+        SchemaType::DateTime => "datetime",
+        SchemaType::Date => "date",
+        SchemaType::Time => "time",
+        SchemaType::Duration => "duration",
+        SchemaType::Quantity => "quantity",
+        SchemaType::Union(_) => "union",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::schema::{Schema, SchemaType};
+
+    use super::infer;
+
+    const SAMPLES: &[&str] = &[
+        "name = \"a\" port = 80 tags [ \"x\" ] ratio = 1 id = 5",
+        "name = \"b\" tags [ ] ratio = 0.5 id = \"five\" -debug",
+        "name = \"c\" port = 81 tags [ \"y\" \"z\" ] ratio = 2 id = null",
+    ];
+
+    fn samples() -> Vec<crate::parse::FennecType> {
+        SAMPLES.iter().map(|s| crate::parse(s).unwrap()).collect()
+    }
+
+    #[test]
+    fn infers_presence_and_types() {
+        let schema = infer(&samples());
+        let SchemaType::Object { keys, .. } = &schema.ty else {
+            panic!("expected an object schema");
+        };
+
+        assert!(!keys["name"].optional);
+        assert!(keys["port"].optional);
+        assert!(keys["debug"].optional);
+        assert!(matches!(keys["port"].ty, SchemaType::Int(_)));
+        assert!(matches!(keys["ratio"].ty, SchemaType::Number(_)));
+        assert!(
+            matches!(&keys["tags"].ty, SchemaType::Array { items: Some(items), .. }
+            if matches!(items.ty, SchemaType::String { .. }))
+        );
+        assert_eq!(keys["id"].ty.name(), "int | string | null");
+    }
+
+    #[test]
+    fn prints_and_reparses() {
+        let samples = samples();
+        let text = infer(&samples).to_string();
+        let schema = Schema::parse(&text).unwrap();
+
+        for sample in &samples {
+            assert!(schema.validate(sample).is_empty(), "{text}");
+        }
+        assert!(!schema
+            .validate(&crate::parse("name = 1").unwrap())
+            .is_empty());
+    }
+}
@@ -0,0 +1,623 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+use num_traits::ToPrimitive;
+use regex::Regex;
+
+use crate::{
+    parse::{FennecType, ParseError},
+    path::Path,
+};
+
+mod infer;
+mod normalize;
+mod validate;
+
+pub use infer::{infer, infer_documents};
+pub use validate::{Violation, ViolationKind};
+
+/**
+ * Schemas are written in Fennec itself, usually in a `.fennec-schema` file.
+ * Each schema is an object describing one value:
+ *
+ * ```fennec
+ * type = "object"
+ * keys {
+ *     host { type = "string" pattern = "^[a-z.]+$" }
+ *     port { type = "int" min = 1 max = 65535 default = 8080 }
+ *     mode { type = "string" enum [ "dev" "prod" ] -optional }
+ *     tags { type = "array" items { type = "string" } max_length = 8 }
+ *     id { type [ "int" "string" ] }
+ * }
+ * ```
+ *
+ * `type` is one of `any`, `object`, `array`, `string`, `int`, `float`, `number`, `bool`, `null`,
+ * `datetime`, `date`, `time`, `duration` or `quantity`,
+ * or an array of them to accept any of the listed types.
+ * `one_of [ { ... } { ... } ]` does the same for schemas with their own constraints.
+ * Object keys are required unless they're marked `-optional` or have a `default`,
+ * and keys that aren't listed are rejected unless the object has `-additional_keys`.
+ */
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub ty: SchemaType,
+    /**
+     * Only meaningful for the schemas of object keys
+     */
+    pub optional: bool,
+    pub default: Option<FennecType>,
+    /**
+     * The values allowed by `enum`
+     */
+    pub allowed: Option<Vec<FennecType>>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SchemaType {
+    Any,
+    Object {
+        keys: HashMap<String, Schema>,
+        additional_keys: bool,
+    },
+    Array {
+        items: Option<Box<Schema>>,
+        min_length: Option<usize>,
+        max_length: Option<usize>,
+    },
+    String {
+        pattern: Option<Regex>,
+    },
+    Int(Range),
+    Float(Range),
+    /**
+     * Either an int or a float
+     */
+    Number(Range),
+    Bool,
+    Null,
+    /**
+     * With or without an offset
+     */
+    DateTime,
+    Date,
+    Time,
+    Duration,
+    /**
+     * A number with a unit, like `512MiB`
+     */
+    Quantity,
+    Union(Vec<Schema>),
+}
+
+/**
+ * Inclusive bounds of a number
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Range {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/**
+ * (..., path of the schema value that's wrong)
+ */
+#[derive(Debug)]
+pub enum SchemaError {
+    Parse(ParseError),
+    UnknownType(String, Path),
+    UnknownField(String, Path),
+    InvalidField(String, Path),
+    InvalidPattern(regex::Error, Path),
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{e}"),
+            Self::UnknownType(name, path) => write!(f, "unknown type \"{name}\" at {path}"),
+            Self::UnknownField(name, path) => write!(f, "unknown field \"{name}\" at {path}"),
+            Self::InvalidField(name, path) => write!(f, "invalid {name} at {path}"),
+            Self::InvalidPattern(e, path) => write!(f, "invalid pattern at {path}: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+const FIELDS: &[&str] = &[
+    "type",
+    "one_of",
+    "keys",
+    "additional_keys",
+    "items",
+    "min_length",
+    "max_length",
+    "pattern",
+    "min",
+    "max",
+    "enum",
+    "default",
+    "optional",
+    "description",
+];
+
+impl Schema {
+    /**
+     * A schema that accepts anything
+     */
+    pub fn any() -> Self {
+        Self::of(SchemaType::Any)
+    }
+
+    pub fn of(ty: SchemaType) -> Self {
+        Self {
+            ty,
+            optional: false,
+            default: None,
+            allowed: None,
+            description: None,
+        }
+    }
+
+    /**
+     * Comments right above a schema are used as its description, unless it has a `description`
+     */
+    pub fn parse(str: &str) -> Result<Self, SchemaError> {
+        let doc = crate::parse_document(str).map_err(SchemaError::Parse)?;
+        Self::from_fennec_at(&doc.value, &Path::new(), &doc.comments)
+    }
+
+    pub fn from_fennec(value: &FennecType) -> Result<Self, SchemaError> {
+        Self::from_fennec_at(value, &Path::new(), &HashMap::new())
+    }
+
+    fn from_fennec_at(
+        value: &FennecType,
+        path: &Path,
+        comments: &HashMap<Path, String>,
+    ) -> Result<Self, SchemaError> {
+        let Some(obj) = value.as_object() else {
+            return Err(SchemaError::InvalidField(
+                "schema".to_string(),
+                path.clone(),
+            ));
+        };
+
+        for key in obj.keys() {
+            if !FIELDS.contains(&key.as_str()) {
+                return Err(SchemaError::UnknownField(key.to_string(), path.clone()));
+            }
+        }
+
+        let field = |name: &str| obj.get(name);
+        let invalid = |name: &str| SchemaError::InvalidField(name.to_string(), path.join_key(name));
+
+        let range = Range {
+            min: field("min")
+                .map(|v| as_number(v).ok_or_else(|| invalid("min")))
+                .transpose()?,
+            max: field("max")
+                .map(|v| as_number(v).ok_or_else(|| invalid("max")))
+                .transpose()?,
+        };
+
+        let ty = match (field("type"), field("one_of")) {
+            (Some(_), Some(_)) => return Err(invalid("one_of")),
+            (None, Some(one_of)) => {
+                let Some(arr) = one_of.as_array() else {
+                    return Err(invalid("one_of"));
+                };
+                let one_of_path = path.join_key("one_of");
+                let mut out = Vec::with_capacity(arr.len());
+                for (i, val) in arr.iter().enumerate() {
+                    out.push(Self::from_fennec_at(
+                        val,
+                        &one_of_path.join_index(i),
+                        comments,
+                    )?);
+                }
+                SchemaType::Union(out)
+            }
+            (Some(FennecType::Array(names)), None) => {
+                let type_path = path.join_key("type");
+                let mut out = Vec::with_capacity(names.len());
+                for (i, name) in names.iter().enumerate() {
+                    let Some(name) = name.as_string() else {
+                        return Err(SchemaError::InvalidField(
+                            "type".to_string(),
+                            type_path.join_index(i),
+                        ));
+                    };
+                    out.push(Self::of(Self::type_from_name(
+                        &name,
+                        obj,
+                        range,
+                        path,
+                        &type_path.join_index(i),
+                        comments,
+                    )?));
+                }
+                SchemaType::Union(out)
+            }
+            (Some(FennecType::String(name)), None) => {
+                Self::type_from_name(name, obj, range, path, &path.join_key("type"), comments)?
+            }
+            (Some(_), None) => return Err(invalid("type")),
+            (None, None) => {
+                let name = if field("keys").is_some() {
+                    "object"
+                } else if field("items").is_some() {
+                    "array"
+                } else {
+                    "any"
+                };
+                Self::type_from_name(name, obj, range, path, path, comments)?
+            }
+        };
+
+        let optional = match field("optional") {
+            Some(val) => val.as_bool().ok_or_else(|| invalid("optional"))?,
+            None => false,
+        };
+
+        let allowed = match field("enum") {
+            Some(val) => Some(val.as_array().ok_or_else(|| invalid("enum"))?.clone()),
+            None => None,
+        };
+
+        let description = match field("description") {
+            Some(val) => Some(val.as_string().ok_or_else(|| invalid("description"))?),
+            None => comments.get(path).cloned(),
+        };
+
+        Ok(Self {
+            ty,
+            optional,
+            default: field("default").cloned(),
+            allowed,
+            description,
+        })
+    }
+
+    fn type_from_name(
+        name: &str,
+        obj: &HashMap<String, FennecType>,
+        range: Range,
+        path: &Path,
+        type_path: &Path,
+        comments: &HashMap<Path, String>,
+    ) -> Result<SchemaType, SchemaError> {
+        let invalid = |name: &str| SchemaError::InvalidField(name.to_string(), path.join_key(name));
+
+        let ty = match name {
+            "any" => SchemaType::Any,
+            "object" => {
+                let mut keys = HashMap::new();
+                if let Some(val) = obj.get("keys") {
+                    let Some(val) = val.as_object() else {
+                        return Err(invalid("keys"));
+                    };
+                    let keys_path = path.join_key("keys");
+                    for (key, schema) in val {
+                        keys.insert(
+                            key.to_string(),
+                            Self::from_fennec_at(schema, &keys_path.join_key(key), comments)?,
+                        );
+                    }
+                }
+                let additional_keys = match obj.get("additional_keys") {
+                    Some(val) => val.as_bool().ok_or_else(|| invalid("additional_keys"))?,
+                    None => false,
+                };
+                SchemaType::Object {
+                    keys,
+                    additional_keys,
+                }
+            }
+            "array" => {
+                let items = match obj.get("items") {
+                    Some(val) => Some(Box::new(Self::from_fennec_at(
+                        val,
+                        &path.join_key("items"),
+                        comments,
+                    )?)),
+                    None => None,
+                };
+                SchemaType::Array {
+                    items,
+                    min_length: as_length(obj.get("min_length"))
+                        .map_err(|_| invalid("min_length"))?,
+                    max_length: as_length(obj.get("max_length"))
+                        .map_err(|_| invalid("max_length"))?,
+                }
+            }
+            "string" => {
+                let pattern = match obj.get("pattern") {
+                    Some(val) => {
+                        let Some(str) = val.as_string() else {
+                            return Err(invalid("pattern"));
+                        };
+                        let regex = Regex::new(&str).map_err(|e| {
+                            SchemaError::InvalidPattern(e, path.join_key("pattern"))
+                        })?;
+                        Some(regex)
+                    }
+                    None => None,
+                };
+                SchemaType::String { pattern }
+            }
+            "int" => SchemaType::Int(range),
+            "float" => SchemaType::Float(range),
+            "number" => SchemaType::Number(range),
+            "bool" => SchemaType::Bool,
+            "null" => SchemaType::Null,
+            "datetime" => SchemaType::DateTime,
+            "date" => SchemaType::Date,
+            "time" => SchemaType::Time,
+            "duration" => SchemaType::Duration,
+            "quantity" => SchemaType::Quantity,
+            _ => {
+                return Err(SchemaError::UnknownType(
+                    name.to_string(),
+                    type_path.clone(),
+                ));
+            }
+        };
+
+        Ok(ty)
+    }
+
+    /**
+     * The schema written out as Fennec, the inverse of `from_fennec`
+     */
+    pub fn to_fennec(&self) -> FennecType {
+        let mut out = HashMap::new();
+
+        match &self.ty {
+            SchemaType::Union(schemas) if schemas.iter().all(Schema::is_plain) => {
+                let names = schemas
+                    .iter()
+                    .map(|schema| FennecType::String(schema.ty.name()))
+                    .collect();
+                out.insert("type".to_string(), FennecType::Array(names));
+            }
+            SchemaType::Union(schemas) => {
+                let schemas = schemas.iter().map(Schema::to_fennec).collect();
+                out.insert("one_of".to_string(), FennecType::Array(schemas));
+            }
+            ty => {
+                out.insert("type".to_string(), FennecType::String(ty.name()));
+            }
+        }
+
+        match &self.ty {
+            SchemaType::Object {
+                keys,
+                additional_keys,
+            } => {
+                let keys = keys
+                    .iter()
+                    .map(|(key, schema)| (key.to_string(), schema.to_fennec()))
+                    .collect();
+                out.insert("keys".to_string(), FennecType::Object(keys));
+                if *additional_keys {
+                    out.insert("additional_keys".to_string(), FennecType::Bool(true));
+                }
+            }
+            SchemaType::Array {
+                items,
+                min_length,
+                max_length,
+            } => {
+                if let Some(items) = items {
+                    out.insert("items".to_string(), items.to_fennec());
+                }
+                if let Some(min) = min_length {
+                    out.insert("min_length".to_string(), FennecType::Int(*min as i64));
+                }
+                if let Some(max) = max_length {
+                    out.insert("max_length".to_string(), FennecType::Int(*max as i64));
+                }
+            }
+            SchemaType::String {
+                pattern: Some(pattern),
+            } => {
+                out.insert(
+                    "pattern".to_string(),
+                    FennecType::String(pattern.to_string()),
+                );
+            }
+            SchemaType::Int(range) | SchemaType::Float(range) | SchemaType::Number(range) => {
+                if let Some(min) = range.min {
+                    out.insert("min".to_string(), from_number(min));
+                }
+                if let Some(max) = range.max {
+                    out.insert("max".to_string(), from_number(max));
+                }
+            }
+            _ => {}
+        }
+
+        if self.optional {
+            out.insert("optional".to_string(), FennecType::Bool(true));
+        }
+        if let Some(default) = &self.default {
+            out.insert("default".to_string(), default.clone());
+        }
+        if let Some(allowed) = &self.allowed {
+            out.insert("enum".to_string(), FennecType::Array(allowed.clone()));
+        }
+        if let Some(description) = &self.description {
+            out.insert(
+                "description".to_string(),
+                FennecType::String(description.to_string()),
+            );
+        }
+
+        FennecType::Object(out)
+    }
+
+    /**
+     * Whether the schema is nothing but its type, so it can be listed in `type [ ... ]`
+     */
+    fn is_plain(&self) -> bool {
+        let plain_type = match &self.ty {
+            SchemaType::Any
+            | SchemaType::Bool
+            | SchemaType::Null
+            | SchemaType::DateTime
+            | SchemaType::Date
+            | SchemaType::Time
+            | SchemaType::Duration
+            | SchemaType::Quantity => true,
+            SchemaType::String { pattern } => pattern.is_none(),
+            SchemaType::Int(range) | SchemaType::Float(range) | SchemaType::Number(range) => {
+                *range == Range::default()
+            }
+            _ => false,
+        };
+        plain_type
+            && !self.optional
+            && self.default.is_none()
+            && self.allowed.is_none()
+            && self.description.is_none()
+    }
+}
+
+impl Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_fennec().to_string())
+    }
+}
+
+fn from_number(num: f64) -> FennecType {
+    if num.fract() == 0.0 && num.abs() < i64::MAX as f64 {
+        return FennecType::Int(num as i64);
+    }
+    FennecType::Float(num)
+}
+
+fn as_number(value: &FennecType) -> Option<f64> {
+    match value {
+        FennecType::Int(i) => Some(*i as f64),
+        FennecType::BigInt(i) => i.to_f64(),
+        FennecType::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+fn as_length(value: Option<&FennecType>) -> Result<Option<usize>, ()> {
+    match value {
+        Some(FennecType::Int(i)) => usize::try_from(*i).map(Some).map_err(|_| ()),
+        Some(_) => Err(()),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::path::Path;
+
+    use super::{Schema, SchemaError, SchemaType, ViolationKind};
+
+    const SCHEMA: &str = r#"
+        type = "object"
+        keys {
+            host { type = "string" pattern = "^[a-z.]+$" }
+            port { type = "int" min = 1 max = 65535 default = 8080 }
+            mode { type = "string" enum [ "dev" "prod" ] -optional }
+            tags { type = "array" items { type = "string" } max_length = 2 -optional }
+            id { type [ "int" "string" ] -optional }
+        }
+    "#;
+
+    #[test]
+    fn parses_schema() {
+        let schema = Schema::parse(SCHEMA).unwrap();
+        let SchemaType::Object {
+            keys,
+            additional_keys,
+        } = &schema.ty
+        else {
+            panic!("expected an object schema");
+        };
+
+        assert!(!additional_keys);
+        assert_eq!(keys.len(), 5);
+        assert!(keys["port"].default.is_some());
+        assert!(keys["mode"].optional);
+        assert!(matches!(&keys["id"].ty, SchemaType::Union(types) if types.len() == 2));
+    }
+
+    #[test]
+    fn comments_become_descriptions() {
+        let schema =
+            Schema::parse("# The whole thing\n\nkeys { host { type = \"string\" } }").unwrap();
+        assert_eq!(schema.description.as_deref(), Some("The whole thing"));
+
+        // Without the blank line it belongs to the first key
+        let schema = Schema::parse("# Just keys\nkeys { host { type = \"string\" } }").unwrap();
+        assert_eq!(schema.description, None);
+    }
+
+    #[test]
+    fn rejects_bad_schemas() {
+        assert!(matches!(
+            Schema::parse("type = \"integer\""),
+            Err(SchemaError::UnknownType(name, _)) if name == "integer"
+        ));
+        assert!(matches!(
+            Schema::parse("type = \"int\" minimum = 3"),
+            Err(SchemaError::UnknownField(name, _)) if name == "minimum"
+        ));
+        assert!(matches!(
+            Schema::parse("type = \"string\" pattern = \"(\""),
+            Err(SchemaError::InvalidPattern(_, _))
+        ));
+    }
+
+    #[test]
+    fn accepts_valid_document() {
+        let schema = Schema::parse(SCHEMA).unwrap();
+        let doc =
+            crate::parse_document("host = \"example.com\" port = 80 mode = \"dev\" id = \"x\"")
+                .unwrap();
+
+        assert!(schema.validate_document(&doc).is_empty());
+    }
+
+    #[test]
+    fn reports_every_violation() {
+        let schema = Schema::parse(SCHEMA).unwrap();
+        let doc = crate::parse_document(
+            "port = 0\nmode = \"staging\"\ntags [ \"a\" 2 \"c\" ]\nid = 1.5\nextra = null\n",
+        )
+        .unwrap();
+
+        let mut violations = schema.validate_document(&doc);
+        violations.sort_by_key(|v| v.path.to_string());
+        let found: Vec<_> = violations
+            .iter()
+            .map(|v| (v.path.to_string(), &v.kind))
+            .collect();
+
+        assert_eq!(found.len(), 7);
+        assert!(matches!(found[0], (ref p, ViolationKind::UnknownKey) if p == "extra"));
+        assert!(matches!(found[1], (ref p, ViolationKind::MissingKey) if p == "host"));
+        assert!(matches!(found[2], (ref p, ViolationKind::WrongType(_, _)) if p == "id"));
+        assert!(matches!(found[3], (ref p, ViolationKind::NotAllowed) if p == "mode"));
+        assert!(matches!(found[4], (ref p, ViolationKind::OutOfRange(_, _)) if p == "port"));
+        assert!(matches!(found[5], (ref p, ViolationKind::TooLong(2)) if p == "tags"));
+        assert!(matches!(found[6], (ref p, ViolationKind::WrongType(_, _)) if p == "tags[1]"));
+
+        let port = violations
+            .iter()
+            .find(|v| v.path == Path::new().join_key("port"))
+            .unwrap();
+        assert_eq!(port.span.map(|s| (s.0, s.1)), Some((0, 8)));
+        assert_eq!(port.to_string(), "port: 0 is less than 1");
+    }
+}
@@ -0,0 +1,214 @@
+use crate::parse::FennecType;
+
+use super::{Schema, SchemaType};
+
+impl Schema {
+    /**
+     * Inserts every missing object key that declares a `default`, all the way down the tree.
+     * Inserted defaults get their own missing keys filled in as well.
+     */
+    pub fn apply_defaults(&self, value: &mut FennecType) {
+        match (&self.ty, value) {
+            (SchemaType::Object { keys, .. }, FennecType::Object(obj)) => {
+                for (key, schema) in keys {
+                    if !obj.contains_key(key)
+                        && let Some(default) = &schema.default
+                    {
+                        obj.insert(key.to_string(), default.clone());
+                    }
+                    if let Some(val) = obj.get_mut(key) {
+                        schema.apply_defaults(val);
+                    }
+                }
+            }
+            (
+                SchemaType::Array {
+                    items: Some(items), ..
+                },
+                FennecType::Array(arr),
+            ) => {
+                for val in arr {
+                    items.apply_defaults(val);
+                }
+            }
+            (SchemaType::Union(schemas), value) => {
+                if let Some(schema) = schemas.iter().find(|s| s.ty.accepts_type_of(value)) {
+                    schema.apply_defaults(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /**
+     * Converts values to the type the schema asks for, where nothing is lost by doing so:
+     * ints become floats for `float` and `number` when the float holds them exactly.
+     * `0b`/`1b` are already bools, so ints are never turned into them.
+     */
+    pub fn coerce(&self, value: &mut FennecType) {
+        match (&self.ty, &mut *value) {
+            (SchemaType::Float(_) | SchemaType::Number(_), FennecType::Int(i)) => {
+                // Otherwise it's left for `validate` to reject
+                if let Some(f) = exact_float(*i) {
+                    *value = FennecType::Float(f);
+                }
+            }
+            (SchemaType::Object { keys, .. }, FennecType::Object(obj)) => {
+                for (key, schema) in keys {
+                    if let Some(val) = obj.get_mut(key) {
+                        schema.coerce(val);
+                    }
+                }
+            }
+            (
+                SchemaType::Array {
+                    items: Some(items), ..
+                },
+                FennecType::Array(arr),
+            ) => {
+                for val in arr {
+                    items.coerce(val);
+                }
+            }
+            (SchemaType::Union(schemas), _) => {
+                let schema = schemas
+                    .iter()
+                    .find(|s| s.ty.accepts_type_of(value))
+                    .or_else(|| schemas.iter().find(|s| s.ty.coerces_from(value)));
+                if let Some(schema) = schema {
+                    schema.coerce(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /**
+     * Fills in defaults, then coerces, so the result only needs `validate` to be trusted
+     */
+    pub fn normalize(&self, value: &mut FennecType) {
+        self.apply_defaults(value);
+        self.coerce(value);
+    }
+}
+
+impl SchemaType {
+    fn coerces_from(&self, value: &FennecType) -> bool {
+        match (self, value) {
+            (SchemaType::Float(_) | SchemaType::Number(_), FennecType::Int(i)) => {
+                exact_float(*i).is_some()
+            }
+            _ => false,
+        }
+    }
+}
+
+/**
+ * `None` past 2^53, where floats stop holding every int
+ */
+fn exact_float(i: i64) -> Option<f64> {
+    let f = i as f64;
+    // Through i128, since `as i64` would saturate 2^63 back down to `i64::MAX`
+    (f as i128 == i128::from(i)).then_some(f)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{parse::FennecType, schema::Schema};
+
+    const SCHEMA: &str = r#"
+        keys {
+            port { type = "int" default = 8080 }
+            ratio { type = "number" }
+            verbose { type = "bool" default = 0b }
+            limits {
+                keys {
+                    cpu { type = "float" default = 1 }
+                    memory { type = "int" -optional }
+                }
+                default { }
+            }
+            workers {
+                items { keys { threads { type = "int" default = 4 } } }
+            }
+        }
+    "#;
+
+    #[test]
+    fn fills_defaults() {
+        let schema = Schema::parse(SCHEMA).unwrap();
+        let mut fen = crate::parse("ratio = 1 workers [ { } { threads = 2 } ]").unwrap();
+        schema.apply_defaults(&mut fen);
+
+        assert_eq!(fen.get_key("port").unwrap().as_int(), Some(8080));
+        assert!(fen.get_key("limits").unwrap().get_key("cpu").is_some());
+        assert!(fen.get_key("limits").unwrap().get_key("memory").is_none());
+
+        let workers = fen.get_key("workers").unwrap();
+        assert_eq!(
+            workers
+                .get_index(0)
+                .unwrap()
+                .get_key("threads")
+                .unwrap()
+                .as_int(),
+            Some(4)
+        );
+        assert_eq!(
+            workers
+                .get_index(1)
+                .unwrap()
+                .get_key("threads")
+                .unwrap()
+                .as_int(),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn normalizes() {
+        let schema = Schema::parse(SCHEMA).unwrap();
+        let mut fen = crate::parse("ratio = 2 workers [ ]").unwrap();
+        schema.normalize(&mut fen);
+
+        assert_eq!(fen.get_key("ratio"), Some(&FennecType::Float(2.0)));
+        assert_eq!(fen.get_key("verbose"), Some(&FennecType::Bool(false)));
+        assert_eq!(
+            fen.get_key("limits").unwrap().get_key("cpu"),
+            Some(&FennecType::Float(1.0))
+        );
+        assert!(schema.validate(&fen).is_empty());
+    }
+
+    #[test]
+    fn keeps_ints_floats_cant_hold() {
+        let schema = Schema::parse(r#"type = "float""#).unwrap();
+        let mut fen = crate::parse("9007199254740993").unwrap();
+        schema.coerce(&mut fen);
+        assert_eq!(fen, FennecType::Int(9_007_199_254_740_993));
+        assert!(!schema.validate(&fen).is_empty());
+
+        let mut fen = FennecType::Int(i64::MAX);
+        schema.coerce(&mut fen);
+        assert_eq!(fen, FennecType::Int(i64::MAX));
+
+        let mut fen = FennecType::Int(9_007_199_254_740_992);
+        schema.coerce(&mut fen);
+        assert_eq!(fen, FennecType::Float(9_007_199_254_740_992.0));
+    }
+
+    #[test]
+    fn leaves_other_values() {
+        let schema = Schema::parse("type [ \"int\" \"bool\" ]").unwrap();
+        let mut fen = FennecType::Int(1);
+        schema.coerce(&mut fen);
+
+        assert_eq!(fen, FennecType::Int(1));
+
+        let schema = Schema::parse("type [ \"string\" \"bool\" ]").unwrap();
+        let mut fen = FennecType::Int(1);
+        schema.coerce(&mut fen);
+        assert_eq!(fen, FennecType::Int(1));
+        assert!(!schema.validate(&fen).is_empty());
+    }
+}
@@ -0,0 +1,316 @@
+use std::fmt::{self, Display};
+
+use num_traits::ToPrimitive;
+
+use crate::{
+    parse::{Document, FennecType},
+    path::Path,
+    token::Position,
+};
+
+use super::{Range, Schema, SchemaType};
+
+/**
+ * One way a value doesn't match its schema.
+ * `span` is only known when validating a `Document`.
+ */
+#[derive(Debug)]
+pub struct Violation {
+    pub path: Path,
+    pub span: Option<Position>,
+    pub kind: ViolationKind,
+}
+
+#[derive(Debug)]
+pub enum ViolationKind {
+    MissingKey,
+    UnknownKey,
+    /**
+     * (expected, found)
+     */
+    WrongType(String, String),
+    /**
+     * The value isn't in the schema's `enum`
+     */
+    NotAllowed,
+    /**
+     * (value, the bound it broke)
+     */
+    OutOfRange(f64, Range),
+    PatternMismatch(String),
+    TooShort(usize),
+    TooLong(usize),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_root() {
+            f.write_str("<root>: ")?;
+        } else {
+            write!(f, "{}: ", self.path)?;
+        }
+
+        match &self.kind {
+            ViolationKind::MissingKey => f.write_str("missing required key"),
+            ViolationKind::UnknownKey => f.write_str("unknown key"),
+            ViolationKind::WrongType(expected, found) => {
+                write!(f, "expected {expected}, found {found}")
+            }
+            ViolationKind::NotAllowed => f.write_str("value isn't one of the allowed values"),
+            ViolationKind::OutOfRange(num, range) => match range {
+                Range { min: Some(min), .. } => write!(f, "{num} is less than {min}"),
+                Range { max: Some(max), .. } => write!(f, "{num} is more than {max}"),
+                _ => f.write_str("out of range"),
+            },
+            ViolationKind::PatternMismatch(pattern) => write!(f, "doesn't match /{pattern}/"),
+            ViolationKind::TooShort(min) => write!(f, "needs at least {min} elements"),
+            ViolationKind::TooLong(max) => write!(f, "can't have more than {max} elements"),
+        }
+    }
+}
+
+impl Schema {
+    /**
+     * Checks the value against the schema, returning every violation found
+     */
+    pub fn validate(&self, value: &FennecType) -> Vec<Violation> {
+        let mut out = Vec::new();
+        self.check(value, &Path::new(), &mut out);
+        out
+    }
+
+    /**
+     * Like `validate`, but fills in the span of every violation
+     */
+    pub fn validate_document(&self, doc: &Document) -> Vec<Violation> {
+        let mut out = self.validate(&doc.value);
+        for violation in &mut out {
+            violation.span = doc.span_of(&violation.path);
+        }
+        out
+    }
+
+    fn check(&self, value: &FennecType, path: &Path, out: &mut Vec<Violation>) {
+        let violation = |kind| Violation {
+            path: path.clone(),
+            span: None,
+            kind,
+        };
+
+        if let Some(allowed) = &self.allowed
+            && !allowed.contains(value)
+        {
+            out.push(violation(ViolationKind::NotAllowed));
+        }
+
+        match (&self.ty, value) {
+            (SchemaType::Any, _) => {}
+            (SchemaType::Null, FennecType::Null) => {}
+            (SchemaType::Bool, FennecType::Bool(_)) => {}
+            (SchemaType::DateTime, FennecType::DateTime(_)) => {}
+            (SchemaType::Date, FennecType::Date(_)) => {}
+            (SchemaType::Time, FennecType::Time(_)) => {}
+            (SchemaType::Duration, FennecType::Duration(_)) => {}
+            (SchemaType::Quantity, FennecType::Quantity(_)) => {}
+
+            (SchemaType::Int(range), FennecType::Int(i)) => {
+                check_range(*range, *i as f64, path, out)
+            }
+            (SchemaType::Int(range) | SchemaType::Number(range), FennecType::BigInt(i)) => {
+                check_range(*range, i.to_f64().unwrap_or(f64::NAN), path, out)
+            }
+            (SchemaType::Float(range), FennecType::Float(f)) => check_range(*range, *f, path, out),
+            (SchemaType::Number(range), FennecType::Int(i)) => {
+                check_range(*range, *i as f64, path, out)
+            }
+            (SchemaType::Number(range), FennecType::Float(f)) => check_range(*range, *f, path, out),
+
+            (SchemaType::String { pattern }, FennecType::String(str)) => {
+                if let Some(pattern) = pattern
+                    && !pattern.is_match(str)
+                {
+                    out.push(violation(ViolationKind::PatternMismatch(
+                        pattern.to_string(),
+                    )));
+                }
+            }
+
+            (
+                SchemaType::Array {
+                    items,
+                    min_length,
+                    max_length,
+                },
+                FennecType::Array(arr),
+            ) => {
+                if let Some(min) = min_length
+                    && arr.len() < *min
+                {
+                    out.push(violation(ViolationKind::TooShort(*min)));
+                }
+                if let Some(max) = max_length
+                    && arr.len() > *max
+                {
+                    out.push(violation(ViolationKind::TooLong(*max)));
+                }
+                if let Some(items) = items {
+                    for (i, val) in arr.iter().enumerate() {
+                        items.check(val, &path.join_index(i), out);
+                    }
+                }
+            }
+
+            (
+                SchemaType::Object {
+                    keys,
+                    additional_keys,
+                },
+                FennecType::Object(obj),
+            ) => {
+                for (key, schema) in keys {
+                    match obj.get(key) {
+                        Some(val) => schema.check(val, &path.join_key(key), out),
+                        None => {
+                            if !schema.optional && schema.default.is_none() {
+                                out.push(Violation {
+                                    path: path.join_key(key),
+                                    span: None,
+                                    kind: ViolationKind::MissingKey,
+                                });
+                            }
+                        }
+                    }
+                }
+                if !additional_keys {
+                    for key in obj.keys() {
+                        if !keys.contains_key(key) {
+                            out.push(Violation {
+                                path: path.join_key(key),
+                                span: None,
+                                kind: ViolationKind::UnknownKey,
+                            });
+                        }
+                    }
+                }
+            }
+
+            (SchemaType::Union(schemas), _) => {
+                let matching: Vec<_> = schemas
+                    .iter()
+                    .filter(|schema| schema.ty.accepts_type_of(value))
+                    .collect();
+
+                let mut best: Option<Vec<Violation>> = None;
+                for schema in matching {
+                    let mut found = Vec::new();
+                    schema.check(value, path, &mut found);
+                    if found.is_empty() {
+                        return;
+                    }
+                    if best.as_ref().is_none_or(|best| found.len() < best.len()) {
+                        best = Some(found);
+                    }
+                }
+
+                match best {
+                    Some(best) => out.extend(best),
+                    None => out.push(violation(ViolationKind::WrongType(
+                        self.ty.name(),
+                        value.type_name().to_string(),
+                    ))),
+                }
+            }
+
+            (ty, _) => out.push(violation(ViolationKind::WrongType(
+                ty.name(),
+                value.type_name().to_string(),
+            ))),
+        }
+    }
+}
+
+fn check_range(range: Range, num: f64, path: &Path, out: &mut Vec<Violation>) {
+    if let Some(min) = range.min
+        && num < min
+    {
+        out.push(Violation {
+            path: path.clone(),
+            span: None,
+            kind: ViolationKind::OutOfRange(
+                num,
+                Range {
+                    min: Some(min),
+                    max: None,
+                },
+            ),
+        });
+    }
+    if let Some(max) = range.max
+        && num > max
+    {
+        out.push(Violation {
+            path: path.clone(),
+            span: None,
+            kind: ViolationKind::OutOfRange(
+                num,
+                Range {
+                    min: None,
+                    max: Some(max),
+                },
+            ),
+        });
+    }
+}
+
+impl SchemaType {
+    /**
+     * The name used for the type in schema files, e.g. `int` or `int | string`
+     */
+    pub fn name(&self) -> String {
+        match self {
+            SchemaType::Any => "any".to_string(),
+            SchemaType::Object { .. } => "object".to_string(),
+            SchemaType::Array { .. } => "array".to_string(),
+            SchemaType::String { .. } => "string".to_string(),
+            SchemaType::Int(_) => "int".to_string(),
+            SchemaType::Float(_) => "float".to_string(),
+            SchemaType::Number(_) => "number".to_string(),
+            SchemaType::Bool => "bool".to_string(),
+            SchemaType::Null => "null".to_string(),
+            SchemaType::DateTime => "datetime".to_string(),
+            SchemaType::Date => "date".to_string(),
+            SchemaType::Time => "time".to_string(),
+            SchemaType::Duration => "duration".to_string(),
+            SchemaType::Quantity => "quantity".to_string(),
+            SchemaType::Union(schemas) => schemas
+                .iter()
+                .map(|schema| schema.ty.name())
+                .collect::<Vec<_>>()
+                .join(" | "),
+        }
+    }
+
+    pub(super) fn accepts_type_of(&self, value: &FennecType) -> bool {
+        match (self, value) {
+            (SchemaType::Any, _)
+            | (SchemaType::Object { .. }, FennecType::Object(_))
+            | (SchemaType::Array { .. }, FennecType::Array(_))
+            | (SchemaType::String { .. }, FennecType::String(_))
+            | (SchemaType::Int(_), FennecType::Int(_) | FennecType::BigInt(_))
+            | (SchemaType::Float(_), FennecType::Float(_))
+            | (
+                SchemaType::Number(_),
+                FennecType::Int(_) | FennecType::BigInt(_) | FennecType::Float(_),
+            )
+            | (SchemaType::Bool, FennecType::Bool(_))
+            | (SchemaType::Null, FennecType::Null)
+            | (SchemaType::DateTime, FennecType::DateTime(_))
+            | (SchemaType::Date, FennecType::Date(_))
+            | (SchemaType::Time, FennecType::Time(_))
+            | (SchemaType::Duration, FennecType::Duration(_))
+            | (SchemaType::Quantity, FennecType::Quantity(_)) => true,
+            (SchemaType::Union(schemas), _) => schemas.iter().any(|s| s.ty.accepts_type_of(value)),
+            _ => false,
+        }
+    }
+}
@@ -1,14 +1,157 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use num_bigint::BigInt;
 use once_cell::sync::Lazy;
-use regex::Regex;
+use regex::{Captures, Regex};
 
-use crate::lazy;
+use crate::{
+    datetime::{Date, DateTime, Time, DURATION_UNITS},
+    lazy,
+    quantity::{Quantity, Unit},
+};
 
 /**
  * (start, end)
  */
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Position(pub usize, pub usize);
 
+/**
+ * (line, column, byte offset), lines and columns count from 1
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location(pub usize, pub usize, pub usize);
+
+/**
+ * Reads an escape from the chars right after its `\`, returning the char and how many chars it took up.
+ * `None` unless it's one of `\n \t \r \b \f \0 \" \\`, `\u{1F98A}` or `\uXXXX`.
+ */
+pub fn read_escape(chars: &[char]) -> Option<(char, usize)> {
+    let char = match chars.first()? {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        'b' => '\u{8}',
+        'f' => '\u{c}',
+        '0' => '\0',
+        '"' => '"',
+        '\\' => '\\',
+        'u' => return read_unicode_escape(&chars[1..]).map(|(char, len)| (char, len + 1)),
+        _ => return None,
+    };
+    Some((char, 1))
+}
+
+fn read_unicode_escape(chars: &[char]) -> Option<(char, usize)> {
+    if chars.first() == Some(&'{') {
+        let end = chars.iter().take(8).position(|char| *char == '}')?;
+        if !(2..=7).contains(&end) {
+            return None;
+        }
+        let code = hex(&chars[1..end])?;
+        return char::from_u32(code).map(|char| (char, end + 1));
+    }
+
+    let code = hex(chars.get(..4)?)?;
+    if let Some(char) = char::from_u32(code) {
+        return Some((char, 4));
+    }
+    // A UTF-16 surrogate pair like `\uD83E\uDD8A`, the way JSON writes characters past `\uFFFF`
+    if (0xD800..0xDC00).contains(&code) && chars.get(4..6) == Some(&['\\', 'u']) {
+        let low = hex(chars.get(6..10)?)?;
+        if (0xDC00..0xE000).contains(&low) {
+            let code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+            return char::from_u32(code).map(|char| (char, 10));
+        }
+    }
+    None
+}
+
+fn hex(digits: &[char]) -> Option<u32> {
+    if !digits.iter().all(char::is_ascii_hexdigit) {
+        return None;
+    }
+    u32::from_str_radix(&digits.iter().collect::<String>(), 16).ok()
+}
+
+/**
+ * The inverse of `read_escape`, for writing `str` between quotes.
+ * Control characters and ones that don't show up on screen, like bidi overrides, are written as `\u{...}`.
+ */
+pub fn escape(str: &str) -> String {
+    let mut out = String::with_capacity(str.len());
+    for char in str.chars() {
+        match char {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '\0' => out.push_str("\\0"),
+            char if is_invisible(char) => out.push_str(&format!("\\u{{{:X}}}", char as u32)),
+            char => out.push(char),
+        }
+    }
+    out
+}
+
+/**
+ * Strips the indentation all non-blank lines share, so deeper lines keep their extra indent.
+ * Blank lines at the start and end are dropped.
+ */
+pub fn dedent(str: &str) -> String {
+    let indent = |line: &str| line.len() - line.trim_start_matches([' ', '\t']).len();
+    let common = str
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(indent)
+        .min()
+        .unwrap_or(0);
+
+    let lines: Vec<_> = str
+        .lines()
+        .skip_while(|line| line.trim().is_empty())
+        .map(|line| &line[common.min(indent(line))..])
+        .collect();
+    lines.join("\n").trim_end().to_string()
+}
+
+fn is_invisible(char: char) -> bool {
+    char.is_control()
+        || matches!(
+            char,
+            '\u{200B}'..='\u{200F}'
+                | '\u{2028}'..='\u{202E}'
+                | '\u{2060}'..='\u{2069}'
+                | '\u{FEFF}'
+                | '\u{FFF9}'..='\u{FFFB}'
+        )
+}
+
+impl Location {
+    /**
+     * Finds the char at `index` in `str`, or the end of `str` if it's past it
+     */
+    pub fn of(str: &str, index: usize) -> Self {
+        let mut line = 1;
+        let mut column = 1;
+        for (i, (offset, char)) in str.char_indices().enumerate() {
+            if i == index {
+                return Self(line, column, offset);
+            }
+            if char == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        Self(line, column, str.len())
+    }
+}
+
 /**
  * value?, (start, end)
  */
@@ -16,43 +159,146 @@ pub struct Position(pub usize, pub usize);
 pub enum Token {
     Identifier(String, Position),
     Flag(String, Position),
+    Reference(String, Position),
     Symbol(char, Position),
     Type(String, Position),
     Comment(String, Position),
     String(String, Position),
     Float(f64, Position),
     Int(i64, Position),
+    /**
+     * An int too big for an i64
+     */
+    BigInt(BigInt, Position),
+    DateTime(DateTime, Position),
+    Date(Date, Position),
+    Time(Time, Position),
+    Duration(Duration, Position),
+    Quantity(Quantity, Position),
     Bool(bool, Position),
     Null(Position),
+    /**
+     * A literal that's written wrong, e.g. a string that never ends
+     */
+    Invalid(&'static str, Position),
     Err(usize),
     Eof,
 }
 
+impl Token {
+    /**
+     * An `Int` if it fits, a `BigInt` otherwise
+     */
+    fn int(num: BigInt, pos: Position) -> Self {
+        match i64::try_from(&num) {
+            Ok(num) => Token::Int(num, pos),
+            Err(_) => Token::BigInt(num, pos),
+        }
+    }
+
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            Token::Identifier(_, pos)
+            | Token::Flag(_, pos)
+            | Token::Reference(_, pos)
+            | Token::Symbol(_, pos)
+            | Token::Type(_, pos)
+            | Token::Comment(_, pos)
+            | Token::String(_, pos)
+            | Token::Float(_, pos)
+            | Token::Int(_, pos)
+            | Token::BigInt(_, pos)
+            | Token::DateTime(_, pos)
+            | Token::Date(_, pos)
+            | Token::Time(_, pos)
+            | Token::Duration(_, pos)
+            | Token::Quantity(_, pos)
+            | Token::Bool(_, pos)
+            | Token::Null(pos)
+            | Token::Invalid(_, pos) => Some(*pos),
+            Token::Err(idx) => Some(Position(*idx, *idx)),
+            Token::Eof => None,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Tokenizer {
     pub chars: Vec<char>,
     pub index: usize,
+    doc: Arc<str>,
+    /**
+     * The byte offset of each char in `doc`, plus one for the end
+     */
+    offsets: Vec<usize>,
+    /**
+     * Comments written on their own lines, keyed by the start of the token right after them
+     */
+    pub docs: HashMap<usize, String>,
+    /**
+     * Comments at the top of the input with a blank line after them, which describe the whole thing
+     */
+    pub header: Option<String>,
 }
 
-const SYMBOLS: Lazy<Vec<char>> = lazy! { "={}[]".chars().collect() };
-const NULL: Lazy<Regex> = lazy! { Regex::new(r"^(null|nil|void)").unwrap() };
-const NOT_NULL: Lazy<Regex> = lazy! { Regex::new(r"^(null|nil|void)[a-z0-9$_\-]").unwrap() };
-const BOOL: Lazy<Regex> = lazy! { Regex::new(r"^([Tt]rue|[Ff]alse|[01]b)").unwrap() };
-const NOT_BOOL: Lazy<Regex> =
+static SYMBOLS: Lazy<Vec<char>> = lazy! { "={}[]".chars().collect() };
+static NULL: Lazy<Regex> = lazy! { Regex::new(r"^(null|nil|void)").unwrap() };
+static NOT_NULL: Lazy<Regex> = lazy! { Regex::new(r"^(null|nil|void)[a-z0-9$_\-]").unwrap() };
+static BOOL: Lazy<Regex> = lazy! { Regex::new(r"^([Tt]rue|[Ff]alse|[01]b)").unwrap() };
+static NOT_BOOL: Lazy<Regex> =
     lazy! { Regex::new(r"^([Tt]rue|[Ff]alse|[01]b)[a-z0-9$_\-]").unwrap() };
-const HEX_LITERAL: Lazy<Regex> = lazy! { Regex::new(r"^([01])x([0-9a-fA-F]+)").unwrap() };
-const OCT_LITERAL: Lazy<Regex> = lazy! { Regex::new(r"^([01])o([0-7]+)").unwrap() };
-const BIN_LITERAL: Lazy<Regex> = lazy! { Regex::new(r"^([01])b([01]+)").unwrap() };
-const FLOATING: Lazy<Regex> = lazy! { Regex::new(r"^\-?[0-9]+\.[0-9]+").unwrap() };
-const INTEGER: Lazy<Regex> = lazy! { Regex::new(r"^\-?[0-9]+").unwrap() };
-const IDENTIFIER: Lazy<Regex> = lazy! { Regex::new(r"^([a-zA-Z$_][a-zA-Z$_\-0-9]*)").unwrap() };
-const FLAG: Lazy<Regex> = lazy! { Regex::new(r"^\-([a-zA-Z$_\-0-9]+)").unwrap() };
+// Digits can be split up with single `_`s between them, e.g. `1_000_000` or `0xFF_FF`
+static HEX_LITERAL: Lazy<Regex> =
+    lazy! { Regex::new(r"^([01])x([0-9a-fA-F](?:_?[0-9a-fA-F])*)").unwrap() };
+static OCT_LITERAL: Lazy<Regex> = lazy! { Regex::new(r"^([01])o([0-7](?:_?[0-7])*)").unwrap() };
+static BIN_LITERAL: Lazy<Regex> = lazy! { Regex::new(r"^([01])b([01](?:_?[01])*)").unwrap() };
+/**
+ * `1.5`, `.5`, `1.5e-3` or `1e6`, so either a `.` or an exponent
+ */
+static FLOATING: Lazy<Regex> = lazy! {
+    Regex::new(r"^[+\-]?(?:(?:[0-9](?:_?[0-9])*)?\.[0-9](?:_?[0-9])*(?:[eE][+\-]?[0-9](?:_?[0-9])*)?|[0-9](?:_?[0-9])*[eE][+\-]?[0-9](?:_?[0-9])*)").unwrap()
+};
+static SPECIAL_FLOAT: Lazy<Regex> = lazy! { Regex::new(r"^[+\-]?(inf|nan)").unwrap() };
+static NOT_SPECIAL_FLOAT: Lazy<Regex> =
+    lazy! { Regex::new(r"^[+\-]?(inf|nan)[a-zA-Z$_\-0-9]").unwrap() };
+/**
+ * `1979-05-27`, with a `T`, time and optional offset after it for a date-time
+ */
+static DATE_TIME: Lazy<Regex> = lazy! {
+    Regex::new(r"^([0-9]{4})-([0-9]{2})-([0-9]{2})(?:[Tt]([0-9]{2}):([0-9]{2}):([0-9]{2})(?:\.([0-9]+))?([Zz]|[+\-][0-9]{2}:[0-9]{2})?)?").unwrap()
+};
+static TIME: Lazy<Regex> =
+    lazy! { Regex::new(r"^([0-9]{2}):([0-9]{2}):([0-9]{2})(?:\.([0-9]+))?").unwrap() };
+/**
+ * `1h30m`, `500ms`, see `DURATION_UNITS`
+ */
+static DURATION: Lazy<Regex> =
+    lazy! { Regex::new(r"^(?:[0-9](?:_?[0-9])*(?:d|h|ms|m|s|us|ns))+").unwrap() };
+static DURATION_PART: Lazy<Regex> = lazy! { Regex::new(r"([0-9_]+)(d|h|ms|m|s|us|ns)").unwrap() };
+/**
+ * `512MiB`, `1.5Gbps`, see `quantity::UNITS`
+ */
+static QUANTITY: Lazy<Regex> = lazy! {
+    Regex::new(r"^([+\-]?[0-9](?:_?[0-9])*(?:\.[0-9](?:_?[0-9])*)?)([a-zA-Z]+(?:/[a-zA-Z]+)?)").unwrap()
+};
+static INTEGER: Lazy<Regex> = lazy! { Regex::new(r"^[+\-]?[0-9](?:_?[0-9])*").unwrap() };
+static IDENTIFIER: Lazy<Regex> = lazy! { Regex::new(r"^([a-zA-Z$_][a-zA-Z$_\-0-9]*)").unwrap() };
+static FLAG: Lazy<Regex> = lazy! { Regex::new(r"^\-([a-zA-Z$_\-0-9]+)").unwrap() };
+static REFERENCE: Lazy<Regex> = lazy! {
+    Regex::new(r#"^&((?:[a-zA-Z$_][a-zA-Z$_\-0-9]*|"(?:[^"\\]|\\.)*")(?:\.(?:[a-zA-Z$_][a-zA-Z$_\-0-9]*|"(?:[^"\\]|\\.)*")|\[[0-9]+\])*)"#).unwrap()
+};
 
 impl Tokenizer {
     pub fn new(doc: &str) -> Self {
+        let mut offsets: Vec<_> = doc.char_indices().map(|(offset, _)| offset).collect();
+        offsets.push(doc.len());
         Self {
             chars: doc.chars().collect(),
             index: 0,
+            doc: doc.into(),
+            offsets,
+            docs: HashMap::new(),
+            header: None,
         }
     }
 
@@ -65,75 +311,136 @@ impl Tokenizer {
     }
 
     pub fn seek_to(&mut self, chars: Vec<char>) -> String {
-        self.seek_to_esc(chars, false)
+        let start = self.index;
+        while !self.is_end() && !chars.contains(&self.get_char()) {
+            self.index += 1;
+        }
+        self.chars[start..self.index].iter().collect()
     }
 
-    pub fn seek_to_esc(&mut self, chars: Vec<char>, escape: bool) -> String {
+    /**
+     * Like `seek_to`, but reads escapes along the way. Stops at the end of the input after a lone `\`.
+     */
+    pub fn seek_to_esc(&mut self, chars: Vec<char>) -> Result<String, Token> {
         let mut out = "".to_string();
         while !self.is_end() && !chars.contains(&self.get_char()) {
             let char = self.get_char();
-            if escape && char == '\\' {
-                self.index += 1;
-                out.push(Tokenizer::get_escape_char(self.get_char()))
+            self.index += 1;
+            if char == '\\' {
+                if self.is_end() {
+                    break;
+                }
+                out.push(self.escape()?);
             } else {
                 out.push(char);
             }
-            self.index += 1;
         }
-        out
+        Ok(out)
     }
 
-    pub fn get_escape_char(escape: char) -> char {
-        match escape {
-            'n' => return '\n',
-            't' => return '\t',
-            'b' => return 8 as char,
-            'f' => return 12 as char,
-            'r' => return '\r',
-            _ => return escape,
-        }
+    /**
+     * Reads the escape after a `\`, with `self.index` just past the `\`
+     */
+    fn escape(&mut self) -> Result<char, Token> {
+        let Some((char, len)) = read_escape(&self.chars[self.index..]) else {
+            let start = self.index - 1;
+            return Err(Token::Invalid("invalid escape", Position(start, start + 2)));
+        };
+        self.index += len;
+        Ok(char)
     }
 
-    pub fn to_triple_quote(&mut self) -> String {
+    /**
+     * Reads up to and past the closing `"""` or `'''` of a string that started at `start`.
+     * Escapes are only read between `"""`, `'''` strings are raw.
+     */
+    pub fn to_triple_quote(&mut self, start: usize, quote: char) -> Result<String, Token> {
         let mut out = "".to_string();
+        let end = quote.to_string().repeat(3);
 
-        while !self.is_end() {
-            if self.to_string().starts_with("\"\"\"") {
+        loop {
+            if self.is_end() {
+                return Err(self.unterminated(start));
+            }
+            if self.rest().starts_with(&end) {
                 break;
             }
             let char = self.get_char();
-            if char == '\\' {
-                self.index += 1;
-                let escape = Tokenizer::get_escape_char(self.get_char());
-                out.push(escape);
+            self.index += 1;
+            if quote == '"' && char == '\\' {
+                if self.is_end() {
+                    return Err(self.unterminated(start));
+                }
+                out.push(self.escape()?);
             } else {
                 out.push(char);
             }
-            self.index += 1;
         }
 
         self.index += 3;
 
-        out
+        Ok(out)
     }
 
     pub fn to_string(&self) -> String {
-        self.chars.split_at(self.index).1.iter().collect()
+        self.rest().to_string()
+    }
+
+    /**
+     * What's left of the input, without copying it
+     */
+    fn rest(&self) -> &str {
+        &self.doc[self.offsets[self.index.min(self.chars.len())]..]
     }
 
     pub fn next(&mut self) -> Token {
+        let mut doc = Vec::new();
+        let mut doc_start = 0;
+        let mut doc_end = 0;
         loop {
             let token = self.next_token();
-            if let Token::Comment(_, _) = token {
+            if let Token::Comment(comment, pos) = &token {
+                if self.starts_line(pos.0) {
+                    if doc.is_empty() {
+                        doc_start = pos.0;
+                    }
+                    doc.push(comment.to_string());
+                    doc_end = pos.1;
+                } else {
+                    doc.clear();
+                }
                 continue;
             }
             if let Token::Type(_, _) = token {
                 continue;
             }
+            if !doc.is_empty() && let Some(pos) = token.position() {
+                if self.is_header(doc_start, doc_end, pos.0) {
+                    self.header = Some(doc.join("\n"));
+                } else {
+                    self.docs.insert(pos.0, doc.join("\n"));
+                }
+            }
             return token;
         }
     }
 
+    /**
+     * Whether the comments from `start` to `end` open the input and have a blank line after them
+     */
+    fn is_header(&self, start: usize, end: usize, next: usize) -> bool {
+        self.chars[..start].iter().all(|c| c.is_whitespace())
+            && self.chars[end..next].iter().filter(|c| **c == '\n').count() > 1
+    }
+
+    fn starts_line(&self, index: usize) -> bool {
+        self.chars[..index]
+            .iter()
+            .rev()
+            .take_while(|c| **c != '\n')
+            .all(|c| c.is_whitespace())
+    }
+
     pub fn next_token(&mut self) -> Token {
         if self.is_end() {
             return Token::Eof;
@@ -174,31 +481,48 @@ impl Tokenizer {
             );
         }
 
-        let str = self.to_string();
+        // Shares the input rather than copying it, so `str` can outlive changes to `self.index`
+        let doc = Arc::clone(&self.doc);
+        let str = &doc[self.offsets[self.index]..];
 
-        if str.starts_with("-\"\"\"") {
+        if str.starts_with("-\"\"\"") || str.starts_with("-\'\'\'") {
+            let quote = self.chars[self.index + 1];
             self.index += 4;
-            let quote = self.to_triple_quote();
-            let lines = quote.lines();
-            let mut str = "".to_string();
-            for line in lines {
-                str.push_str(line.trim_start());
-                str.push('\n');
-            }
-            return Token::String(str.trim().to_string(), Position(start_idx, self.index));
+            let quote = match self.to_triple_quote(start_idx, quote) {
+                Ok(quote) => quote,
+                Err(token) => return token,
+            };
+            return Token::String(dedent(&quote), Position(start_idx, self.index));
         }
 
-        if str.starts_with("\"\"\"") {
+        if str.starts_with("\"\"\"") || str.starts_with("\'\'\'") {
             self.index += 3;
-            return Token::String(
-                self.to_triple_quote().trim().to_string(),
-                Position(start_idx, self.index),
-            );
+            let quote = match self.to_triple_quote(start_idx, char) {
+                Ok(quote) => quote,
+                Err(token) => return token,
+            };
+            return Token::String(quote.trim().to_string(), Position(start_idx, self.index));
+        }
+
+        if char == '\'' {
+            self.index += 1;
+            let str = self.seek_to(vec!['\'']);
+            if self.is_end() {
+                return self.unterminated(start_idx);
+            }
+            self.index += 1;
+            return Token::String(str, Position(start_idx, self.index));
         }
 
         if char == '"' {
             self.index += 1;
-            let str = self.seek_to(vec!['"']);
+            let str = match self.seek_to_esc(vec!['"']) {
+                Ok(str) => str,
+                Err(token) => return token,
+            };
+            if self.is_end() {
+                return self.unterminated(start_idx);
+            }
             self.index += 1;
             return Token::String(str, Position(start_idx, self.index));
         }
@@ -221,46 +545,96 @@ impl Tokenizer {
             return Token::Bool(str.to_lowercase() == "true" || str == "1b", Position(start_idx, self.index));
         }
 
+        if let Some(capture) = DATE_TIME.captures(&str) {
+            self.index += capture[0].len();
+            return self.date_time(&capture, start_idx);
+        }
+
+        if let Some(capture) = TIME.captures(&str) {
+            self.index += capture[0].len();
+            let pos = Position(start_idx, self.index);
+            return match time(&capture, 1) {
+                Some(time) => Token::Time(time, pos),
+                None => Token::Invalid("invalid date or time", pos),
+            };
+        }
+
+        // `1m` followed by more letters isn't a duration, so it can't be read as one
+        if let Some(capture) = DURATION.captures(&str)
+            && !str[capture[0].len()..].starts_with(|char: char| {
+                char.is_ascii_alphanumeric() || "$_-".contains(char)
+            })
+        {
+            self.index += capture[0].len();
+            return self.duration(&capture[0], start_idx);
+        }
+
         if let Some(capture) = HEX_LITERAL.captures(&str) {
-            let str = &capture[0];
-            self.index += str.len();
-            let sign = if &capture[1] == "1" { -1 } else { 1 };
-            return Token::Int(
-                sign * i64::from_str_radix(&capture[2], 16).unwrap(),
-                Position(start_idx, self.index),
-            );
+            self.index += capture[0].len();
+            return self.radix_int(&capture[1], &capture[2], 16, start_idx);
         }
 
         if let Some(capture) = OCT_LITERAL.captures(&str) {
-            let str = &capture[0];
-            self.index += str.len();
-            let sign = if &capture[1] == "1" { -1 } else { 1 };
-            return Token::Int(
-                sign * i64::from_str_radix(&capture[2], 8).unwrap(),
-                Position(start_idx, self.index),
-            );
+            self.index += capture[0].len();
+            return self.radix_int(&capture[1], &capture[2], 8, start_idx);
         }
 
         if let Some(capture) = BIN_LITERAL.captures(&str) {
+            self.index += capture[0].len();
+            return self.radix_int(&capture[1], &capture[2], 2, start_idx);
+        }
+
+        // Followed by a `+` it's an exponent, e.g. `1e+5`
+        if let Some(capture) = QUANTITY.captures(&str)
+            && !str[capture[0].len()..].starts_with(|char: char| {
+                char.is_ascii_alphanumeric() || "$_-+".contains(char)
+            })
+        {
+            self.index += capture[0].len();
+            return self.quantity(&capture[1], &capture[2], start_idx);
+        }
+
+        if let Some(capture) = FLOATING.captures(&str) {
             let str = &capture[0];
             self.index += str.len();
-            let sign = if &capture[1] == "1" { -1 } else { 1 };
-            return Token::Int(
-                sign * i64::from_str_radix(&capture[2], 2).unwrap(),
-                Position(start_idx, self.index),
-            );
+            let pos = Position(start_idx, self.index);
+            // Too many digits parse to infinity rather than failing, `inf` has to be written out
+            return match str.replace('_', "").parse::<f64>() {
+                Ok(num) if num.is_finite() => Token::Float(num, pos),
+                _ => Token::Invalid("float out of range", pos),
+            };
         }
 
-        if let Some(capture) = FLOATING.captures(&str) {
+        let special_match = SPECIAL_FLOAT.captures(&str);
+        let not_special_match = NOT_SPECIAL_FLOAT.captures(&str);
+
+        if not_special_match.is_none() && let Some(capture) = special_match {
             let str = &capture[0];
             self.index += str.len();
-            return Token::Float(str.parse::<f64>().unwrap(), Position(start_idx, self.index));
+            let num = match &capture[1] {
+                "inf" if str.starts_with('-') => f64::NEG_INFINITY,
+                "inf" => f64::INFINITY,
+                _ => f64::NAN,
+            };
+            return Token::Float(num, Position(start_idx, self.index));
         }
 
         if let Some(capture) = INTEGER.captures(&str) {
             let str = &capture[0];
             self.index += str.len();
-            return Token::Int(str.parse::<i64>().unwrap(), Position(start_idx, self.index));
+            let pos = Position(start_idx, self.index);
+            let digits = str.replace('_', "");
+            if let Ok(num) = digits.parse::<i64>() {
+                return Token::Int(num, pos);
+            }
+            let digits = digits.trim_start_matches('+');
+            return Token::int(digits.parse().expect("INTEGER only matches digits"), pos);
+        }
+
+        if let Some(capture) = REFERENCE.captures(&str) {
+            let str = &capture[1];
+            self.index += str.chars().count() + 1;
+            return Token::Reference(str.to_string(), Position(start_idx, self.index));
         }
 
         if let Some(capture) = FLAG.captures(&str) {
@@ -277,4 +651,117 @@ impl Tokenizer {
 
         Token::Err(start_idx)
     }
+
+    fn unterminated(&mut self, start: usize) -> Token {
+        self.index = self.chars.len();
+        Token::Invalid("unterminated string", Position(start, self.index))
+    }
+
+    fn date_time(&self, capture: &Captures, start: usize) -> Token {
+        let pos = Position(start, self.index);
+        let num = |group| num_of(&capture[group]);
+        let Some(date) = Date::new(num(1) as u16, num(2) as u8, num(3) as u8) else {
+            return Token::Invalid("invalid date or time", pos);
+        };
+        if capture.get(4).is_none() {
+            return Token::Date(date, pos);
+        }
+        // `Some(None)` for a local date-time, `None` for an offset that's out of range
+        let offset = match capture.get(8).map(|offset| offset.as_str()) {
+            None => Some(None),
+            Some("Z" | "z") => Some(Some(0)),
+            Some(offset) => {
+                let (hours, minutes) = (num_of(&offset[1..3]), num_of(&offset[4..]));
+                let sign = if offset.starts_with('-') { -1 } else { 1 };
+                (hours < 24 && minutes < 60).then_some(Some(sign * (hours * 60 + minutes) as i16))
+            }
+        };
+        match (time(capture, 4), offset) {
+            (Some(time), Some(offset)) => Token::DateTime(DateTime { date, time, offset }, pos),
+            _ => Token::Invalid("invalid date or time", pos),
+        }
+    }
+
+    /**
+     * Looks `suffix` up in `UNITS`, rejecting units that aren't there and negative numbers
+     */
+    fn quantity(&self, number: &str, suffix: &str, start: usize) -> Token {
+        let pos = Position(start, self.index);
+        let Some(unit) = Unit::find(suffix) else {
+            return Token::Invalid("unknown unit", pos);
+        };
+        if number.starts_with('-') {
+            return Token::Invalid("quantities can't be negative", pos);
+        }
+        match Quantity::from_number(number.trim_start_matches('+'), unit) {
+            Some(quantity) => Token::Quantity(quantity, pos),
+            None => Token::Invalid("quantity out of range", pos),
+        }
+    }
+
+    /**
+     * Adds up each part of a duration, which have to go from the biggest unit to the smallest
+     */
+    fn duration(&self, str: &str, start: usize) -> Token {
+        let pos = Position(start, self.index);
+        let mut nanos: u128 = 0;
+        let mut last = None;
+        for part in DURATION_PART.captures_iter(str) {
+            let unit = DURATION_UNITS
+                .iter()
+                .position(|(unit, _)| *unit == &part[2])
+                .expect("The regex only matches units");
+            if last.is_some_and(|last| unit <= last) {
+                return Token::Invalid("duration units have to go from biggest to smallest", pos);
+            }
+            last = Some(unit);
+            let total = part[1]
+                .replace('_', "")
+                .parse::<u128>()
+                .ok()
+                .and_then(|num| num.checked_mul(DURATION_UNITS[unit].1))
+                .and_then(|num| num.checked_add(nanos));
+            let Some(total) = total else {
+                return Token::Invalid("duration out of range", pos);
+            };
+            nanos = total;
+        }
+        match u64::try_from(nanos / 1_000_000_000) {
+            Ok(secs) => Token::Duration(Duration::new(secs, (nanos % 1_000_000_000) as u32), pos),
+            Err(_) => Token::Invalid("duration out of range", pos),
+        }
+    }
+
+    /**
+     * A `0x`/`0o`/`0b` literal, or a negative one if it starts with a `1`
+     */
+    fn radix_int(&self, sign: &str, digits: &str, radix: u32, start: usize) -> Token {
+        let pos = Position(start, self.index);
+        let num = BigInt::parse_bytes(digits.replace('_', "").as_bytes(), radix)
+            .expect("The regex only matches digits");
+        if sign == "1" {
+            return Token::int(-num, pos);
+        }
+        Token::int(num, pos)
+    }
+}
+
+fn num_of(digits: &str) -> u32 {
+    digits.parse().expect("The regex only matches digits")
+}
+
+/**
+ * The time in `capture`, with the hours in `group` and the minutes, seconds and fraction after it.
+ * Digits past nanoseconds are dropped.
+ */
+fn time(capture: &Captures, group: usize) -> Option<Time> {
+    let num = |group| num_of(&capture[group]) as u8;
+    let nanosecond = match capture.get(group + 3) {
+        Some(fraction) => {
+            let digits = &fraction.as_str()[..fraction.len().min(9)];
+            num_of(&format!("{digits:0<9}"))
+        }
+        None => 0,
+    };
+    Time::new(num(group), num(group + 1), num(group + 2), nanosecond)
 }
@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+use js_sys::{Array, BigInt, Object, Reflect};
+use num_traits::{FromPrimitive, ToPrimitive};
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsError, JsValue};
+
+use crate::{
+    datetime::{self, Date, DateTime, Time},
+    parse::{FennecType, ParseError, StringifyOptions},
+    quantity::Quantity,
+    schema::Schema,
+    token::Location,
+};
+
+/**
+ * How ints and floats are told apart on the JS side, from the `numbers` option
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Numbers {
+    /**
+     * Both are plain numbers. Going back the other way, whole numbers turn into ints.
+     */
+    Plain,
+    /**
+     * Ints are `BigInt`s
+     */
+    BigInt,
+    /**
+     * Ints are `{ type: "int", value: "1" }`, with the value as a string so it stays exact,
+     * and floats are `{ type: "float", value: 1 }`.
+     * Going back the other way, this is the only mode that reads any tagged value back.
+     */
+    Tagged,
+}
+
+impl Numbers {
+    fn from_options(options: &JsValue) -> Result<Self, JsError> {
+        let numbers = option(options, "numbers");
+        if numbers.is_undefined() {
+            return Ok(Self::Plain);
+        }
+        match numbers.as_string().as_deref() {
+            Some("number") => Ok(Self::Plain),
+            Some("bigint") => Ok(Self::BigInt),
+            Some("tagged") => Ok(Self::Tagged),
+            _ => Err(JsError::new(
+                "numbers must be \"number\", \"bigint\" or \"tagged\"",
+            )),
+        }
+    }
+}
+
+fn option(options: &JsValue, name: &str) -> JsValue {
+    if !options.is_object() {
+        return JsValue::UNDEFINED;
+    }
+    Reflect::get(options, &name.into()).unwrap_or(JsValue::UNDEFINED)
+}
+
+fn parse_error(e: &ParseError, str: &str) -> JsError {
+    match e.position() {
+        Some(pos) => {
+            let Location(line, column, _) = Location::of(str, pos.0);
+            JsError::new(&format!("{e} at line {line}, column {column}"))
+        }
+        None => JsError::new(&e.to_string()),
+    }
+}
+
+fn tagged(ty: &str, value: JsValue) -> JsValue {
+    let obj = Object::new();
+    let _ = Reflect::set(&obj, &"type".into(), &ty.into());
+    let _ = Reflect::set(&obj, &"value".into(), &value);
+    obj.into()
+}
+
+fn to_js(value: &FennecType, numbers: Numbers) -> JsValue {
+    match value {
+        FennecType::Object(obj) => {
+            let out = Object::new();
+            for (key, value) in obj {
+                let _ = Reflect::set(&out, &key.into(), &to_js(value, numbers));
+            }
+            out.into()
+        }
+        FennecType::Array(arr) => arr
+            .iter()
+            .map(|value| to_js(value, numbers))
+            .collect::<Array>()
+            .into(),
+        FennecType::String(str) => str.into(),
+        FennecType::Float(f) => match numbers {
+            Numbers::Tagged => tagged("float", (*f).into()),
+            _ => (*f).into(),
+        },
+        FennecType::Int(i) => match numbers {
+            Numbers::Plain => (*i as f64).into(),
+            Numbers::BigInt => BigInt::from(*i).into(),
+            Numbers::Tagged => tagged("int", i.to_string().into()),
+        },
+        FennecType::BigInt(i) => match numbers {
+            Numbers::Plain => i.to_f64().unwrap_or(f64::NAN).into(),
+            Numbers::BigInt => BigInt::new(&i.to_string().into())
+                .map_or(JsValue::UNDEFINED, JsValue::from),
+            Numbers::Tagged => tagged("int", i.to_string().into()),
+        },
+        FennecType::Bool(b) => (*b).into(),
+        FennecType::Null => JsValue::NULL,
+        // JS has nothing that holds these exactly, so they're always tagged
+        FennecType::DateTime(date_time) => tagged("datetime", date_time.to_string().into()),
+        FennecType::Date(date) => tagged("date", date.to_string().into()),
+        FennecType::Time(time) => tagged("time", time.to_string().into()),
+        FennecType::Duration(duration) => {
+            tagged("duration", datetime::format_duration(duration).into())
+        }
+        FennecType::Quantity(quantity) => tagged("quantity", quantity.to_string().into()),
+        // Parsing resolves every reference, so there are none left by the time a value gets here
+        FennecType::Reference(path, _) => format!("&{path}").into(),
+    }
+}
+
+/**
+ * The inverse of `tagged`, `None` if `value` isn't a tagged value.
+ * Only `Tagged` reads tags back, otherwise `{ type, value }` is just an object with those keys.
+ */
+fn from_tagged(value: &JsValue, numbers: Numbers) -> Option<Result<FennecType, JsError>> {
+    if numbers != Numbers::Tagged {
+        return None;
+    }
+    let keys = Object::keys(value.dyn_ref::<Object>()?);
+    if keys.length() != 2 {
+        return None;
+    }
+    let inner = option(value, "value");
+    let ty = option(value, "type").as_string()?;
+    match ty.as_str() {
+        "int" if inner.is_string() || inner.is_bigint() || inner.as_f64().is_some() => {
+            Some(int_from_js(&inner))
+        }
+        "float" => inner.as_f64().map(|f| Ok(FennecType::Float(f))),
+        "datetime" | "date" | "time" | "duration" | "quantity" => {
+            let str = inner.as_string()?;
+            let value = match ty.as_str() {
+                "datetime" => str.parse::<DateTime>().ok().map(FennecType::DateTime),
+                "date" => str.parse::<Date>().ok().map(FennecType::Date),
+                "time" => str.parse::<Time>().ok().map(FennecType::Time),
+                "quantity" => str.parse::<Quantity>().ok().map(FennecType::Quantity),
+                _ => datetime::parse_duration(&str).map(FennecType::Duration),
+            };
+            Some(value.ok_or_else(|| JsError::new(&format!("{str} isn't a valid {ty}"))))
+        }
+        _ => None,
+    }
+}
+
+fn int_from_js(value: &JsValue) -> Result<FennecType, JsError> {
+    if let Some(str) = value.as_string() {
+        return str
+            .parse::<num_bigint::BigInt>()
+            .map(FennecType::from)
+            .map_err(|_| JsError::new(&format!("{str} isn't an int")));
+    }
+    if let Some(f) = value.as_f64() {
+        return match num_bigint::BigInt::from_f64(f) {
+            Some(i) if f.fract() == 0.0 => Ok(i.into()),
+            _ => Err(JsError::new(&format!("{f} isn't an int"))),
+        };
+    }
+    // A JS BigInt, which can be any size
+    let Some(str) = value.dyn_ref::<BigInt>().and_then(|i| i.to_string(10).ok()) else {
+        return Err(JsError::new("BigInt can't be read"));
+    };
+    str.as_string()
+        .and_then(|str| str.parse::<num_bigint::BigInt>().ok())
+        .map(FennecType::from)
+        .ok_or_else(|| JsError::new("BigInt can't be read"))
+}
+
+fn from_js(value: &JsValue, numbers: Numbers) -> Result<FennecType, JsError> {
+    if value.is_null() || value.is_undefined() {
+        return Ok(FennecType::Null);
+    }
+    if let Some(b) = value.as_bool() {
+        return Ok(FennecType::Bool(b));
+    }
+    if let Some(str) = value.as_string() {
+        return Ok(FennecType::String(str));
+    }
+    if value.is_bigint() {
+        return int_from_js(value);
+    }
+    if let Some(f) = value.as_f64() {
+        // Whole numbers only become ints when nothing else can say which they were
+        if numbers == Numbers::Plain && f.fract() == 0.0 && f.abs() < i64::MAX as f64 {
+            return Ok(FennecType::Int(f as i64));
+        }
+        return Ok(FennecType::Float(f));
+    }
+    if Array::is_array(value) {
+        return Array::from(value)
+            .iter()
+            .map(|value| from_js(&value, numbers))
+            .collect::<Result<_, _>>()
+            .map(FennecType::Array);
+    }
+    if let Some(value) = from_tagged(value, numbers) {
+        return value;
+    }
+    if let Some(obj) = value.dyn_ref::<Object>() {
+        let mut out = HashMap::new();
+        for entry in Object::entries(obj).iter() {
+            let entry = Array::from(&entry);
+            let key = entry.get(0).as_string().unwrap_or_default();
+            out.insert(key, from_js(&entry.get(1), numbers)?);
+        }
+        return Ok(FennecType::Object(out));
+    }
+    Err(JsError::new(&format!(
+        "{} can't be written as Fennec",
+        value.js_typeof().as_string().unwrap_or_default()
+    )))
+}
+
+/**
+ * Parses a document into plain JS values.
+ * `options.numbers` is `"number"` (the default), `"bigint"` or `"tagged"`, see `Numbers`.
+ * Dates, times, durations and quantities are always tagged,
+ * e.g. `{ type: "duration", value: "1m30s" }`, which `stringify` only reads back with `"tagged"`.
+ */
+#[wasm_bindgen]
+pub fn parse(str: &str, options: JsValue) -> Result<JsValue, JsError> {
+    let numbers = Numbers::from_options(&options)?;
+    match crate::parse(str) {
+        Ok(value) => Ok(to_js(&value, numbers)),
+        Err(e) => Err(parse_error(&e, str)),
+    }
+}
+
+/**
+ * Writes a JS value back out as a document.
+ * Takes `indent`, `sortKeys` and `dottedKeys` along with the same `numbers` as `parse`.
+ */
+#[wasm_bindgen]
+pub fn stringify(value: JsValue, options: JsValue) -> Result<String, JsError> {
+    let numbers = Numbers::from_options(&options)?;
+    let mut stringify_options = StringifyOptions::default();
+    if let Some(indent) = option(&options, "indent").as_f64() {
+        stringify_options.indent = indent as usize;
+    }
+    if let Some(sort_keys) = option(&options, "sortKeys").as_bool() {
+        stringify_options.sort_keys = sort_keys;
+    }
+    if let Some(dotted_keys) = option(&options, "dottedKeys").as_bool() {
+        stringify_options.dotted_keys = dotted_keys;
+    }
+    Ok(from_js(&value, numbers)?.stringify(&stringify_options))
+}
+
+/**
+ * Checks a document against a schema, both given as source text.
+ * Returns a list of `{ path, message, line, column }`, empty when the document matches.
+ */
+#[wasm_bindgen]
+pub fn validate(str: &str, schema: &str) -> Result<Array, JsError> {
+    let schema = Schema::parse(schema).map_err(|e| JsError::new(&e.to_string()))?;
+    let doc = crate::parse_document(str).map_err(|e| parse_error(&e, str))?;
+
+    Ok(schema
+        .validate_document(&doc)
+        .iter()
+        .map(|violation| {
+            let obj = Object::new();
+            let _ = Reflect::set(&obj, &"path".into(), &violation.path.to_string().into());
+            let _ = Reflect::set(&obj, &"message".into(), &violation.to_string().into());
+            if let Some(span) = violation.span {
+                let Location(line, column, _) = Location::of(str, span.0);
+                let _ = Reflect::set(&obj, &"line".into(), &line.into());
+                let _ = Reflect::set(&obj, &"column".into(), &column.into());
+            }
+            JsValue::from(obj)
+        })
+        .collect())
+}
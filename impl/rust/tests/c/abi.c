@@ -0,0 +1,130 @@
+#include <stdio.h>
+#include <string.h>
+
+#include "fennec_abi.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                                 \
+    do {                                                                            \
+        if (!(cond)) {                                                              \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+            failures++;                                                             \
+        }                                                                           \
+    } while (0)
+
+/// Everything has to be reachable through the accessors alone, since fennec_value_t has no fields.
+static void check_access(void) {
+    fennec_value_t *fen = fennec_parse("server { name = \"a\" ports [ 80 443 ] } debug = true", NULL);
+    CHECK(fen != NULL);
+    CHECK(fennec_value_type(fen) == FENNEC_TYPE_OBJECT);
+
+    const fennec_value_t *server = fennec_object_get(fen, "server");
+    size_t found = 0;
+    for (size_t i = 0; i < fennec_value_len(server); i++) {
+        const char *key = fennec_object_key(server, i);
+        if (strcmp(key, "ports") == 0) {
+            CHECK(fennec_value_at(server, i) == fennec_value_get(fen, "server.ports"));
+            found++;
+        }
+    }
+    CHECK(found == 1);
+
+    int64_t port = 0;
+    CHECK(fennec_value_int(fennec_value_get(fen, "server.ports[1]"), &port) && port == 443);
+
+    const char *name = NULL;
+    size_t len = 0;
+    CHECK(fennec_value_string(fennec_value_get(fen, "server.name"), &name, &len));
+    CHECK(strcmp(name, "a") == 0 && len == 1);
+
+    bool debug = false;
+    CHECK(fennec_value_bool(fennec_object_get(fen, "debug"), &debug) && debug);
+    CHECK(!fennec_value_float(fennec_object_get(fen, "debug"), NULL));
+    CHECK(fennec_value_get(fen, "server.missing") == NULL);
+
+    fennec_value_free(fen);
+}
+
+static void check_big_ints(void) {
+    fennec_value_t *fen = fennec_parse("mask = 0xFFFFFFFFFFFFFFFF huge = 1x10000000000000000", NULL);
+    CHECK(fen != NULL);
+
+    const fennec_value_t *mask = fennec_object_get(fen, "mask");
+    uint64_t u = 0;
+    CHECK(fennec_value_type(mask) == FENNEC_TYPE_BIGINT);
+    CHECK(!fennec_value_int(mask, NULL));
+    CHECK(fennec_value_uint(mask, &u) && u == UINT64_MAX);
+
+    const fennec_value_t *huge = fennec_object_get(fen, "huge");
+    const char *digits = NULL;
+    CHECK(!fennec_value_uint(huge, NULL));
+    CHECK(fennec_value_bigint(huge, &digits) && strcmp(digits, "-18446744073709551616") == 0);
+
+    fennec_value_free(fen);
+}
+
+static void check_dates(void) {
+    fennec_value_t *fen = fennec_parse("at = 1970-01-02T01:00:00+01:00 day = 2024-02-29 wait = 1m30s", NULL);
+    CHECK(fen != NULL);
+
+    const fennec_value_t *at = fennec_object_get(fen, "at");
+    int64_t secs = 0;
+    uint32_t nanos = 1;
+    CHECK(fennec_value_type(at) == FENNEC_TYPE_DATETIME);
+    CHECK(fennec_value_timestamp(at, &secs, &nanos) && secs == 86400 && nanos == 0);
+
+    const fennec_value_t *day = fennec_object_get(fen, "day");
+    const char *text = NULL;
+    CHECK(fennec_value_type(day) == FENNEC_TYPE_DATE);
+    CHECK(fennec_value_datetime(day, &text) && strcmp(text, "2024-02-29") == 0);
+    CHECK(!fennec_value_timestamp(day, NULL, NULL));
+
+    const fennec_value_t *wait = fennec_object_get(fen, "wait");
+    uint64_t wait_secs = 0;
+    CHECK(fennec_value_type(wait) == FENNEC_TYPE_DURATION);
+    CHECK(fennec_value_duration(wait, &wait_secs, NULL) && wait_secs == 90);
+
+    fennec_value_free(fen);
+}
+
+static void check_quantities(void) {
+    fennec_value_t *fen = fennec_parse("cache = 512MiB link = 1.5Gbps", NULL);
+    CHECK(fen != NULL);
+
+    const fennec_value_t *cache = fennec_object_get(fen, "cache");
+    uint64_t bytes = 0;
+    CHECK(fennec_value_type(cache) == FENNEC_TYPE_QUANTITY);
+    CHECK(fennec_value_bytes(cache, &bytes) && bytes == 536870912);
+
+    const fennec_value_t *link = fennec_object_get(fen, "link");
+    const char *text = NULL;
+    CHECK(fennec_value_quantity(link, &text) && strcmp(text, "1.5Gbps") == 0);
+    CHECK(!fennec_value_bytes(link, &bytes));
+
+    fennec_value_free(fen);
+}
+
+static void check_errors(void) {
+    fennec_error_t *error = NULL;
+    CHECK(fennec_parse("a = 1\nb = ]", &error) == NULL);
+    CHECK(error != NULL);
+    CHECK(strcmp(fennec_error_message(error), "unexpected ']'") == 0);
+    CHECK(fennec_error_line(error) == 2 && fennec_error_column(error) == 5);
+    fennec_error_free(error);
+}
+
+int main(void) {
+    CHECK(fennec_abi_version() >= FENNEC_ABI_VERSION);
+    check_access();
+    check_big_ints();
+    check_dates();
+    check_quantities();
+    check_errors();
+
+    if (failures != 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+    return 0;
+}
@@ -0,0 +1,190 @@
+#include <stdio.h>
+#include <string.h>
+
+#include "fennec.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                                 \
+    do {                                                                            \
+        if (!(cond)) {                                                              \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+            failures++;                                                             \
+        }                                                                           \
+    } while (0)
+
+/// Walking the structs by hand has to land on the same values the lookup functions find.
+static void check_layout(void) {
+    const FennecConfig_FennecValue *fen =
+        FennecConfig_ParseString("a = 1 b = 2.5 c = true d = \"e\" f [ 1 2 3 ] g = null");
+
+    CHECK(fen->type == FennecConfig_FennecValue_Type_Object);
+    CHECK(fen->value.object.len == 6);
+    for (size_t i = 0; i < fen->value.object.len; i++) {
+        CHECK(&fen->value.object.values[i] == FennecConfig_ObjectGet(fen, fen->value.object.keys[i]));
+    }
+
+    const FennecConfig_FennecValue *a = FennecConfig_ObjectGet(fen, "a");
+    CHECK(a->type == FennecConfig_FennecValue_Type_Int && a->value.i == 1);
+
+    const FennecConfig_FennecValue *b = FennecConfig_ObjectGet(fen, "b");
+    CHECK(b->type == FennecConfig_FennecValue_Type_Float && b->value.f == 2.5);
+
+    const FennecConfig_FennecValue *c = FennecConfig_ObjectGet(fen, "c");
+    CHECK(c->type == FennecConfig_FennecValue_Type_Bool && c->value.b);
+
+    const FennecConfig_FennecValue *d = FennecConfig_ObjectGet(fen, "d");
+    CHECK(d->type == FennecConfig_FennecValue_Type_String && strcmp(d->value.string, "e") == 0);
+
+    const FennecConfig_FennecValue *f = FennecConfig_ObjectGet(fen, "f");
+    CHECK(f->type == FennecConfig_FennecValue_Type_Array && f->value.array.len == 3);
+    CHECK(&f->value.array.arr[2] == FennecConfig_Get(fen, "f[2]"));
+    CHECK(f->value.array.arr[2].value.i == 3);
+
+    CHECK(FennecConfig_ObjectGet(fen, "g")->type == FennecConfig_FennecValue_Type_Null);
+
+    FennecConfig_FennecValue_Free(fen);
+}
+
+static void check_access(void) {
+    const FennecConfig_FennecValue *fen =
+        FennecConfig_ParseString("server { host = \"localhost\" ports [ 80 443 ] -debug ratio = 1 }");
+
+    const char *host = NULL;
+    int64_t port = 0;
+    double ratio = 0;
+    bool debug = false;
+    CHECK(FennecConfig_GetString(fen, "server.host", &host) && strcmp(host, "localhost") == 0);
+    CHECK(FennecConfig_GetInt(fen, "server.ports[1]", &port) && port == 443);
+    CHECK(FennecConfig_GetFloat(fen, "server.ratio", &ratio) && ratio == 1.0);
+    CHECK(FennecConfig_GetBool(fen, "server.debug", &debug) && debug);
+    CHECK(!FennecConfig_GetInt(fen, "server.host", &port) && port == 443);
+
+    const FennecConfig_FennecValue *ports = FennecConfig_Get(fen, "server.ports");
+    FennecConfig_Iter iter = FennecConfig_Iterate(ports);
+    const char *key;
+    const FennecConfig_FennecValue *value;
+    int64_t sum = 0;
+    while (FennecConfig_IterNext(&iter, &key, &value)) {
+        CHECK(key == NULL);
+        sum += FennecConfig_FennecValue_GetInt(value);
+    }
+    CHECK(sum == 523);
+
+    const FennecConfig_FennecValue_Object *server = FennecConfig_FennecValue_GetObject(FennecConfig_Get(fen, "server"));
+    CHECK(server != NULL && FennecConfig_FennecValue_Object_HasKey(server, "ports"));
+    const FennecConfig_FennecValue_Array *arr = FennecConfig_FennecValue_GetArray(ports);
+    CHECK(FennecConfig_FennecValue_Array_Len(arr) == 2);
+    CHECK(FennecConfig_FennecValue_GetNumber(FennecConfig_FennecValue_Array_GetIdx(arr, 0)) == 80.0);
+    CHECK(FennecConfig_FennecValue_GetArray(fen) == NULL);
+
+    FennecConfig_FennecValue_Free(fen);
+}
+
+static void check_big_ints(void) {
+    const FennecConfig_FennecValue *fen =
+        FennecConfig_ParseString("mask = 0xFFFFFFFFFFFFFFFF huge = 99999999999999999999 port = 80");
+
+    int64_t i = 0;
+    uint64_t u = 0;
+    const char *digits = NULL;
+    CHECK(!FennecConfig_GetInt(fen, "mask", &i));
+    CHECK(FennecConfig_GetUInt(fen, "mask", &u) && u == UINT64_MAX);
+    CHECK(FennecConfig_GetUInt(fen, "port", &u) && u == 80);
+    CHECK(!FennecConfig_GetUInt(fen, "huge", &u) && u == 80);
+    CHECK(FennecConfig_GetBigInt(fen, "huge", &digits) && strcmp(digits, "99999999999999999999") == 0);
+    CHECK(!FennecConfig_GetBigInt(fen, "port", &digits));
+
+    FennecConfig_FennecValue_Free(fen);
+}
+
+static void check_dates(void) {
+    const FennecConfig_FennecValue *fen =
+        FennecConfig_ParseString("at = 1970-01-01T00:01:00Z local = 1970-01-01T00:01:00 wait = 2s500ms");
+
+    const char *text = NULL;
+    int64_t secs = 0;
+    uint32_t nanos = 0;
+    CHECK(FennecConfig_GetDateTime(fen, "local", &text) && strcmp(text, "1970-01-01T00:01:00") == 0);
+    CHECK(FennecConfig_GetTimestamp(fen, "at", &secs, &nanos) && secs == 60 && nanos == 0);
+    CHECK(!FennecConfig_GetTimestamp(fen, "local", &secs, &nanos));
+
+    uint64_t wait = 0;
+    CHECK(FennecConfig_GetDuration(fen, "wait", &wait, &nanos) && wait == 2 && nanos == 500000000);
+    CHECK(!FennecConfig_GetDuration(fen, "at", &wait, NULL));
+
+    FennecConfig_FennecValue_Free(fen);
+}
+
+static void check_quantities(void) {
+    const FennecConfig_FennecValue *fen = FennecConfig_ParseString("cache = 512MiB link = 1.5Gbps");
+
+    const char *text = NULL;
+    uint64_t bytes = 0;
+    CHECK(FennecConfig_GetQuantity(fen, "link", &text) && strcmp(text, "1.5Gbps") == 0);
+    CHECK(FennecConfig_GetBytes(fen, "cache", &bytes) && bytes == 536870912);
+    CHECK(!FennecConfig_GetBytes(fen, "link", &bytes));
+
+    FennecConfig_FennecValue_Free(fen);
+}
+
+static void check_errors(void) {
+    const FennecConfig_Error *error = NULL;
+    const FennecConfig_FennecValue *fen = FennecConfig_ParseStringEx("a = 1\nb = ]", &error);
+
+    CHECK(fen == NULL);
+    CHECK(error != NULL);
+    CHECK(strcmp(FennecConfig_Error_Message(error), "unexpected ']'") == 0);
+    CHECK(FennecConfig_Error_Line(error) == 2);
+    CHECK(FennecConfig_Error_Column(error) == 5);
+    CHECK(FennecConfig_Error_Offset(error) == 10);
+
+    FennecConfig_Error_Free(error);
+}
+
+static void check_building(void) {
+    FennecConfig_Value *obj = FennecConfig_NewObject();
+    FennecConfig_Value *arr = FennecConfig_NewArray();
+    CHECK(FennecConfig_ArrayPush(arr, FennecConfig_NewInt(1)));
+    CHECK(FennecConfig_ArrayPush(arr, FennecConfig_NewFloat(2.5)));
+    CHECK(FennecConfig_ObjectSet(obj, "list", arr));
+    CHECK(FennecConfig_ObjectSet(obj, "name", FennecConfig_NewString("fennec")));
+    CHECK(!FennecConfig_ArrayPush(obj, FennecConfig_NewNull()));
+    CHECK(FennecConfig_ObjectSet(obj, "since", FennecConfig_NewDateTime("2024-02-29")));
+    CHECK(FennecConfig_ObjectSet(obj, "wait", FennecConfig_NewDuration(90, 0)));
+    CHECK(FennecConfig_NewDateTime("2023-02-30") == NULL);
+    CHECK(FennecConfig_ObjectSet(obj, "limit", FennecConfig_NewQuantity("2GB")));
+    CHECK(FennecConfig_NewQuantity("2gb") == NULL);
+    FennecConfig_Value *server = FennecConfig_NewObject();
+    CHECK(FennecConfig_ObjectSet(server, "port", FennecConfig_NewInt(80)));
+    CHECK(FennecConfig_ObjectSet(obj, "server", server));
+
+    FennecConfig_StringifyOptions options = {.indent = 2, .sort_keys = true, .dotted_keys = true};
+    char *str = FennecConfig_Stringify(obj, &options);
+    CHECK(strcmp(str, "limit = 2GB\nlist [\n  1\n  2.5\n]\nname = \"fennec\"\nserver.port = 80\nsince = 2024-02-29\nwait = 1m30s\n") == 0);
+
+    const FennecConfig_FennecValue *fen = FennecConfig_ParseString(str);
+    int64_t first = 0;
+    CHECK(FennecConfig_GetInt(fen, "list[0]", &first) && first == 1);
+    CHECK(FennecConfig_GetInt(fen, "server.port", &first) && first == 80);
+
+    FennecConfig_FennecValue_Free(fen);
+    FennecConfig_String_Free(str);
+    FennecConfig_Value_Free(obj);
+}
+
+int main(void) {
+    check_layout();
+    check_access();
+    check_big_ints();
+    check_dates();
+    check_quantities();
+    check_errors();
+    check_building();
+
+    if (failures != 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+    return 0;
+}
@@ -0,0 +1,40 @@
+#![cfg(unix)]
+
+use std::{env, path::PathBuf, process::Command};
+
+/**
+ * Compiles `tests/c/api.c` against the generated `fennec.h` and the static library, then runs it
+ */
+#[test]
+fn c_api() {
+    compile_and_run("api");
+}
+
+/**
+ * Same for `tests/c/abi.c`, which only sees the opaque handles in `fennec_abi.h`
+ */
+#[test]
+fn c_abi() {
+    compile_and_run("abi");
+}
+
+fn compile_and_run(name: &str) {
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // The static library sits next to the test binaries, in target/<profile>/deps
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let exe = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("c_{name}"));
+
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .args(["-std=c11", "-Wall", "-Wextra", "-Werror", "-I"])
+        .arg(env!("OUT_DIR"))
+        .arg(manifest.join(format!("tests/c/{name}.c")))
+        .arg(deps.join("libfennec.a"))
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&exe)
+        .status()
+        .expect("Couldn't run the C compiler");
+    assert!(status.success(), "tests/c/{name}.c didn't compile");
+
+    let status = Command::new(&exe).status().unwrap();
+    assert!(status.success(), "tests/c/{name}.c failed");
+}
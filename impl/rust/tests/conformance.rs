@@ -0,0 +1,172 @@
+use std::{
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+};
+
+use fennec::{
+    datetime::{self, Date, DateTime, Time},
+    parse::FennecType,
+    path::Path as FennecPath,
+    quantity::Quantity,
+    BigInt,
+};
+use serde_json::Value;
+
+/**
+ * Runs the corpus in `conformance/` at the root of the repo, which every implementation is checked against.
+ * Each `valid/<name>.fennec` has to parse to the value in `valid/<name>.json`,
+ * and each `invalid/<name>.fennec` has to fail to parse.
+ */
+#[test]
+fn conformance() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../conformance");
+    let mut failures = Vec::new();
+
+    for input in cases(&root.join("valid")) {
+        let expected = input.with_extension("json");
+        let expected: Value = match fs::read_to_string(&expected) {
+            Ok(json) => serde_json::from_str(&json).unwrap(),
+            Err(e) => {
+                failures.push(format!("{}: {e}", name(&root, &expected)));
+                continue;
+            }
+        };
+        let result = match parse(&input) {
+            Ok(Ok(value)) => compare(&expected, &value, &FennecPath::new()),
+            Ok(Err(e)) => Err(format!("didn't parse: {e}")),
+            Err(panic) => Err(panic),
+        };
+        if let Err(e) = result {
+            failures.push(format!("{}: {e}", name(&root, &input)));
+        }
+    }
+
+    for input in cases(&root.join("invalid")) {
+        let result = match parse(&input) {
+            Ok(Ok(value)) => Err(format!("parsed to {value:?}")),
+            Ok(Err(_)) => Ok(()),
+            Err(panic) => Err(panic),
+        };
+        if let Err(e) = result {
+            failures.push(format!("{}: {e}", name(&root, &input)));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} conformance cases failed:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+fn cases(dir: &Path) -> Vec<PathBuf> {
+    let mut cases: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "fennec"))
+        .collect();
+    cases.sort();
+    cases
+}
+
+fn name(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/**
+ * A panic is reported like any other failure, so one bad case doesn't hide the rest
+ */
+fn parse(path: &Path) -> Result<Result<FennecType, String>, String> {
+    let input = fs::read_to_string(path).unwrap();
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        fennec::parse(&input).map_err(|e| e.to_string())
+    }))
+    .map_err(|panic| match panic.downcast_ref::<&str>() {
+        Some(msg) => format!("panicked: {msg}"),
+        None => match panic.downcast_ref::<String>() {
+            Some(msg) => format!("panicked: {msg}"),
+            None => "panicked".to_string(),
+        },
+    })
+}
+
+/**
+ * Objects and arrays are plain JSON. Everything else is `{ "type": ..., "value": ... }`,
+ * with the value written as a string so ints and floats keep their exact text,
+ * except null which is just `{ "type": "null" }`.
+ */
+fn compare(expected: &Value, actual: &FennecType, path: &FennecPath) -> Result<(), String> {
+    let at = if path.is_root() {
+        "the root".to_string()
+    } else {
+        path.to_string()
+    };
+
+    if let Value::Array(expected) = expected {
+        let FennecType::Array(actual) = actual else {
+            return Err(format!("expected an array at {at}, found {actual:?}"));
+        };
+        if expected.len() != actual.len() {
+            return Err(format!(
+                "expected {} elements at {at}, found {}",
+                expected.len(),
+                actual.len()
+            ));
+        }
+        for (i, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+            compare(expected, actual, &path.join_index(i))?;
+        }
+        return Ok(());
+    }
+
+    let Value::Object(expected) = expected else {
+        return Err(format!("{at} isn't an object or array in the .json"));
+    };
+    let (Some(Value::String(ty)), value) = (expected.get("type"), expected.get("value")) else {
+        let FennecType::Object(actual) = actual else {
+            return Err(format!("expected an object at {at}, found {actual:?}"));
+        };
+        for key in actual.keys() {
+            if !expected.contains_key(key) {
+                return Err(format!("unexpected key \"{key}\" at {at}"));
+            }
+        }
+        for (key, expected) in expected {
+            let Some(actual) = actual.get(key) else {
+                return Err(format!("missing key \"{key}\" at {at}"));
+            };
+            compare(expected, actual, &path.join_key(key))?;
+        }
+        return Ok(());
+    };
+
+    let value = value.and_then(Value::as_str).unwrap_or_default();
+    let matches = match (ty.as_str(), actual) {
+        ("string", FennecType::String(str)) => str == value,
+        ("int", FennecType::Int(_) | FennecType::BigInt(_)) => {
+            value.parse::<BigInt>().ok() == actual.as_big_int()
+        }
+        ("float", FennecType::Float(f)) => match value.parse::<f64>() {
+            Ok(expected) if expected.is_nan() => f.is_nan(),
+            Ok(expected) => expected == *f,
+            Err(_) => false,
+        },
+        ("bool", FennecType::Bool(b)) => value.parse() == Ok(*b),
+        ("null", FennecType::Null) => true,
+        ("datetime", FennecType::DateTime(d)) => value.parse::<DateTime>() == Ok(*d),
+        ("date", FennecType::Date(d)) => value.parse::<Date>() == Ok(*d),
+        ("time", FennecType::Time(t)) => value.parse::<Time>() == Ok(*t),
+        ("duration", FennecType::Duration(d)) => datetime::parse_duration(value) == Some(*d),
+        ("quantity", FennecType::Quantity(q)) => value.parse::<Quantity>() == Ok(*q),
+        _ => false,
+    };
+    if !matches {
+        return Err(format!("expected {ty} {value:?} at {at}, found {actual:?}"));
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use fennec::{convert::ConvertError, parse::FennecType};
+use fennec_derive::FennecConfig;
+
+#[derive(Debug, PartialEq, FennecConfig)]
+enum Mode {
+    #[fennec(rename = "dev")]
+    Development,
+    #[fennec(rename = "prod")]
+    Production,
+    Custom(String),
+    Pair(i64, i64),
+    Limits {
+        cpu: f64,
+        memory: u32,
+    },
+}
+
+#[derive(Debug, PartialEq, FennecConfig)]
+struct Port(u16);
+
+#[derive(Debug, PartialEq, FennecConfig)]
+struct Server {
+    host: String,
+    #[fennec(rename = "listen")]
+    port: Port,
+    #[fennec(default = "default_workers")]
+    workers: u8,
+    #[fennec(default)]
+    tags: Vec<String>,
+    #[fennec(flag)]
+    debug: bool,
+    timeout: Option<f32>,
+    mode: Mode,
+    r#type: String,
+}
+
+fn default_workers() -> u8 {
+    4
+}
+
+#[derive(Debug, PartialEq, FennecConfig)]
+struct Config {
+    servers: Vec<Server>,
+    extra: HashMap<String, FennecType>,
+}
+
+fn load(str: &str) -> Result<Config, ConvertError> {
+    Config::try_from(&fennec::parse(str).unwrap())
+}
+
+#[test]
+fn loads_documents() {
+    let config = load(
+        "servers [
+            { host = \"a\" listen = 80 mode = \"prod\" type = \"web\" -debug timeout = 1.5 }
+            { host = \"b\" listen = 81 tags [ \"x\" ] type = \"db\" mode { Limits { cpu = 1 memory = 512 } } }
+            { host = \"c\" listen = 82 type = \"db\" mode { Pair [ 1 2 ] } workers = 1 }
+        ]
+        extra { anything = null }",
+    )
+    .unwrap();
+
+    let servers = &config.servers;
+    assert_eq!(servers[0].port, Port(80));
+    assert_eq!(servers[0].workers, 4);
+    assert!(servers[0].debug);
+    assert_eq!(servers[0].timeout, Some(1.5));
+    assert_eq!(servers[0].mode, Mode::Production);
+    assert_eq!(servers[0].r#type, "web");
+    assert!(!servers[1].debug);
+    assert_eq!(servers[1].tags, vec!["x"]);
+    assert_eq!(servers[1].timeout, None);
+    assert_eq!(
+        servers[1].mode,
+        Mode::Limits {
+            cpu: 1.0,
+            memory: 512
+        }
+    );
+    assert_eq!(servers[2].mode, Mode::Pair(1, 2));
+    assert_eq!(servers[2].workers, 1);
+    assert_eq!(config.extra["anything"], FennecType::Null);
+}
+
+#[test]
+fn reports_failing_paths() {
+    let err = load("servers [ { host = \"a\" listen = 80 mode = \"dev\" type = \"web\" } { host = \"b\" listen = -1 } ] extra { }")
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ConvertError::OutOfRange((-1).into(), "servers[1].listen".parse().unwrap())
+    );
+
+    let err = load("servers [ { listen = 80 } ] extra { }").unwrap_err();
+    assert_eq!(err.to_string(), "missing key servers[0].host");
+
+    let err = load(
+        "servers [ { host = \"a\" listen = 80 type = \"web\" mode { Limits { cpu = \"x\" memory = 1 } } } ] extra { }",
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "expected float at servers[0].mode.Limits.cpu, found string"
+    );
+
+    let err =
+        load("servers [ { host = \"a\" listen = 80 type = \"web\" mode = \"test\" } ] extra { }")
+            .unwrap_err();
+    assert_eq!(
+        err,
+        ConvertError::UnknownVariant("test".to_string(), "servers[0].mode".parse().unwrap())
+    );
+}
+
+#[test]
+fn round_trips() {
+    let server = Server {
+        host: "a".to_string(),
+        port: Port(80),
+        workers: 2,
+        tags: vec![],
+        debug: false,
+        timeout: None,
+        mode: Mode::Custom("x".to_string()),
+        r#type: "web".to_string(),
+    };
+
+    let fen = FennecType::from(server);
+    let obj = fen.as_object().unwrap();
+    assert!(!obj.contains_key("debug"));
+    assert!(!obj.contains_key("timeout"));
+    assert_eq!(obj["listen"], FennecType::Int(80));
+    assert_eq!(
+        obj["mode"].get_key("Custom"),
+        Some(&FennecType::String("x".to_string()))
+    );
+
+    let reparsed = fennec::parse(&fen.to_string()).unwrap();
+    let server = Server::try_from(&reparsed).unwrap();
+    assert_eq!(server.workers, 2);
+    assert_eq!(server.mode, Mode::Custom("x".to_string()));
+    assert_eq!(
+        FennecType::from(Mode::Development),
+        FennecType::String("dev".to_string())
+    );
+}
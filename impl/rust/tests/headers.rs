@@ -0,0 +1,18 @@
+use std::{fs, path::Path};
+
+/**
+ * `build.rs` generates the headers into `OUT_DIR`. The copies next to `Cargo.toml` are the ones
+ * people include, so they have to be kept in step with the sources.
+ */
+#[test]
+fn headers_are_up_to_date() {
+    for header in ["fennec.h", "fennec_abi.h"] {
+        let generated = Path::new(env!("OUT_DIR")).join(header);
+        let committed = Path::new(env!("CARGO_MANIFEST_DIR")).join(header);
+        assert!(
+            fs::read_to_string(&generated).unwrap() == fs::read_to_string(&committed).unwrap(),
+            "{header} is out of date, copy it over from {}",
+            generated.display()
+        );
+    }
+}
@@ -0,0 +1,157 @@
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use fennec::wasm::{parse, stringify, validate};
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn options(numbers: &str) -> JsValue {
+    let obj = Object::new();
+    Reflect::set(&obj, &"numbers".into(), &numbers.into()).unwrap();
+    Reflect::set(&obj, &"sortKeys".into(), &true.into()).unwrap();
+    obj.into()
+}
+
+fn get(value: &JsValue, key: &str) -> JsValue {
+    Reflect::get(value, &key.into()).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn parses_to_plain_values() {
+    let value = parse(
+        "a = 1 b = 2.5 c [ true null ] d { e = \"f\" }",
+        JsValue::UNDEFINED,
+    )
+    .map_err(JsValue::from)
+    .unwrap();
+
+    assert_eq!(get(&value, "a").as_f64(), Some(1.0));
+    assert_eq!(get(&value, "b").as_f64(), Some(2.5));
+    let c = Array::from(&get(&value, "c"));
+    assert_eq!((c.get(0).as_bool(), c.get(1).is_null()), (Some(true), true));
+    assert_eq!(
+        get(&get(&value, "d"), "e").as_string().as_deref(),
+        Some("f")
+    );
+
+    assert!(parse("a = ]", JsValue::UNDEFINED).is_err());
+}
+
+#[wasm_bindgen_test]
+fn keeps_ints_apart() {
+    let doc = "big = 9007199254740993 half = 0.5";
+
+    let value = parse(doc, options("bigint"))
+        .map_err(JsValue::from)
+        .unwrap();
+    assert!(get(&value, "big").is_bigint());
+    assert_eq!(get(&value, "half").as_f64(), Some(0.5));
+
+    let value = parse(doc, options("tagged"))
+        .map_err(JsValue::from)
+        .unwrap();
+    let big = get(&value, "big");
+    assert_eq!(get(&big, "type").as_string().as_deref(), Some("int"));
+    assert_eq!(
+        get(&big, "value").as_string().as_deref(),
+        Some("9007199254740993")
+    );
+
+    let str = stringify(value, options("tagged"))
+        .map_err(JsValue::from)
+        .unwrap();
+    assert_eq!(fennec::parse(&str).unwrap(), fennec::parse(doc).unwrap());
+}
+
+#[wasm_bindgen_test]
+fn rejects_tagged_ints_that_arent_whole() {
+    for value in [1.5, f64::NAN, f64::INFINITY] {
+        let int = Object::new();
+        Reflect::set(&int, &"type".into(), &"int".into()).unwrap();
+        Reflect::set(&int, &"value".into(), &value.into()).unwrap();
+        assert!(stringify(int.into(), options("tagged")).is_err());
+    }
+}
+
+#[wasm_bindgen_test]
+fn validates() {
+    let schema = "type = \"object\" keys { port { type = \"int\" max = 65535 } }";
+    let violations = validate("port = 70000", schema)
+        .map_err(JsValue::from)
+        .unwrap();
+
+    assert_eq!(violations.length(), 1);
+    let violation = violations.get(0);
+    assert_eq!(get(&violation, "path").as_string().as_deref(), Some("port"));
+    assert_eq!(get(&violation, "line").as_f64(), Some(1.0));
+    assert!(
+        validate("port = 80", schema)
+            .map_err(JsValue::from)
+            .unwrap()
+            .length()
+            == 0
+    );
+}
+
+#[wasm_bindgen_test]
+fn round_trips_big_ints() {
+    let doc = "mask = 0xFFFFFFFFFFFFFFFF";
+    for numbers in ["bigint", "tagged"] {
+        let value = parse(doc, options(numbers))
+            .map_err(JsValue::from)
+            .unwrap();
+        let str = stringify(value, options(numbers))
+            .map_err(JsValue::from)
+            .unwrap();
+        assert_eq!(str, "mask = 18446744073709551615\n");
+    }
+}
+
+#[wasm_bindgen_test]
+fn round_trips_dates_and_durations() {
+    let doc = "at = 1979-05-27T07:32:00Z wait = 1m30s";
+    let value = parse(doc, JsValue::UNDEFINED)
+        .map_err(JsValue::from)
+        .unwrap();
+    let wait = get(&value, "wait");
+    assert_eq!(get(&wait, "type").as_string().as_deref(), Some("duration"));
+    assert_eq!(get(&wait, "value").as_string().as_deref(), Some("1m30s"));
+
+    let str = stringify(value.clone(), options("tagged"))
+        .map_err(JsValue::from)
+        .unwrap();
+    assert_eq!(str, "at = 1979-05-27T07:32:00Z\nwait = 1m30s\n");
+
+    // Without tags turned on, an object that happens to look like one stays an object
+    let str = stringify(wait, options("number"))
+        .map_err(JsValue::from)
+        .unwrap();
+    assert_eq!(str, "type = \"duration\"\nvalue = \"1m30s\"\n");
+}
+
+#[wasm_bindgen_test]
+fn writes_dotted_keys() {
+    let value = parse("server { http { port = 80 } }", JsValue::UNDEFINED)
+        .map_err(JsValue::from)
+        .unwrap();
+    let options = options("number");
+    Reflect::set(&options, &"dottedKeys".into(), &true.into()).unwrap();
+
+    let str = stringify(value, options).map_err(JsValue::from).unwrap();
+    assert_eq!(str, "server.http.port = 80\n");
+}
+
+#[wasm_bindgen_test]
+fn round_trips_quantities() {
+    let value = parse("limit = 1.5GiB", options("bigint"))
+        .map_err(JsValue::from)
+        .unwrap();
+    let limit = get(&value, "limit");
+    assert_eq!(get(&limit, "type").as_string().as_deref(), Some("quantity"));
+    assert_eq!(get(&limit, "value").as_string().as_deref(), Some("1.5GiB"));
+
+    let str = stringify(value, options("tagged"))
+        .map_err(JsValue::from)
+        .unwrap();
+    assert_eq!(str, "limit = 1.5GiB\n");
+}